@@ -1,82 +1,442 @@
+use crate::cli_args::parse_endpoint;
+use crate::key_source::{KeySource, DEFAULT_PASSPHRASE_ENV};
+use crate::layered_config::LayeredConfig;
+use crate::planner::BulkEndpoints;
+use alphavantage_client::{
+    http_settings::parse_timeout, CachePolicy, HttpSettings, KeyStrategy, RateLimits, RateWindow,
+    ResetRule, ResponseCache,
+};
+use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol, YearQuarter};
 use anyhow::{Context, Result};
 use serde::Deserialize;
-use std::fs;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 /// Configuration loaded from alphavantage.toml file
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct ApiConfig {
+    #[serde(default)]
     pub api: ApiSection,
+    #[serde(default)]
     pub rate_limit: RateLimitSection,
+    #[serde(default)]
+    pub cache: CacheSection,
+    #[serde(default)]
+    pub http: HttpSection,
+    #[serde(default)]
+    pub bulk: BulkSection,
+    /// Named symbol lists for `--watchlist`, e.g. `semis = ["NVDA", "MU"]`
+    #[serde(default)]
+    pub watchlists: BTreeMap<String, Vec<TickerSymbol>>,
+    #[serde(default)]
+    pub transcripts: TranscriptsSection,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct ApiSection {
+    #[serde(default)]
     pub api_key: String,
+    /// File holding the key, relative to the config file
+    #[serde(default)]
+    pub api_key_file: Option<PathBuf>,
+    /// Environment variable holding the key
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    /// Shell command printing the key, e.g. `"op read op://vault/alphavantage/key"`
+    #[serde(default)]
+    pub api_key_cmd: Option<String>,
+    /// File encrypted with `age --passphrase`, relative to the config file
+    #[serde(default)]
+    pub api_key_age_file: Option<PathBuf>,
+    /// Environment variable holding the age passphrase
+    /// (default `ALPHA_VANTAGE_KEY_PASSPHRASE`)
+    #[serde(default)]
+    pub api_key_passphrase_env: Option<String>,
+    /// Optional pool of keys, tried in turn according to `key_strategy`
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    #[serde(default)]
+    pub key_strategy: KeyStrategy,
+}
+
+impl ApiSection {
+    /// Where the primary key comes from, if any.
+    ///
+    /// # Errors
+    /// Returns error if more than one of `api_key`, `api_key_file`, `api_key_env`,
+    /// `api_key_cmd` and `api_key_age_file` is set.
+    pub fn key_source(&self) -> Result<Option<KeySource>> {
+        let passphrase_env = self
+            .api_key_passphrase_env
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PASSPHRASE_ENV.to_string());
+        let sources: Vec<KeySource> = [
+            Some(self.api_key.clone())
+                .filter(|key| !key.is_empty())
+                .map(KeySource::Inline),
+            self.api_key_file.clone().map(KeySource::File),
+            self.api_key_env.clone().map(KeySource::Env),
+            self.api_key_cmd.clone().map(KeySource::Command),
+            self.api_key_age_file.clone().map(|file| KeySource::Age {
+                file,
+                passphrase_env,
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        match sources.as_slice() {
+            [] => Ok(None),
+            [source] => Ok(Some(source.clone())),
+            _ => anyhow::bail!(
+                "Set only one of api_key, api_key_file, api_key_env, api_key_cmd and \
+                 api_key_age_file (found {})",
+                sources
+                    .iter()
+                    .map(KeySource::describe)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// Resolves the primary key (running `api_key_cmd` or decrypting
+    /// `api_key_age_file` if that is its source), followed by the `api_keys` pool.
+    ///
+    /// # Errors
+    /// Returns error if the primary key source fails.
+    pub fn resolve_keys(&self) -> Result<Vec<ApiKey>> {
+        let mut keys = Vec::new();
+        if let Some(source) = self.key_source()? {
+            let key = source.resolve().with_context(|| {
+                format!("Failed to read the API key from {}", source.describe())
+            })?;
+            keys.push(key);
+        }
+        for key in &self.api_keys {
+            if !keys.iter().any(|k| k.secret() == key) {
+                keys.push(ApiKey::new(key.clone()));
+            }
+        }
+        Ok(keys)
+    }
+
+    /// All configured keys: `api_key` first (if set), then the `api_keys` pool.
+    #[must_use]
+    pub fn all_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        if !self.api_key.is_empty() {
+            keys.push(self.api_key.clone());
+        }
+        for key in &self.api_keys {
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        keys
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct RateLimitSection {
-    pub daily_limit: u32,
+    /// Calendar-day quota. Premium plans without a daily cap can omit it.
+    #[serde(default)]
+    pub daily_limit: Option<u32>,
+    /// When the daily quota resets, e.g. `{ timezone = "America/New_York", at = "00:00" }`
+    /// or `{ mode = "rolling" }`. Defaults to midnight UTC.
+    #[serde(default)]
+    pub reset: ResetRule,
     #[serde(default = "default_min_delay")]
     pub min_delay_ms: u64,
+    /// Sliding windows, e.g. `{ limit = 75, per = "minute" }`
+    #[serde(default)]
+    pub windows: Vec<RateWindow>,
+    /// Longest time to sleep waiting for a window before failing the call
+    #[serde(default = "default_max_wait")]
+    pub max_wait_ms: u64,
 }
 
 const fn default_min_delay() -> u64 {
     1000
 }
 
-impl ApiConfig {
-    /// Load configuration from alphavantage.toml in the project root
+const fn default_max_wait() -> u64 {
+    300_000
+}
+
+impl Default for RateLimitSection {
+    fn default() -> Self {
+        Self {
+            daily_limit: None,
+            reset: ResetRule::default(),
+            min_delay_ms: default_min_delay(),
+            windows: Vec::new(),
+            max_wait_ms: default_max_wait(),
+        }
+    }
+}
+
+impl RateLimitSection {
+    /// Converts the file section into the limits enforced by the client.
+    #[must_use]
+    pub fn to_rate_limits(&self) -> RateLimits {
+        RateLimits {
+            daily_limit: self.daily_limit,
+            reset: self.reset,
+            min_delay_ms: self.min_delay_ms,
+            windows: self.windows.clone(),
+            max_wait_ms: self.max_wait_ms,
+        }
+    }
+}
+
+/// On-disk response cache. Only endpoints listed under `[cache.ttl]` are cached.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CacheSection {
+    /// Defaults to the per-user cache directory
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    /// Function name to TTL, e.g. `OVERVIEW = "1d"`
+    #[serde(default)]
+    pub ttl: BTreeMap<String, String>,
+}
+
+impl CacheSection {
+    /// Parses the configured TTLs.
+    ///
+    /// # Errors
+    /// Returns error for an unknown function name or malformed TTL.
+    pub fn policy(&self) -> Result<CachePolicy> {
+        Ok(CachePolicy::from_pairs(
+            self.ttl.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+        )?)
+    }
+
+    /// The cache described by this section.
+    ///
+    /// # Errors
+    /// Returns error if the TTLs are invalid.
+    pub fn to_cache(&self) -> Result<ResponseCache> {
+        let dir = self.dir.clone().unwrap_or_else(ResponseCache::default_dir);
+        Ok(ResponseCache::new(dir, self.policy()?))
+    }
+}
+
+/// HTTP client settings for live calls; unset fields keep the client defaults.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct HttpSection {
+    /// e.g. `"5s"`; no separate connect limit by default
+    pub connect_timeout: Option<String>,
+    /// Limit for the whole request, e.g. `"30s"` (default 10s)
+    pub read_timeout: Option<String>,
+    /// e.g. `"http://proxy.corp:8080"`; `HTTP_PROXY`/`HTTPS_PROXY` apply otherwise
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+    /// Extra headers sent with every request
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Request gzip-compressed responses (default true)
+    pub gzip: Option<bool>,
+    /// PEM file of additional root certificates
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl HttpSection {
+    /// The client settings described by this section.
+    ///
+    /// # Errors
+    /// Returns error for a malformed timeout.
+    pub fn to_settings(&self) -> Result<HttpSettings> {
+        let defaults = HttpSettings::default();
+        Ok(HttpSettings {
+            connect_timeout: self
+                .connect_timeout
+                .as_deref()
+                .map(parse_timeout)
+                .transpose()?,
+            read_timeout: self
+                .read_timeout
+                .as_deref()
+                .map(parse_timeout)
+                .transpose()?
+                .unwrap_or(defaults.read_timeout),
+            proxy: self.proxy.clone(),
+            user_agent: self.user_agent.clone(),
+            headers: self.headers.clone(),
+            gzip: self.gzip.unwrap_or(defaults.gzip),
+            ca_bundle: self.ca_bundle.clone(),
+        })
+    }
+}
+
+/// Endpoints fetched by bulk runs and the parameters of their ticker calls.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct BulkSection {
+    /// Function names to fetch; every bulk endpoint when empty
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    /// Function names to leave out
+    #[serde(default)]
+    pub exclude_endpoints: Vec<String>,
+    /// Function name to query parameters, e.g. `NEWS_SENTIMENT = { limit = 200 }`.
+    /// `quarter = "latest"` is the last quarter that ended before the run.
+    #[serde(default)]
+    pub params: BTreeMap<String, BTreeMap<String, toml::Value>>,
+}
+
+impl BulkSection {
+    /// The selected endpoints with their parameters, resolving `latest` quarters
+    /// relative to `today`.
     ///
     /// # Errors
-    /// Returns error if config file cannot be found, read, parsed, or contains invalid values
-    pub fn load() -> Result<Self> {
-        let config_path = Self::find_config_file()?;
-        Self::load_from_path(&config_path)
+    /// Returns error for an unknown or non-bulk function name, a selection that
+    /// leaves nothing to fetch, parameters for a market-wide endpoint, or an
+    /// invalid parameter.
+    pub fn to_endpoints(&self, today: chrono::NaiveDate) -> Result<BulkEndpoints> {
+        let parse = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| parse_endpoint(name).map_err(anyhow::Error::msg))
+                .collect::<Result<Vec<_>>>()
+        };
+        let mut endpoints =
+            BulkEndpoints::select(&parse(&self.endpoints)?, &parse(&self.exclude_endpoints)?)?;
+        for (name, values) in &self.params {
+            let endpoint = parse_endpoint(name).map_err(anyhow::Error::msg)?;
+            let mut params = BTreeMap::new();
+            for (key, value) in values {
+                if ["function", "symbol", "apikey"].contains(&key.as_str()) {
+                    anyhow::bail!("{endpoint}: `{key}` is set by the explorer");
+                }
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                        value.to_string()
+                    }
+                    _ => anyhow::bail!("{endpoint}: `{key}` must be a string, number or boolean"),
+                };
+                let value = if endpoint == EndpointName::EarningsCallTranscript && key == "quarter"
+                {
+                    if value.eq_ignore_ascii_case("latest") {
+                        YearQuarter::latest(today).to_string()
+                    } else {
+                        value
+                            .parse::<YearQuarter>()
+                            .map_err(|e| anyhow::anyhow!("{endpoint}: {e} or latest"))?
+                            .to_string()
+                    }
+                } else {
+                    value
+                };
+                params.insert(key.clone(), value);
+            }
+            endpoints.set_params(endpoint, params)?;
+        }
+        Ok(endpoints)
     }
+}
 
-    /// Load configuration from a specific path
+/// Terms the `transcripts` report counts when neither `--terms` nor
+/// `[transcripts] terms` is set.
+pub const DEFAULT_TERMS: [&str; 5] = ["AI", "guidance", "supply", "demand", "margin"];
+
+/// Settings of the `transcripts` report.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TranscriptsSection {
+    /// Words or phrases to count, matched as whole words ignoring case
+    #[serde(default)]
+    pub terms: Vec<String>,
+}
+
+impl TranscriptsSection {
+    /// The configured terms, or [`DEFAULT_TERMS`] if none are.
+    #[must_use]
+    pub fn terms(&self) -> Vec<String> {
+        if self.terms.is_empty() {
+            DEFAULT_TERMS.iter().map(ToString::to_string).collect()
+        } else {
+            self.terms.clone()
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Load configuration from a specific path, on top of the built-in defaults
     ///
     /// # Errors
-    /// Returns error if file cannot be read, parsed as TOML, or contains invalid configuration
+    /// Returns error if file cannot be read, parsed as TOML, or contains invalid
+    /// configuration, or if it configures no API key
     pub fn load_from_path(path: &Path) -> Result<Self> {
-        let contents = fs::read_to_string(path).with_context(|| {
-            format!(
-                "Failed to read config file at {}. \
-                 Did you create alphavantage.toml from alphavantage.toml.template?",
-                path.display()
-            )
-        })?;
+        let config = LayeredConfig::from_file(path)?.config;
+        config.check_api_key(&path.display().to_string())?;
+        Ok(config)
+    }
 
-        let config: Self = toml::from_str(&contents).with_context(|| {
-            format!(
-                "Failed to parse config file at {}. \
-                 Ensure it follows the format in alphavantage.toml.template",
-                path.display()
-            )
-        })?;
+    /// Checks values that deserialize but make no sense. `origin` names the file
+    /// or layer in error messages.
+    ///
+    /// # Errors
+    /// Returns error for conflicting key sources, zero limits, malformed TTLs,
+    /// invalid `[http]` settings or an invalid `[bulk]` selection.
+    pub fn validate(&self, origin: &str) -> Result<()> {
+        self.api
+            .key_source()
+            .with_context(|| format!("Invalid [api] in {origin}"))?;
 
-        // Validate the config
-        if config.api.api_key == "YOUR_API_KEY_HERE" || config.api.api_key.is_empty() {
+        if self.rate_limit.daily_limit == Some(0) {
+            anyhow::bail!("Invalid daily_limit in {origin}. Must be greater than 0.");
+        }
+
+        if self.rate_limit.windows.iter().any(|w| w.limit == 0) {
             anyhow::bail!(
-                "API key not configured. Please edit {} and set your actual API key.",
-                path.display()
+                "Invalid rate_limit.windows entry in {origin}. Each limit must be greater than 0."
             );
         }
 
-        if config.rate_limit.daily_limit == 0 {
+        self.cache
+            .policy()
+            .with_context(|| format!("Invalid [cache.ttl] in {origin}"))?;
+        self.http
+            .to_settings()
+            .and_then(|settings| Ok(settings.build_client()?))
+            .with_context(|| format!("Invalid [http] in {origin}"))?;
+        self.bulk
+            .to_endpoints(chrono::Utc::now().date_naive())
+            .with_context(|| format!("Invalid [bulk] in {origin}"))?;
+        if self.transcripts.terms.iter().any(|t| t.trim().is_empty()) {
+            anyhow::bail!("Invalid [transcripts] in {origin}. Terms may not be blank.");
+        }
+        Ok(())
+    }
+
+    /// Checks that a key (or key source) is configured and is not the template's
+    /// placeholder.
+    ///
+    /// # Errors
+    /// Returns error if no usable key is configured.
+    pub fn check_api_key(&self, origin: &str) -> Result<()> {
+        let keys = self.api.all_keys();
+        let source = self.api.key_source()?;
+        if (source.is_none() && keys.is_empty()) || keys.iter().any(|k| k == "YOUR_API_KEY_HERE") {
             anyhow::bail!(
-                "Invalid daily_limit in {}. Must be greater than 0.",
-                path.display()
+                "API key not configured. Please edit {origin} and set your actual API key."
             );
         }
-
-        Ok(config)
+        Ok(())
     }
 
     /// Find the config file, starting from current directory and moving up
-    fn find_config_file() -> Result<PathBuf> {
+    ///
+    /// # Errors
+    /// Returns error if no alphavantage.toml exists in the current directory or above
+    pub fn find_config_file() -> Result<PathBuf> {
         let config_name = "alphavantage.toml";
 
         // Try current directory first
@@ -108,6 +468,7 @@ impl ApiConfig {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use alphavantage_client::WindowPeriod;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -128,7 +489,7 @@ daily_limit = 50
 
         let config = ApiConfig::load_from_path(temp_file.path()).expect("Failed to load config");
         assert_eq!(config.api.api_key, "TEST_KEY_12345");
-        assert_eq!(config.rate_limit.daily_limit, 50);
+        assert_eq!(config.rate_limit.daily_limit, Some(50));
         assert_eq!(config.rate_limit.min_delay_ms, 1000); // Default check
     }
 
@@ -152,6 +513,386 @@ min_delay_ms = 500
         assert_eq!(config.rate_limit.min_delay_ms, 500);
     }
 
+    #[test]
+    fn test_windows_without_daily_limit() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[rate_limit]
+min_delay_ms = 0
+windows = [
+    {{ limit = 5, per = "second" }},
+    {{ limit = 75, per = "minute" }},
+]
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let config = ApiConfig::load_from_path(temp_file.path()).expect("Failed to load config");
+        let limits = config.rate_limit.to_rate_limits();
+        assert_eq!(limits.daily_limit, None);
+        assert_eq!(
+            limits.windows,
+            vec![
+                RateWindow {
+                    limit: 5,
+                    per: WindowPeriod::Second
+                },
+                RateWindow {
+                    limit: 75,
+                    per: WindowPeriod::Minute
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zero_window_limit() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[rate_limit]
+windows = [{{ limit = 0, per = "minute" }}]
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let result = ApiConfig::load_from_path(temp_file.path());
+        assert!(result.unwrap_err().to_string().contains("windows"));
+    }
+
+    #[test]
+    fn test_reset_rule() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[rate_limit]
+daily_limit = 25
+reset = {{ timezone = "America/New_York", at = "00:00" }}
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let config = ApiConfig::load_from_path(temp_file.path()).expect("Failed to load config");
+        assert_eq!(
+            config.rate_limit.to_rate_limits().reset,
+            ResetRule::fixed("America/New_York", "00:00").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_reset_timezone() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[rate_limit]
+reset = {{ timezone = "Eastern", at = "00:00" }}
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let result = ApiConfig::load_from_path(temp_file.path());
+        assert!(format!("{:#}", result.unwrap_err()).contains("Unknown timezone 'Eastern'"));
+    }
+
+    #[test]
+    fn test_cache_ttls() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[rate_limit]
+daily_limit = 25
+
+[cache]
+dir = "/tmp/av-cache"
+
+[cache.ttl]
+OVERVIEW = "1d"
+GLOBAL_QUOTE = "1m"
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let config = ApiConfig::load_from_path(temp_file.path()).expect("Failed to load config");
+        let cache = config.cache.to_cache().unwrap();
+        assert_eq!(cache.dir(), Path::new("/tmp/av-cache"));
+        let policy = config.cache.policy().unwrap();
+        assert_eq!(
+            policy.ttl(alphavantage_core::domain::EndpointName::Overview),
+            Some(std::time::Duration::from_hours(24))
+        );
+        assert_eq!(
+            policy.ttl(alphavantage_core::domain::EndpointName::BalanceSheet),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalid_cache_ttl() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[rate_limit]
+daily_limit = 25
+
+[cache.ttl]
+OVERVIEW = "one day"
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let result = ApiConfig::load_from_path(temp_file.path());
+        assert!(format!("{:#}", result.unwrap_err()).contains("Invalid TTL 'one day'"));
+    }
+
+    #[test]
+    fn test_bulk_section() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[bulk]
+exclude_endpoints = ["LISTING_STATUS", "earnings-calendar"]
+
+[bulk.params.EARNINGS_CALL_TRANSCRIPT]
+quarter = "latest"
+
+[bulk.params.NEWS_SENTIMENT]
+limit = 200
+sort = "LATEST"
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let config = ApiConfig::load_from_path(temp_file.path()).expect("Failed to load config");
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 8, 15).unwrap();
+        let endpoints = config.bulk.to_endpoints(today).unwrap();
+        assert!(!endpoints.market.contains(&EndpointName::ListingStatus));
+        assert!(!endpoints.ticker.contains(&EndpointName::EarningsCalendar));
+        assert_eq!(endpoints.ticker.len(), 12);
+        assert_eq!(
+            endpoints.params(EndpointName::EarningsCallTranscript),
+            BTreeMap::from([("quarter".to_string(), "2024Q2".to_string())])
+        );
+        assert_eq!(
+            endpoints.describe_params().last().unwrap(),
+            "NEWS_SENTIMENT limit=200, sort=LATEST"
+        );
+    }
+
+    #[test]
+    fn test_invalid_bulk_section() {
+        for (bulk, expected) in [
+            (
+                "endpoints = [\"GLOBAL_QUOTE\"]",
+                "GLOBAL_QUOTE is not fetched",
+            ),
+            ("endpoints = [\"OVERVEIW\"]", "Unknown endpoint 'OVERVEIW'"),
+            (
+                "params.EARNINGS_CALL_TRANSCRIPT = { quarter = \"2024Q5\" }",
+                "Invalid quarter '2024Q5'",
+            ),
+            (
+                "params.MARKET_STATUS = { region = \"US\" }",
+                "MARKET_STATUS takes no parameters",
+            ),
+            (
+                "params.OVERVIEW = { symbol = \"IBM\" }",
+                "`symbol` is set by the explorer",
+            ),
+        ] {
+            let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+            writeln!(temp_file, "[api]\napi_key = \"TEST_KEY\"\n\n[bulk]\n{bulk}")
+                .expect("Failed to write to temp file");
+            let err = format!(
+                "{:#}",
+                ApiConfig::load_from_path(temp_file.path()).unwrap_err()
+            );
+            assert!(err.contains("Invalid [bulk]"), "{err}");
+            assert!(err.contains(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_transcripts_section() {
+        assert_eq!(ApiConfig::default().transcripts.terms(), DEFAULT_TERMS);
+
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            "[api]\napi_key = \"TEST_KEY\"\n\n[transcripts]\nterms = [\"HBM\", \"gross margin\"]"
+        )
+        .expect("Failed to write to temp file");
+        let config = ApiConfig::load_from_path(temp_file.path()).unwrap();
+        assert_eq!(config.transcripts.terms(), ["HBM", "gross margin"]);
+
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            "[api]\napi_key = \"TEST_KEY\"\n\n[transcripts]\nterms = [\" \"]"
+        )
+        .expect("Failed to write to temp file");
+        let err = format!(
+            "{:#}",
+            ApiConfig::load_from_path(temp_file.path()).unwrap_err()
+        );
+        assert!(err.contains("Invalid [transcripts]"), "{err}");
+    }
+
+    #[test]
+    fn test_http_section() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_key = "TEST_KEY"
+
+[rate_limit]
+daily_limit = 25
+
+[http]
+connect_timeout = "5s"
+read_timeout = "1m"
+proxy = "http://proxy.corp:8080"
+user_agent = "research-bot/1.0"
+gzip = false
+
+[http.headers]
+X-Team = "research"
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let config = ApiConfig::load_from_path(temp_file.path()).expect("Failed to load config");
+        let settings = config.http.to_settings().unwrap();
+        assert_eq!(
+            settings.connect_timeout,
+            Some(std::time::Duration::from_secs(5))
+        );
+        assert_eq!(settings.read_timeout, std::time::Duration::from_mins(1));
+        assert_eq!(settings.proxy.as_deref(), Some("http://proxy.corp:8080"));
+        assert!(!settings.gzip);
+        assert_eq!(settings.headers.get("X-Team").unwrap(), "research");
+
+        // Defaults when the section is absent
+        let defaults = HttpSection::default().to_settings().unwrap();
+        assert_eq!(defaults, HttpSettings::default());
+    }
+
+    #[test]
+    fn test_invalid_http_section() {
+        for (section, expected) in [
+            ("read_timeout = \"soon\"", "Invalid timeout 'soon'"),
+            (
+                "ca_bundle = \"/nonexistent/ca.pem\"",
+                "Failed to read CA bundle",
+            ),
+            ("retries = 3", "unknown field `retries`"),
+        ] {
+            let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+            writeln!(
+                temp_file,
+                "[api]\napi_key = \"TEST_KEY\"\n\n[rate_limit]\ndaily_limit = 25\n\n[http]\n{section}"
+            )
+            .expect("Failed to write to temp file");
+
+            let err = format!(
+                "{:#}",
+                ApiConfig::load_from_path(temp_file.path()).unwrap_err()
+            );
+            assert!(err.contains(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_key_pool() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(
+            temp_file,
+            r#"
+[api]
+api_keys = ["KEY_ONE", "KEY_TWO"]
+key_strategy = "round-robin"
+
+[rate_limit]
+daily_limit = 25
+"#
+        )
+        .expect("Failed to write to temp file");
+
+        let config = ApiConfig::load_from_path(temp_file.path()).expect("Failed to load config");
+        assert_eq!(config.api.all_keys(), vec!["KEY_ONE", "KEY_TWO"]);
+        assert_eq!(config.api.key_strategy, KeyStrategy::RoundRobin);
+    }
+
+    #[test]
+    fn test_key_indirections() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("av.key"), "FILE_KEY\n").unwrap();
+        let path = dir.path().join("alphavantage.toml");
+        std::fs::write(
+            &path,
+            "[api]\napi_key_file = \"av.key\"\napi_keys = [\"POOL_KEY\"]\n\n[rate_limit]\ndaily_limit = 25\n",
+        )
+        .unwrap();
+
+        // Relative key files are found next to the config file
+        let config = ApiConfig::load_from_path(&path).expect("Failed to load config");
+        let keys = config.api.resolve_keys().unwrap();
+        let keys: Vec<&str> = keys.iter().map(ApiKey::secret).collect();
+        assert_eq!(keys, vec!["FILE_KEY", "POOL_KEY"]);
+
+        std::fs::write(
+            &path,
+            "[api]\napi_key_cmd = \"exit 1\"\n\n[rate_limit]\ndaily_limit = 25\n",
+        )
+        .unwrap();
+        // The command only runs when the key is needed
+        let config = ApiConfig::load_from_path(&path).expect("Failed to load config");
+        let err = format!("{:#}", config.api.resolve_keys().unwrap_err());
+        assert!(
+            err.contains("Failed to read the API key from api_key_cmd"),
+            "{err}"
+        );
+
+        std::fs::write(
+            &path,
+            "[api]\napi_key = \"INLINE\"\napi_key_env = \"AV_KEY\"\n\n[rate_limit]\ndaily_limit = 25\n",
+        )
+        .unwrap();
+        let err = format!("{:#}", ApiConfig::load_from_path(&path).unwrap_err());
+        assert!(err.contains("Set only one of api_key"), "{err}");
+        assert!(!err.contains("INLINE"), "{err}");
+    }
+
     #[test]
     fn test_missing_api_key() {
         let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
@@ -0,0 +1,97 @@
+use crate::api_config::ApiConfig;
+use crate::cli_args::CacheAction;
+use alphavantage_client::{CacheStats, ResponseCache};
+use anyhow::Result;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Runs a `cache` subcommand against the response cache directory.
+///
+/// TTLs come from the effective configuration, so "expired" matches what a live
+/// run would consider stale.
+///
+/// # Errors
+/// Returns error if the configured TTLs are invalid or entries cannot be removed.
+pub fn run(action: CacheAction, cache_dir: Option<PathBuf>, config: &ApiConfig) -> Result<()> {
+    let section = &config.cache;
+    let cache = match cache_dir {
+        Some(dir) => ResponseCache::new(dir, section.policy()?),
+        None => section.to_cache()?,
+    };
+
+    match action {
+        CacheAction::Stats => {
+            println!(
+                "Response cache: {}\n\n{}",
+                cache.dir().display(),
+                render_stats(&cache.stats())
+            );
+        }
+        CacheAction::Purge => {
+            let removed = cache.purge()?;
+            println!("Removed {removed} cached responses");
+        }
+        CacheAction::Prune => {
+            let removed = cache.prune()?;
+            println!("Removed {removed} expired responses");
+        }
+    }
+    Ok(())
+}
+
+/// Human-readable cache summary.
+#[must_use]
+pub fn render_stats(stats: &CacheStats) -> String {
+    if stats.entries == 0 {
+        return "No cached responses.".to_string();
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Entries: {} ({} expired)",
+        stats.entries, stats.expired
+    );
+    let _ = writeln!(out, "Size:    {}", format_bytes(stats.bytes));
+    for (function, count) in &stats.by_function {
+        let _ = writeln!(out, "  {function:<28} {count:>5}");
+    }
+    out.trim_end().to_string()
+}
+
+#[allow(clippy::cast_precision_loss)] // display only
+fn format_bytes(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    if bytes >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    } else if bytes >= KIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_render_stats() {
+        let stats = CacheStats {
+            entries: 3,
+            expired: 1,
+            bytes: 2048,
+            by_function: BTreeMap::from([
+                ("GLOBAL_QUOTE".to_string(), 1),
+                ("OVERVIEW".to_string(), 2),
+            ]),
+        };
+
+        let text = render_stats(&stats);
+        assert!(text.starts_with("Entries: 3 (1 expired)\nSize:    2.0 KiB"));
+        assert!(text.contains("  OVERVIEW"));
+        assert_eq!(render_stats(&CacheStats::default()), "No cached responses.");
+    }
+}
@@ -1,11 +1,16 @@
-use alphavantage_core::domain::{HorizonParam, QuarterParam, TickerSymbol};
+use alphavantage_core::domain::{
+    EndpointName, HorizonParam, QuarterParam, TickerSymbol, YearQuarter,
+};
 use clap::{Parser, Subcommand, ValueEnum};
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::PathBuf;
+use std::str::FromStr;
 use tracing::Level;
 
 /// Alpha Vantage Explorer - API validation and reporting tool
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)] // independent command-line switches
 pub struct CliArgs {
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -14,22 +19,113 @@ pub struct CliArgs {
     #[arg(long, value_delimiter = ',', value_parser = parse_ticker, global = true)]
     pub symbols: Option<Vec<TickerSymbol>>,
 
+    /// Add the named symbol lists from `[watchlists]` in the config files (comma-separated)
+    #[arg(long, value_name = "NAME", value_delimiter = ',', global = true)]
+    pub watchlist: Vec<String>,
+
+    /// Add the symbols in a plain text or CSV file; `#` starts a comment
+    #[arg(long, value_name = "FILE", global = true)]
+    pub symbols_file: Option<PathBuf>,
+
+    /// Add the symbols of a universe, narrowed by `--exchange` and `--asset-type`
+    #[arg(long, value_enum, global = true)]
+    pub universe: Option<Universe>,
+
+    /// Keep universe symbols listed on these exchanges, e.g. NASDAQ,NYSE
+    #[arg(long, value_delimiter = ',', global = true, requires = "universe")]
+    pub exchange: Vec<String>,
+
+    /// Keep universe symbols of these asset types: Stock, ETF
+    #[arg(long, value_delimiter = ',', global = true, requires = "universe")]
+    pub asset_type: Vec<String>,
+
     /// Output directory for reports and raw data
     #[arg(long, default_value = "out", global = true)]
     pub out_dir: PathBuf,
 
-    /// Alpha Vantage API Key
-    #[arg(long, env = "ALPHA_VANTAGE_API_KEY", global = true)]
+    /// Alpha Vantage API Key (overrides `ALPHA_VANTAGE_API_KEY` and the config files)
+    #[arg(long, global = true)]
     pub api_key: Option<String>,
 
+    /// Apply the `[profile.<NAME>]` tables of the config files (or set
+    /// `ALPHA_VANTAGE_PROFILE`)
+    #[arg(long, value_name = "NAME", global = true)]
+    pub profile: Option<String>,
+
+    /// Calendar-day call quota, overriding the config files
+    #[arg(long, value_name = "N", global = true)]
+    pub daily_limit: Option<u32>,
+
+    /// Minimum delay between live calls in milliseconds, overriding the config files
+    #[arg(long, value_name = "MS", global = true)]
+    pub min_delay_ms: Option<u64>,
+
     /// Enable live API calls (consumes quota)
     #[arg(long, default_value_t = false, global = true)]
     pub live_api: bool,
 
+    /// Serve responses from a previous run's output or raw directory instead of the
+    /// bundled fixtures (no API calls)
+    #[arg(long, value_name = "DIR", global = true, conflicts_with = "live_api")]
+    pub replay_from: Option<PathBuf>,
+
+    /// Script mock responses per endpoint (latency, HTTP errors, rate-limit bodies,
+    /// malformed or truncated payloads, timeouts) from a TOML scenario file
+    #[arg(
+        long,
+        value_name = "FILE",
+        global = true,
+        conflicts_with_all = ["live_api", "replay_from"]
+    )]
+    pub mock_scenario: Option<PathBuf>,
+
+    /// Save each live response, with the API key stripped, into DIR using the
+    /// mock fixture layout
+    #[arg(long, value_name = "DIR", global = true, requires = "live_api")]
+    pub record: Option<PathBuf>,
+
     /// Disable raw JSON persistence
     #[arg(long, default_value_t = false, global = true)]
     pub no_raw: bool,
 
+    /// Do not read or write the response cache
+    #[arg(long, default_value_t = false, global = true)]
+    pub no_cache: bool,
+
+    /// Ignore cached responses but store the fresh ones
+    #[arg(
+        long,
+        default_value_t = false,
+        global = true,
+        conflicts_with = "no_cache"
+    )]
+    pub refresh: bool,
+
+    /// Fetch only these bulk endpoints, e.g. `OVERVIEW,BALANCE_SHEET` (replaces
+    /// `[bulk] endpoints`)
+    #[arg(long, value_name = "LIST", value_delimiter = ',', value_parser = parse_endpoint)]
+    pub endpoints: Vec<EndpointName>,
+
+    /// Leave these endpoints out of a bulk run (replaces `[bulk] exclude_endpoints`)
+    #[arg(long, value_name = "LIST", value_delimiter = ',', value_parser = parse_endpoint)]
+    pub exclude_endpoints: Vec<EndpointName>,
+
+    /// Print the bulk run plan and estimated duration without calling the API
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Continue the bulk run recorded in `out_dir`, skipping tasks that already succeeded
+    #[arg(long, default_value_t = false)]
+    pub resume: bool,
+
+    /// What a bulk run does when it needs more calls than today's remaining quota
+    #[arg(long, value_enum, default_value_t = OverQuota::Refuse)]
+    pub over_quota: OverQuota,
+
+    /// Maximum bulk calls in flight at once; the rate limiter still paces them
+    #[arg(long, value_name = "N", default_value = "1")]
+    pub concurrency: NonZeroUsize,
+
     /// Log output format
     #[arg(long, value_enum, default_value_t = LogFormat::Human, global = true)]
     pub log_format: LogFormat,
@@ -181,24 +277,247 @@ pub enum Commands {
         output: Option<PathBuf>,
     },
 
-    /// Fetch earnings call transcript (`EARNINGS_CALL_TRANSCRIPT` endpoint)
+    /// Fetch earnings call transcripts (`EARNINGS_CALL_TRANSCRIPT` endpoint) for one
+    /// quarter or a range
+    #[command(group(
+        clap::ArgGroup::new("period").required(true).args(["year", "from", "latest"])
+    ))]
     EarningsCallTranscript {
         /// Stock ticker symbol
         #[arg(short, long, value_parser = parse_ticker)]
         symbol: TickerSymbol,
 
         /// Year of earnings call
-        #[arg(short, long)]
-        year: u16,
+        #[arg(short, long, requires = "quarter")]
+        year: Option<u16>,
 
         /// Quarter (Q1, Q2, Q3, Q4)
-        #[arg(short, long)]
-        quarter: QuarterParam,
+        #[arg(short, long, requires = "year")]
+        quarter: Option<QuarterParam>,
+
+        /// First quarter of a range, e.g. 2022Q1
+        #[arg(long, value_name = "YYYYQn", requires = "to")]
+        from: Option<YearQuarter>,
+
+        /// Last quarter of a range, e.g. 2024Q4
+        #[arg(long, value_name = "YYYYQn", requires = "from")]
+        to: Option<YearQuarter>,
+
+        /// The N most recent completed quarters (at most 100)
+        #[arg(long, value_name = "N")]
+        latest: Option<NonZeroU32>,
 
         /// Output file path (optional)
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+
+    /// Inspect or adjust the rate limiter's quota state (no API calls)
+    Quota {
+        #[command(subcommand)]
+        action: QuotaAction,
+
+        /// Print machine-readable JSON instead of text
+        #[arg(long, default_value_t = false, global = true)]
+        json: bool,
+
+        /// Rate limiter state file (defaults to ~/.alphavantage-explorer-tokens.json)
+        #[arg(long, global = true)]
+        state_file: Option<PathBuf>,
+
+        /// Only act on the API key with this fingerprint
+        #[arg(long, global = true)]
+        key: Option<String>,
+    },
+
+    /// Inspect or clean up the on-disk response cache (no API calls)
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+
+        /// Cache directory (defaults to `[cache] dir` in alphavantage.toml, then the user cache dir)
+        #[arg(long, global = true)]
+        cache_dir: Option<PathBuf>,
+    },
+
+    /// Inspect or create the configuration (no API calls)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Check the configuration, state file, output directory and API key, and
+    /// suggest fixes (makes one API call unless `--offline`)
+    Doctor {
+        /// Skip the API call verifying the key
+        #[arg(long, default_value_t = false)]
+        offline: bool,
+
+        /// API endpoint to verify the key against, e.g. a local fake-alphavantage server
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+
+        /// Symbol used for the verifying `GLOBAL_QUOTE` call
+        #[arg(long, default_value = "AAPL", value_parser = parse_ticker)]
+        symbol: TickerSymbol,
+
+        /// Rate limiter state file (defaults to ~/.alphavantage-explorer-tokens.json)
+        #[arg(long)]
+        state_file: Option<PathBuf>,
+    },
+
+    /// Keyword trends, speakers and Q&A across stored earnings call transcripts
+    /// (no API calls)
+    Transcripts {
+        /// Directories searched for saved transcript JSON (defaults to --out-dir)
+        #[arg(long = "dir", value_name = "DIR")]
+        dirs: Vec<PathBuf>,
+
+        /// Terms to count, e.g. `AI,guidance,supply` (defaults to `[transcripts] terms`)
+        #[arg(long, value_delimiter = ',')]
+        terms: Vec<String>,
+
+        /// Only these symbols, comma-separated
+        #[arg(long = "symbol", value_delimiter = ',', value_parser = parse_ticker)]
+        symbols: Vec<TickerSymbol>,
+
+        /// Output directory path (defaults to --out-dir)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Print the effective configuration and where each value was set
+    Show,
+    /// Write a config file for a plan tier (uses `--api-key` when given)
+    Init {
+        /// Plan tier whose rate limits to write
+        #[arg(long, value_enum, default_value_t = PlanTier::Free)]
+        tier: PlanTier,
+
+        /// Read the key from this environment variable instead of storing it
+        #[arg(
+            long,
+            value_name = "VAR",
+            group = "key_source",
+            conflicts_with = "api_key"
+        )]
+        key_env: Option<String>,
+
+        /// Read the key from this file instead of storing it
+        #[arg(
+            long,
+            value_name = "FILE",
+            group = "key_source",
+            conflicts_with = "api_key"
+        )]
+        key_file: Option<PathBuf>,
+
+        /// Run this command to print the key, e.g. a password manager CLI
+        #[arg(
+            long,
+            value_name = "CMD",
+            group = "key_source",
+            conflicts_with = "api_key"
+        )]
+        key_cmd: Option<String>,
+
+        /// File to write (default: ./alphavantage.toml)
+        #[arg(long, value_name = "FILE", conflicts_with = "user")]
+        path: Option<PathBuf>,
+
+        /// Write the per-user config file instead of ./alphavantage.toml
+        #[arg(long, default_value_t = false)]
+        user: bool,
+
+        /// Overwrite an existing file
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+}
+
+/// Alpha Vantage plan tiers, by calls allowed per minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum PlanTier {
+    /// 25 calls per day
+    #[default]
+    Free,
+    /// 75 calls per minute, no daily cap
+    #[value(name = "premium-75")]
+    Premium75,
+    /// 150 calls per minute, no daily cap
+    #[value(name = "premium-150")]
+    Premium150,
+    /// 300 calls per minute, no daily cap
+    #[value(name = "premium-300")]
+    Premium300,
+    /// 600 calls per minute, no daily cap
+    #[value(name = "premium-600")]
+    Premium600,
+    /// 1200 calls per minute, no daily cap
+    #[value(name = "premium-1200")]
+    Premium1200,
+}
+
+impl PlanTier {
+    /// Calls allowed per minute, `None` for the free tier's daily quota.
+    #[must_use]
+    pub const fn per_minute(self) -> Option<u32> {
+        match self {
+            Self::Free => None,
+            Self::Premium75 => Some(75),
+            Self::Premium150 => Some(150),
+            Self::Premium300 => Some(300),
+            Self::Premium600 => Some(600),
+            Self::Premium1200 => Some(1200),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheAction {
+    /// Show entry counts, expired entries and size on disk
+    Stats,
+    /// Delete every cached response
+    Purge,
+    /// Delete expired responses and those of endpoints without a TTL
+    Prune,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaAction {
+    /// Show remaining calls, calls today, next reset and last call per key
+    Status,
+    /// Restore the full daily quota
+    Reset,
+    /// Set the calls left until the next reset (e.g. to match the provider)
+    SetRemaining {
+        /// Calls remaining
+        remaining: u32,
+    },
+    /// Show calls per day over the last 30 days
+    History,
+}
+
+/// Where `--universe` takes its symbols from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Universe {
+    /// Active listings in the cached `LISTING_STATUS` response
+    ListingStatus,
+}
+
+/// Policy for bulk runs that do not fit in the remaining quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OverQuota {
+    /// Do not start the run
+    #[default]
+    Refuse,
+    /// Run the highest-priority calls that fit and skip the rest
+    Truncate,
+    /// Run what fits today and plan the remaining tickers for following days
+    Spread,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -232,3 +551,12 @@ impl From<LogLevel> for Level {
 fn parse_ticker(s: &str) -> std::result::Result<TickerSymbol, String> {
     TickerSymbol::new(s).map_err(|e| e.to_string())
 }
+
+/// Parses a function name such as `OVERVIEW`, ignoring case and accepting `-` for `_`.
+///
+/// # Errors
+/// Returns error naming `s` if it is not a known function.
+pub fn parse_endpoint(s: &str) -> std::result::Result<EndpointName, String> {
+    EndpointName::from_str(&s.trim().to_ascii_uppercase().replace('-', "_"))
+        .map_err(|_| format!("Unknown endpoint '{s}'"))
+}
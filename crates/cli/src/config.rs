@@ -1,20 +1,54 @@
-use crate::api_config::ApiConfig;
-use crate::cli_args::{CliArgs, LogFormat};
-use alphavantage_client::ClientMode;
+use crate::api_config::ApiSection;
+use crate::cli_args::{CliArgs, LogFormat, OverQuota};
+use crate::layered_config::LayeredConfig;
+use crate::planner::BulkEndpoints;
+use crate::symbols;
+use alphavantage_client::http_client::BASE_URL;
+use alphavantage_client::{
+    create_client, AlphaVantageClient, CachingClient, ClientMode, HttpSettings, KeyPool,
+    KeyStrategy, MockClient, MockScenario, RateLimiter, RateLimits, RecordingClient, ReplayClient,
+    ResponseCache,
+};
 use alphavantage_core::domain::{ApiKey, TickerSymbol};
+use alphavantage_core::ports::ApiClient;
 use anyhow::{Context, Result};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use tracing::Level;
 
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent command-line switches
 pub struct Config {
     pub symbols: Vec<TickerSymbol>,
+    /// Bulk endpoints to fetch and their parameters
+    pub endpoints: BulkEndpoints,
     pub out_dir: PathBuf,
     pub api_key: ApiKey,
+    /// Additional keys to pool with `api_key` in live mode (empty for a single key)
+    pub api_keys: Vec<ApiKey>,
+    pub key_strategy: KeyStrategy,
     pub client_mode: ClientMode,
+    /// Directory an offline run replays saved responses from
+    pub replay_from: Option<PathBuf>,
+    /// Scripted latency and faults for the mock client
+    pub mock_scenario: Option<MockScenario>,
+    /// Fixture directory live responses are recorded into
+    pub record_to: Option<PathBuf>,
     pub save_raw: bool,
-    pub daily_limit: u32,
-    pub min_delay_ms: u64,
+    pub rate_limits: RateLimits,
+    /// Timeouts, proxy, headers and CA bundle for the live HTTP client
+    pub http: HttpSettings,
+    /// Response cache wrapped around the live client, `None` when disabled
+    pub cache: Option<ResponseCache>,
+    /// Skip cached responses but store fresh ones
+    pub refresh_cache: bool,
+    /// Plan the bulk run without making any API calls
+    pub dry_run: bool,
+    /// Skip bulk tasks recorded as done in the run manifest
+    pub resume: bool,
+    pub over_quota: OverQuota,
+    /// Maximum bulk calls in flight at once
+    pub concurrency: NonZeroUsize,
     #[allow(dead_code)]
     pub log_format: LogFormat,
     #[allow(dead_code)]
@@ -33,67 +67,183 @@ impl Config {
             ClientMode::Mock
         };
 
-        // Determine API key, rate limit, and min delay
-        let (api_key_str, daily_limit, min_delay_ms) = match client_mode {
+        // Malformed config files are errors in every mode
+        let layered = LayeredConfig::load(&args)?;
+        let file = &layered.config;
+
+        // Determine API key(s) and rate limits
+        let (api_key, pool_keys, key_strategy, rate_limits, cache, http) = match client_mode {
             ClientMode::Live => {
-                // Try to load from config file first, then fall back to CLI args
-                if let Ok(config_file) = ApiConfig::load() {
-                    tracing::info!(
-                        "Loaded API configuration from alphavantage.toml (daily limit: {}, min delay: {}ms)",
-                        config_file.rate_limit.daily_limit,
-                        config_file.rate_limit.min_delay_ms
-                    );
-                    let key = args.api_key.unwrap_or(config_file.api.api_key);
-                    let limit = config_file.rate_limit.daily_limit;
-                    let delay = config_file.rate_limit.min_delay_ms;
-                    (key, limit, delay)
+                tracing::info!(
+                    "Loaded configuration (profile: {}, daily limit: {:?}, min delay: {}ms, windows: {})",
+                    layered.profile.as_deref().unwrap_or("none"),
+                    file.rate_limit.daily_limit,
+                    file.rate_limit.min_delay_ms,
+                    file.rate_limit.windows.len()
+                );
+                let (key, pool) = live_keys(&file.api)?;
+                // Nothing to cache without TTLs
+                let cache = if args.no_cache || file.cache.ttl.is_empty() {
+                    None
                 } else {
-                    // Fall back to CLI args/env vars
-                    tracing::warn!(
-                        "Could not load alphavantage.toml, using CLI args/env vars. \
-                         Create alphavantage.toml from alphavantage.toml.template for easier configuration."
-                    );
-                    let key = args.api_key.context(
-                        "API key required for live mode. \
-                         Set ALPHA_VANTAGE_API_KEY env var, use --api-key flag, \
-                         or create alphavantage.toml from alphavantage.toml.template",
-                    )?;
-                    (key, 25, 1000) // Default to free tier limit and delay
-                }
+                    Some(file.cache.to_cache()?)
+                };
+                (
+                    key,
+                    pool,
+                    file.api.key_strategy,
+                    file.rate_limit.to_rate_limits(),
+                    cache,
+                    file.http.to_settings()?,
+                )
             }
             ClientMode::Mock => {
                 // For mock mode, use dummy values
                 (
-                    args.api_key.unwrap_or_else(|| "mock_key".to_string()),
-                    25,
-                    0, // No delay in mock mode
+                    ApiKey::new(
+                        args.api_key
+                            .clone()
+                            .unwrap_or_else(|| "mock_key".to_string()),
+                    ),
+                    Vec::new(),
+                    KeyStrategy::default(),
+                    RateLimits::daily(25, 0), // No delay in mock mode
+                    None,
+                    HttpSettings::default(),
                 )
             }
         };
 
-        let api_key = ApiKey::new(api_key_str);
+        let mock_scenario = args
+            .mock_scenario
+            .as_deref()
+            .map(MockScenario::load)
+            .transpose()?;
 
-        // For bulk mode (no subcommand), use symbols or default to AAPL,NVDA,MU
-        // For granular mode (has subcommand), symbols field won't be used
-        let symbols = args.symbols.unwrap_or_else(|| {
-            // Safe to use from_static since we control these known-valid symbols
-            const DEFAULT_SYMBOLS: &[&str] = &["AAPL", "NVDA", "MU"];
-            DEFAULT_SYMBOLS
-                .iter()
-                .filter_map(|s| TickerSymbol::new(*s).ok())
-                .collect()
-        });
+        // Bulk mode symbols; granular commands take their own `--symbol`
+        let symbols = symbols::resolve(&args, file)?;
+        let endpoints = file.bulk.to_endpoints(chrono::Utc::now().date_naive())?;
 
         Ok(Self {
             symbols,
+            endpoints,
             out_dir: args.out_dir,
             api_key,
+            api_keys: pool_keys,
+            key_strategy,
             client_mode,
+            replay_from: args.replay_from,
+            mock_scenario,
+            record_to: args.record,
             save_raw: !args.no_raw,
-            daily_limit,
-            min_delay_ms,
+            rate_limits,
+            http,
+            cache,
+            refresh_cache: args.refresh,
+            dry_run: args.dry_run,
+            resume: args.resume,
+            over_quota: args.over_quota,
+            concurrency: args.concurrency,
             log_format: args.log_format,
             log_level: args.log_level.into(),
         })
     }
+
+    /// Calls left today across every configured key, or `None` when unlimited.
+    ///
+    /// # Errors
+    /// Returns error if the rate limiter state file cannot be read.
+    pub fn remaining_quota(&self) -> Result<Option<u32>> {
+        // The mock client and the "demo" key are not rate limited
+        if self.client_mode == ClientMode::Mock || self.api_key.is_demo() {
+            return Ok(None);
+        }
+
+        let limiter =
+            RateLimiter::with_limits(RateLimiter::resolve_state_path(), self.rate_limits.clone());
+        let mut total = 0u32;
+        for key in std::iter::once(&self.api_key).chain(&self.api_keys) {
+            match limiter.remaining(key)? {
+                Some(remaining) => total = total.saturating_add(remaining),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(total))
+    }
+
+    /// Calls allowed per day across every configured key, or `None` without a daily limit.
+    #[must_use]
+    pub fn pool_daily_limit(&self) -> Option<u32> {
+        let keys = u32::try_from(1 + self.api_keys.len()).unwrap_or(u32::MAX);
+        self.rate_limits
+            .daily_limit
+            .map(|limit| limit.saturating_mul(keys))
+    }
+
+    /// Builds the API client for this configuration, behind the response cache
+    /// when one is configured.
+    ///
+    /// # Errors
+    /// Returns error if the live HTTP client cannot be built from the `[http]` settings.
+    pub fn create_client(&self) -> Result<Box<dyn ApiClient>> {
+        Ok(match self.client_mode {
+            ClientMode::Live => {
+                let mut client = AlphaVantageClient::with_settings(
+                    BASE_URL,
+                    self.rate_limits.clone(),
+                    &self.http,
+                )
+                .context("Failed to create HTTP client")?;
+                if !self.api_keys.is_empty() {
+                    let mut keys = vec![self.api_key.clone()];
+                    keys.extend(self.api_keys.iter().cloned());
+                    client = client.with_key_pool(KeyPool::new(keys, self.key_strategy));
+                }
+                let client: Box<dyn ApiClient> = match &self.record_to {
+                    Some(dir) => Box::new(RecordingClient::new(client, dir.clone())),
+                    None => Box::new(client),
+                };
+                match &self.cache {
+                    Some(cache) => Box::new(
+                        CachingClient::new(client, cache.clone()).refresh(self.refresh_cache),
+                    ),
+                    None => client,
+                }
+            }
+            ClientMode::Mock => {
+                if let Some(dir) = &self.replay_from {
+                    Box::new(ReplayClient::new(dir.clone()))
+                } else if let Some(scenario) = &self.mock_scenario {
+                    Box::new(MockClient::new().with_scenario(scenario.clone()))
+                } else {
+                    create_client(self.client_mode, &self.rate_limits)?
+                }
+            }
+        })
+    }
+}
+
+/// The primary key and pool for a live run.
+///
+/// The key source (e.g. `api_key_cmd`) of the effective configuration is only read
+/// here, so an explicit `--api-key` or `ALPHA_VANTAGE_API_KEY`, which replaces it,
+/// never triggers it.
+///
+/// # Errors
+/// Returns error if no key is configured, the key is the template placeholder, or
+/// the key source fails.
+pub fn live_keys(api: &ApiSection) -> Result<(ApiKey, Vec<ApiKey>)> {
+    let missing = || {
+        anyhow::anyhow!(
+            "API key required for live mode. \
+             Set ALPHA_VANTAGE_API_KEY env var, use --api-key flag, \
+             or create alphavantage.toml from alphavantage.toml.template"
+        )
+    };
+    if api.all_keys().iter().any(|k| k == "YOUR_API_KEY_HERE") {
+        return Err(missing());
+    }
+    let mut keys = api.resolve_keys()?.into_iter();
+    let key = keys.next().ok_or_else(missing)?;
+    Ok((key, keys.collect()))
 }
@@ -0,0 +1,150 @@
+use crate::cli_args::PlanTier;
+use crate::layered_config::LayeredConfig;
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Placeholder written when no key or key source is given.
+pub const KEY_PLACEHOLDER: &str = "YOUR_API_KEY_HERE";
+
+/// How the generated file names the API key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySetting {
+    /// `api_key`, the key itself or [`KEY_PLACEHOLDER`]
+    Inline(String),
+    /// `api_key_env`
+    Env(String),
+    /// `api_key_file`
+    File(PathBuf),
+    /// `api_key_cmd`
+    Command(String),
+}
+
+/// Writes a config file for `tier` to `path` and checks that it loads.
+///
+/// # Errors
+/// Returns error if `path` exists and `force` is not set, the file cannot be
+/// written, or the generated file does not load.
+pub fn run(tier: PlanTier, key: &KeySetting, path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        anyhow::bail!(
+            "{} already exists; pass --force to overwrite it",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, render(tier, key))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    LayeredConfig::from_file(path).context("Generated config does not load")?;
+
+    println!("Wrote {} for the {} tier", path.display(), tier_name(tier));
+    if *key == KeySetting::Inline(KEY_PLACEHOLDER.to_string()) {
+        println!("Replace {KEY_PLACEHOLDER} with your key, or rerun with --api-key, --key-env, --key-file or --key-cmd");
+    }
+    println!("Run `alphavantage_cli doctor` to check it");
+    Ok(())
+}
+
+/// The generated file: the key setting, the tier's rate limits and cache TTLs.
+#[must_use]
+pub fn render(tier: PlanTier, key: &KeySetting) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Generated by `alphavantage_cli config init --tier {}`",
+        tier_name(tier)
+    );
+    let _ = writeln!(out, "# See alphavantage.toml.template for every setting\n");
+
+    out.push_str("[api]\n");
+    let (field, value) = match key {
+        KeySetting::Inline(key) => ("api_key", key.clone()),
+        KeySetting::Env(var) => ("api_key_env", var.clone()),
+        KeySetting::File(file) => ("api_key_file", file.display().to_string()),
+        KeySetting::Command(cmd) => ("api_key_cmd", cmd.clone()),
+    };
+    let _ = writeln!(out, "{field} = {}\n", Value::String(value));
+
+    out.push_str("[rate_limit]\n");
+    match tier.per_minute() {
+        None => {
+            out.push_str("# Free tier: 25 calls/day, one call per second\n");
+            out.push_str("daily_limit = 25\nmin_delay_ms = 1000\n\n");
+        }
+        Some(per_minute) => {
+            let _ = writeln!(
+                out,
+                "# {per_minute} calls/minute with no daily cap, spread evenly over the minute"
+            );
+            let _ = writeln!(
+                out,
+                "windows = [{{ limit = {per_minute}, per = \"minute\" }}]\nmin_delay_ms = {}\n",
+                60_000 / per_minute
+            );
+        }
+    }
+
+    out.push_str("# How long each endpoint's responses stay fresh: s, m, h or d\n");
+    out.push_str("[cache.ttl]\n");
+    for (endpoint, ttl) in [
+        ("OVERVIEW", "1d"),
+        ("BALANCE_SHEET", "30d"),
+        ("INCOME_STATEMENT", "30d"),
+        ("CASH_FLOW", "30d"),
+        ("GLOBAL_QUOTE", "1m"),
+        ("LISTING_STATUS", "7d"),
+    ] {
+        let _ = writeln!(out, "{endpoint} = \"{ttl}\"");
+    }
+    out
+}
+
+fn tier_name(tier: PlanTier) -> String {
+    clap::ValueEnum::to_possible_value(&tier)
+        .map_or_else(|| format!("{tier:?}"), |v| v.get_name().to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_every_tier_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        for tier in PlanTier::value_variants() {
+            let path = dir.path().join(format!("{}.toml", tier_name(*tier)));
+            std::fs::write(&path, render(*tier, &KeySetting::Env("AV_KEY".into()))).unwrap();
+            let config = LayeredConfig::from_file(&path).unwrap().config;
+            assert_eq!(config.api.api_key_env.as_deref(), Some("AV_KEY"));
+            match tier.per_minute() {
+                None => assert_eq!(config.rate_limit.daily_limit, Some(25)),
+                Some(n) => {
+                    assert_eq!(config.rate_limit.daily_limit, None, "{tier:?}");
+                    assert_eq!(config.rate_limit.windows.first().map(|w| w.limit), Some(n));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alphavantage.toml");
+        let key = KeySetting::Inline("KEY \"quoted\"".to_string());
+        run(PlanTier::Free, &key, &path, false).unwrap();
+        let config = LayeredConfig::from_file(&path).unwrap().config;
+        assert_eq!(config.api.api_key, "KEY \"quoted\"");
+
+        let err = run(PlanTier::Premium75, &key, &path, false).unwrap_err();
+        assert!(err.to_string().contains("--force"), "{err}");
+        run(PlanTier::Premium75, &key, &path, true).unwrap();
+        let config = LayeredConfig::from_file(&path).unwrap().config;
+        assert_eq!(config.rate_limit.windows.len(), 1);
+    }
+}
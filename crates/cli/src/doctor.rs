@@ -0,0 +1,398 @@
+use crate::api_config::ApiConfig;
+use crate::cli_args::CliArgs;
+use crate::config::live_keys;
+use crate::layered_config::LayeredConfig;
+use alphavantage_client::http_client::BASE_URL;
+use alphavantage_client::{AlphaVantageClient, QuotaStatus, RateLimiter};
+use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol};
+use alphavantage_core::ports::ApiClient;
+use alphavantage_core::ExplorerError;
+use std::fmt::{self, Write};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+/// Temporary file written to check that a directory is writable.
+const PROBE_NAME: &str = ".alphavantage-doctor-probe";
+
+/// Outcome of one doctor check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// Works, but likely not as intended
+    Warn,
+    /// Runs will fail until fixed
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Ok => "ok",
+            Self::Warn => "warn",
+            Self::Fail => "FAIL",
+        })
+    }
+}
+
+/// One line of the doctor report, with a suggested fix unless it passed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// What `doctor` should check beyond the configuration.
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+    /// Skip the API call verifying the key
+    pub offline: bool,
+    /// Endpoint the key is verified against (default: the public API)
+    pub base_url: Option<String>,
+    /// Symbol for the verifying `GLOBAL_QUOTE` call
+    pub symbol: TickerSymbol,
+    /// Rate limiter state file (default: the per-user state file)
+    pub state_file: Option<PathBuf>,
+}
+
+/// Runs every check for `args`. A check that cannot run because an earlier one
+/// failed is left out.
+pub async fn run(args: &CliArgs, options: &DoctorOptions) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let config = match LayeredConfig::load(args) {
+        Ok(layered) => {
+            checks.push(config_check(&layered));
+            layered.config
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "config",
+                format!("{e:#}"),
+                "Fix the file named above, or replace it with `alphavantage_cli config init --force`",
+            ));
+            ApiConfig::default()
+        }
+    };
+
+    let key = match live_keys(&config.api) {
+        Ok((key, pool)) => {
+            checks.push(Check::ok(
+                "api key",
+                format!(
+                    "{} key(s) configured, primary {}",
+                    pool.len() + 1,
+                    key.fingerprint()
+                ),
+            ));
+            Some(key)
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "api key",
+                format!("{e:#}"),
+                "Pass --api-key, set ALPHA_VANTAGE_API_KEY, or run `alphavantage_cli config init --key-env <VAR>`",
+            ));
+            None
+        }
+    };
+
+    let state_path = options
+        .state_file
+        .clone()
+        .unwrap_or_else(RateLimiter::resolve_state_path);
+    let limiter = RateLimiter::with_limits(state_path.clone(), config.rate_limit.to_rate_limits());
+    checks.push(state_file_check(&limiter, key.as_ref()));
+    checks.push(dir_check("out dir", &args.out_dir, "--out-dir"));
+    if !args.no_cache && !config.cache.ttl.is_empty() {
+        match config.cache.to_cache() {
+            Ok(cache) => checks.push(dir_check("cache dir", cache.dir(), "[cache] dir")),
+            Err(e) => checks.push(Check::fail(
+                "cache dir",
+                format!("{e:#}"),
+                "Fix the TTLs in [cache.ttl]",
+            )),
+        }
+    }
+
+    if let Some(key) = key {
+        if options.offline {
+            checks.push(Check::warn(
+                "key check",
+                "Skipped (--offline)",
+                "Run without --offline to verify the key with one call",
+            ));
+        } else {
+            checks.push(key_check(&config, options, state_path, &key).await);
+        }
+    }
+    checks
+}
+
+/// Human-readable report, one line per check with fixes indented below.
+#[must_use]
+pub fn render(checks: &[Check]) -> String {
+    let mut out = String::new();
+    for check in checks {
+        let _ = writeln!(
+            out,
+            "[{:<4}] {:<10} {}",
+            check.status, check.name, check.detail
+        );
+        if let Some(fix) = &check.fix {
+            let _ = writeln!(out, "{:18}fix: {fix}", "");
+        }
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    let _ = write!(
+        out,
+        "\n{} passed, {warned} warning(s), {failed} failed",
+        checks.len() - failed - warned
+    );
+    out
+}
+
+fn config_check(layered: &LayeredConfig) -> Check {
+    let profile = layered
+        .profile
+        .as_deref()
+        .map(|p| format!(" (profile {p})"))
+        .unwrap_or_default();
+    if layered.files.is_empty() {
+        return Check::warn(
+            "config",
+            format!("No config file found, using built-in defaults{profile}"),
+            "Run `alphavantage_cli config init --tier <TIER>` to create alphavantage.toml",
+        );
+    }
+    let files: Vec<String> = layered
+        .files
+        .iter()
+        .map(|f| f.display().to_string())
+        .collect();
+    Check::ok("config", format!("Loaded {}{profile}", files.join(", ")))
+}
+
+fn state_file_check(limiter: &RateLimiter, key: Option<&ApiKey>) -> Check {
+    let path = limiter.state_path();
+    // The rate limiter creates missing directories, so probe the nearest existing one
+    let probe = if path.exists() || path.parent().is_none_or(Path::exists) {
+        path.to_path_buf()
+    } else {
+        nearest_existing(path).join(PROBE_NAME)
+    };
+    if let Err(e) = probe_file(&probe) {
+        return Check::fail(
+            "state file",
+            format!("{} is not writable: {e}", path.display()),
+            "Fix the permissions; bulk runs always use the default state file, only quota and doctor take --state-file",
+        );
+    }
+    if !path.exists() {
+        // Nothing counted yet; reading the status would create the directory
+        return Check::ok(
+            "state file",
+            format!("{} will be created on first use", path.display()),
+        );
+    }
+    let status = match limiter.status() {
+        Ok(status) => status,
+        Err(e) => {
+            return Check::fail(
+                "state file",
+                format!("{} cannot be read: {e}", path.display()),
+                "Delete the file to start counting from scratch",
+            )
+        }
+    };
+    let exhausted = key.and_then(|key| {
+        status
+            .iter()
+            .find(|s| s.fingerprint == key.fingerprint() && s.remaining == Some(0))
+    });
+    exhausted.map_or_else(
+        || Check::ok("state file", format!("{} is writable", path.display())),
+        |s| exhausted_check(path, s),
+    )
+}
+
+/// Warns that the key behind `status` has no calls left.
+fn exhausted_check(path: &Path, status: &QuotaStatus) -> Check {
+    let reset = status
+        .next_reset
+        .map_or_else(|| "the next reset".to_string(), |t| t.to_rfc3339());
+    Check::warn(
+        "state file",
+        format!(
+            "{}: no calls left for {} until {reset}",
+            path.display(),
+            status.fingerprint
+        ),
+        "Wait for the reset, or `alphavantage_cli quota set-remaining <N>` if the provider allows more",
+    )
+}
+
+fn dir_check(name: &'static str, dir: &Path, setting: &str) -> Check {
+    // The directory is created on first use, so check the nearest existing ancestor
+    let existing = nearest_existing(dir);
+    if !existing.is_dir() {
+        return Check::fail(
+            name,
+            format!("{} is not a directory", existing.display()),
+            format!("Point {setting} at a directory"),
+        );
+    }
+    let probe = existing.join(PROBE_NAME);
+    match probe_file(&probe) {
+        Ok(()) => Check::ok(name, format!("{} is writable", dir.display())),
+        Err(e) => Check::fail(
+            name,
+            format!("Cannot write to {}: {e}", existing.display()),
+            format!("Fix the directory's permissions or point {setting} elsewhere"),
+        ),
+    }
+}
+
+/// The deepest part of `path` that exists, or the current directory.
+fn nearest_existing(path: &Path) -> &Path {
+    path.ancestors()
+        .find(|p| p.exists())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+/// Opens `path` for writing without changing it, removing it again if it did not exist.
+fn probe_file(path: &Path) -> std::io::Result<()> {
+    let existed = path.exists();
+    OpenOptions::new().append(true).create(true).open(path)?;
+    if !existed {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+async fn key_check(
+    config: &ApiConfig,
+    options: &DoctorOptions,
+    state_path: PathBuf,
+    key: &ApiKey,
+) -> Check {
+    let base_url = options.base_url.as_deref().unwrap_or(BASE_URL);
+    let limits = config.rate_limit.to_rate_limits();
+    let client = match config.http.to_settings().and_then(|http| {
+        Ok(AlphaVantageClient::with_settings(
+            base_url,
+            limits.clone(),
+            &http,
+        )?)
+    }) {
+        Ok(client) => client.with_rate_limiter(RateLimiter::with_limits(state_path, limits)),
+        Err(e) => return Check::fail("key check", format!("{e:#}"), "Fix the [http] section"),
+    };
+
+    let result = client
+        .fetch_ticker_endpoint(EndpointName::GlobalQuote, &options.symbol, None, key)
+        .await;
+    match result {
+        Ok(_) => Check::ok(
+            "key check",
+            format!(
+                "{base_url} answered GLOBAL_QUOTE for {} (1 call used)",
+                options.symbol.as_str()
+            ),
+        ),
+        Err(ExplorerError::ProviderError(msg)) if msg.to_ascii_lowercase().contains("apikey") => {
+            Check::fail(
+                "key check",
+                format!("Key rejected: {msg}"),
+                "Check the key, or claim one at https://www.alphavantage.co/support/#api-key",
+            )
+        }
+        Err(ExplorerError::RateLimited) => Check::warn(
+            "key check",
+            "Out of quota, key not verified",
+            "Check `alphavantage_cli quota status`, or rerun with --offline",
+        ),
+        Err(e @ (ExplorerError::Network(_) | ExplorerError::HttpStatus(_))) => Check::fail(
+            "key check",
+            format!("Could not reach {base_url}: {e}"),
+            "Check network access and the proxy and ca_bundle in [http]",
+        ),
+        Err(e) => Check::warn(
+            "key check",
+            format!("The call failed: {e}"),
+            "Retry with another --symbol; the key itself was not rejected",
+        ),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("not/yet/created");
+        assert_eq!(
+            dir_check("out dir", &missing, "--out-dir").status,
+            Status::Ok
+        );
+        assert!(!dir.path().join(".alphavantage-doctor-probe").exists());
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        let check = dir_check("out dir", &file.join("sub"), "--out-dir");
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.fix.unwrap().contains("--out-dir"));
+    }
+
+    #[test]
+    fn test_render() {
+        let checks = [
+            Check::ok("config", "Loaded alphavantage.toml"),
+            Check::fail("api key", "No key", "Pass --api-key"),
+        ];
+        let report = render(&checks);
+        assert!(
+            report.contains("[ok  ] config     Loaded alphavantage.toml"),
+            "{report}"
+        );
+        assert!(report.contains("[FAIL] api key    No key\n"), "{report}");
+        assert!(report.contains("fix: Pass --api-key"), "{report}");
+        assert!(
+            report.ends_with("1 passed, 0 warning(s), 1 failed"),
+            "{report}"
+        );
+    }
+}
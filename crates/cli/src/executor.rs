@@ -1,20 +1,24 @@
-#![allow(clippy::expect_used)]
-
 use crate::config::Config;
 use crate::index_generator::{generate_index, ExecutionResults};
+use crate::manifest::RunManifest;
+use crate::planner::{bulk_calls, PlannedCall, RunPlan};
 use crate::progress::ProgressReporter;
-use alphavantage_client::{
-    create_client, FileSystemJsonPersister, MarkdownWriterImpl, SchemaAnalyzerImpl,
-};
+use alphavantage_client::{FileSystemJsonPersister, MarkdownWriterImpl, SchemaAnalyzerImpl};
 use alphavantage_core::domain::{EndpointName, SchemaTable, TickerSymbol};
+use alphavantage_core::logic::insider::InsiderActivity;
 use alphavantage_core::logic::json_to_table::parse_json_to_tables;
+use alphavantage_core::logic::news::NewsFeed;
 use alphavantage_core::ports::{JsonPersister, MarkdownWriter};
 use anyhow::Result;
-use std::collections::HashMap;
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 use std::path::PathBuf;
 use tracing::instrument;
 
+/// Most bullish and bearish articles listed per ticker
+const TOP_ARTICLES: usize = 3;
+
 pub struct Executor {
     config: Config,
 }
@@ -28,17 +32,67 @@ impl Executor {
 
     /// Runs the executor to fetch all endpoints for all configured symbols
     ///
-    /// # Errors
-    /// Returns error if API calls fail, file I/O fails, or markdown generation fails
+    /// The run is planned up front against the remaining quota; with `dry_run` the
+    /// plan is printed and no API calls are made. Progress is checkpointed to a run
+    /// manifest in `out_dir`; with `resume`, tasks it records as done are not fetched
+    /// again and their raw JSON is reused instead.
     ///
-    /// # Panics
-    /// May panic if ticker symbol not found in pre-initialized results map (internal logic error)
+    /// # Errors
+    /// Returns error if the plan does not fit the quota under the `Refuse` policy,
+    /// the manifest cannot be read or written, API calls fail, file I/O fails, or
+    /// markdown generation fails
     pub async fn run(&self) -> Result<()> {
-        let client = create_client(
-            self.config.client_mode,
-            self.config.daily_limit,
-            self.config.min_delay_ms,
-        );
+        let mut manifest = if self.config.resume {
+            RunManifest::load_or_new(&self.config.out_dir)?
+        } else {
+            RunManifest::new(&self.config.out_dir)
+        };
+        let (done, pending): (Vec<PlannedCall>, Vec<PlannedCall>) =
+            bulk_calls(&self.config.symbols, &self.config.endpoints)
+                .into_iter()
+                .partition(|call| manifest.is_done(call, &self.call_params(call)));
+        // Fresh cached responses cost no quota, so only the rest are planned
+        let (cached, pending) = self.partition_cached(pending);
+
+        let plan = RunPlan::build(
+            pending,
+            self.config.remaining_quota()?,
+            self.config.pool_daily_limit(),
+            self.config.over_quota,
+        )?;
+
+        if self.config.dry_run {
+            if !done.is_empty() {
+                println!("Resuming: {} calls already done", done.len());
+            }
+            if !cached.is_empty() {
+                println!("From cache: {} calls (no quota used)", cached.len());
+            }
+            for params in self.config.endpoints.describe_params() {
+                println!("Parameters: {params}");
+            }
+            println!("{}", plan.render(&self.config.rate_limits));
+            return Ok(());
+        }
+        if !done.is_empty() {
+            tracing::info!(
+                "Resuming run: {} calls already done, {} to fetch",
+                done.len(),
+                plan.total_calls
+            );
+        }
+
+        let deferred = plan.not_run().count();
+        if deferred > 0 {
+            tracing::warn!(
+                "Quota allows {} of {} calls today; {} will not run (see --dry-run)",
+                plan.today().len(),
+                plan.total_calls,
+                deferred
+            );
+        }
+
+        let client = self.config.create_client()?;
         let persister = FileSystemJsonPersister::new();
         let analyzer = SchemaAnalyzerImpl::new();
 
@@ -47,80 +101,53 @@ impl Executor {
         let mut tables_accumulator: HashMap<EndpointName, HashMap<TickerSymbol, Vec<SchemaTable>>> =
             HashMap::new();
 
-        // 4 market-wide endpoints
-        let market_endpoints = [
-            EndpointName::MarketStatus,
-            EndpointName::TopGainersLosers,
-            EndpointName::ListingStatus,
-            EndpointName::NewsSentiment,
-        ];
-
-        // 13 ticker-specific endpoints per PRD
-        let ticker_endpoints = [
-            EndpointName::Overview,
-            EndpointName::IncomeStatement,
-            EndpointName::BalanceSheet,
-            EndpointName::CashFlow,
-            EndpointName::Earnings,
-            EndpointName::EarningsEstimates,
-            EndpointName::NewsSentiment,
-            EndpointName::InsiderTransactions,
-            EndpointName::Dividends,
-            EndpointName::Splits,
-            EndpointName::SharesOutstanding,
-            EndpointName::EarningsCalendar,
-            EndpointName::EarningsCallTranscript,
-        ];
-
-        let total_tasks =
-            market_endpoints.len() + (self.config.symbols.len() * ticker_endpoints.len());
-        let progress = ProgressReporter::new(total_tasks);
-
-        // 1. Fetch Market Wide
-        for &endpoint in &market_endpoints {
-            let success = self
-                .fetch_and_process(client.as_ref(), &persister, &progress, endpoint, None)
-                .await?;
-            results.market_status.insert(endpoint, success);
-        }
-
-        // 2. Fetch per Ticker
-        for ticker in &self.config.symbols {
-            results.ticker_status.insert(ticker.clone(), HashMap::new());
-
-            for &endpoint in &ticker_endpoints {
-                let (success, tables_opt) = self
-                    .fetch_and_process_ticker(
-                        client.as_ref(),
-                        &persister,
-                        &progress,
-                        endpoint,
-                        ticker,
-                    )
-                    .await?;
-
-                // Track status
-                let entry = results
-                    .ticker_status
-                    .get_mut(ticker)
-                    .expect("ticker should exist in pre-initialized map");
-                entry.insert(endpoint, (success, None));
-                // We don't have error msg easily propagated here without changing signature more,
-                // but fetch_and_process_ticker logs it.
-                // Let's assume success=true means no error msg.
-                // To do this properly, fetch_and_process_ticker should return error string.
-                // Refactoring below.
-
-                // Accumulate tables
-                if let Some(tables) = tables_opt {
-                    tables_accumulator
-                        .entry(endpoint)
-                        .or_default()
-                        .insert(ticker.clone(), tables);
+        // 1. Reuse tasks finished by an earlier run
+        self.reuse_done(&done, &mut results, &mut tables_accumulator);
+
+        let progress = ProgressReporter::new(cached.len() + plan.today().len());
+
+        // 2. Fetch cached and planned calls: market-wide first, then per ticker.
+        // Up to `concurrency` calls are in flight while the rate limiter paces them;
+        // results are processed in plan order so markdown output stays deterministic.
+        let mut fetches = stream::iter(cached.iter().chain(plan.today()))
+            .map(|call| self.fetch(client.as_ref(), &progress, call))
+            .buffered(self.config.concurrency.get());
+        while let Some((call, result)) = fetches.next().await {
+            let endpoint = call.endpoint;
+            let Some(ticker) = &call.ticker else {
+                if let Some(json) = &result {
+                    self.process_market(&persister, endpoint, json);
                 }
+                let success = result.is_some();
+                results.market_status.insert(endpoint, success);
+                manifest.record(call, BTreeMap::new(), success)?;
+                continue;
+            };
+
+            let success = result.is_some();
+            let tables_opt =
+                result.and_then(|json| self.process_ticker(&persister, endpoint, ticker, &json));
+            manifest.record(call, self.call_params(call), success)?;
+
+            // Track status
+            results
+                .ticker_status
+                .entry(ticker.clone())
+                .or_default()
+                .insert(endpoint, (success, None));
+
+            // Accumulate tables
+            if let Some(tables) = tables_opt {
+                tables_accumulator
+                    .entry(endpoint)
+                    .or_default()
+                    .insert(ticker.clone(), tables);
             }
         }
 
+        // Calls left out by the plan still show up in the index as not fetched
+        Self::mark_not_run(&plan, &mut results);
+
         // 3. Compute Schema Diffs
         for (endpoint, ticker_map) in &tables_accumulator {
             if ticker_map.len() > 1 {
@@ -132,7 +159,8 @@ impl Executor {
         }
 
         results.end_time = Some(chrono::Local::now());
-        progress.summary().await;
+        results.key_usage = client.key_usage();
+        progress.summary(&results.key_usage).await;
 
         // 4. Generate Index
         generate_index(&results, &self.config.out_dir)?;
@@ -140,22 +168,123 @@ impl Executor {
         Ok(())
     }
 
-    // Refactored to return success status for market endpoints
-    #[instrument(skip(self, client, persister, progress))]
-    async fn fetch_and_process(
+    /// Splits off calls the response cache can answer without using quota.
+    fn partition_cached(&self, calls: Vec<PlannedCall>) -> (Vec<PlannedCall>, Vec<PlannedCall>) {
+        let Some(cache) = self
+            .config
+            .cache
+            .as_ref()
+            .filter(|_| !self.config.refresh_cache)
+        else {
+            return (Vec::new(), calls);
+        };
+        calls.into_iter().partition(|call| {
+            cache.is_fresh(
+                call.endpoint,
+                call.ticker.as_ref().map(TickerSymbol::as_str),
+                &self.call_params(call),
+            )
+        })
+    }
+
+    /// Parameters sent with `call`; market-wide calls take none.
+    fn call_params(&self, call: &PlannedCall) -> BTreeMap<String, String> {
+        if call.ticker.is_some() {
+            self.config.endpoints.params(call.endpoint)
+        } else {
+            BTreeMap::new()
+        }
+    }
+
+    /// Records calls the plan left out (skipped or deferred) as failed.
+    fn mark_not_run(plan: &RunPlan, results: &mut ExecutionResults) {
+        for call in plan.not_run() {
+            if let Some(ticker) = &call.ticker {
+                results
+                    .ticker_status
+                    .entry(ticker.clone())
+                    .or_default()
+                    .insert(
+                        call.endpoint,
+                        (false, Some("Not run: over daily quota".to_string())),
+                    );
+            } else {
+                results.market_status.insert(call.endpoint, false);
+            }
+        }
+    }
+
+    /// Marks `done` tasks as succeeded and loads their saved tables for schema diffs.
+    fn reuse_done(
+        &self,
+        done: &[PlannedCall],
+        results: &mut ExecutionResults,
+        tables_accumulator: &mut HashMap<EndpointName, HashMap<TickerSymbol, Vec<SchemaTable>>>,
+    ) {
+        for call in done {
+            let endpoint = call.endpoint;
+            let Some(ticker) = &call.ticker else {
+                results.market_status.insert(endpoint, true);
+                continue;
+            };
+            results
+                .ticker_status
+                .entry(ticker.clone())
+                .or_default()
+                .insert(endpoint, (true, None));
+            if let Some(tables) = self.load_raw_tables(endpoint, ticker) {
+                tables_accumulator
+                    .entry(endpoint)
+                    .or_default()
+                    .insert(ticker.clone(), tables);
+            }
+        }
+    }
+
+    /// Tables rebuilt from the raw JSON an earlier run saved, for schema diffs.
+    fn load_raw_tables(
+        &self,
+        endpoint: EndpointName,
+        ticker: &TickerSymbol,
+    ) -> Option<Vec<SchemaTable>> {
+        let path = self.raw_path(endpoint, Some(ticker));
+        let json: serde_json::Value = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())?;
+        parse_json_to_tables(endpoint, &json, Some(3)).ok()
+    }
+
+    /// Where the raw JSON of `endpoint` (for `ticker`, or market-wide) is saved.
+    fn raw_path(&self, endpoint: EndpointName, ticker: Option<&TickerSymbol>) -> PathBuf {
+        let mut path = self.config.out_dir.join("raw");
+        if let Some(t) = ticker {
+            path.push("tickers");
+            path.push(t.as_str());
+        } else {
+            path.push("market");
+        }
+        path.push(format!("{endpoint}.json"));
+        path
+    }
+
+    /// Fetches one planned call, reporting its outcome; `None` when it failed.
+    #[instrument(skip(self, client, progress), fields(ticker = ?call.ticker, endpoint = %call.endpoint))]
+    async fn fetch<'a>(
         &self,
         client: &dyn alphavantage_core::ports::ApiClient,
-        persister: &FileSystemJsonPersister,
         progress: &ProgressReporter,
-        endpoint: EndpointName,
-        ticker: Option<&TickerSymbol>,
-    ) -> Result<bool> {
-        let display_name = ticker.map_or("MARKET", alphavantage_core::domain::TickerSymbol::as_str);
+        call: &'a PlannedCall,
+    ) -> (&'a PlannedCall, Option<serde_json::Value>) {
+        let endpoint = call.endpoint;
+        let display_name = call.ticker.as_ref().map_or("MARKET", TickerSymbol::as_str);
         ProgressReporter::start_fetch(endpoint, display_name);
 
-        let result = if let Some(t) = ticker {
+        let result = if let Some(t) = &call.ticker {
+            let params: HashMap<String, String> =
+                self.config.endpoints.params(endpoint).into_iter().collect();
+            let params = (!params.is_empty()).then_some(&params);
             client
-                .fetch_ticker_endpoint(endpoint, t, None, &self.config.api_key)
+                .fetch_ticker_endpoint(endpoint, t, params, &self.config.api_key)
                 .await
         } else {
             client
@@ -165,98 +294,65 @@ impl Executor {
 
         match result {
             Ok(json) => {
-                if self.config.save_raw {
-                    let mut path = self.config.out_dir.join("raw");
-                    if let Some(t) = ticker {
-                        path.push("tickers");
-                        path.push(t.as_str());
-                    } else {
-                        path.push("market");
-                    }
-                    path.push(format!("{endpoint}.json"));
-
-                    if let Err(e) = persister.save_raw_json(&path, &json) {
-                        tracing::warn!("Failed to save raw JSON for {}: {}", endpoint, e);
-                    }
-                }
-
-                if let Err(e) = self.generate_markdown(endpoint, ticker, &json) {
-                    tracing::warn!("Failed to generate markdown for {}: {}", endpoint, e);
-                }
-
                 progress
                     .finish_fetch(endpoint, display_name, true, None)
                     .await;
-                Ok(true)
+                (call, Some(json))
             }
             Err(e) => {
                 progress
                     .finish_fetch(endpoint, display_name, false, Some(&e.to_string()))
                     .await;
-                Ok(false)
+                (call, None)
             }
         }
     }
 
-    // Separate function for ticker to return Tables and detailed status
-    async fn fetch_and_process_ticker(
+    /// Saves a market-wide response and writes its markdown.
+    fn process_market(
         &self,
-        client: &dyn alphavantage_core::ports::ApiClient,
         persister: &FileSystemJsonPersister,
-        progress: &ProgressReporter,
         endpoint: EndpointName,
-        ticker: &TickerSymbol,
-    ) -> Result<(bool, Option<Vec<SchemaTable>>)> {
-        ProgressReporter::start_fetch(endpoint, ticker.as_str());
-
-        let result = client
-            .fetch_ticker_endpoint(endpoint, ticker, None, &self.config.api_key)
-            .await;
+        json: &serde_json::Value,
+    ) {
+        if self.config.save_raw {
+            let path = self.raw_path(endpoint, None);
+            if let Err(e) = persister.save_raw_json(&path, json) {
+                tracing::warn!("Failed to save raw JSON for {}: {}", endpoint, e);
+            }
+        }
 
-        match result {
-            Ok(json) => {
-                // Save RAW
-                if self.config.save_raw {
-                    let mut path = self.config.out_dir.join("raw");
-                    path.push("tickers");
-                    path.push(ticker.as_str());
-                    path.push(format!("{endpoint}.json"));
-                    let _ = persister.save_raw_json(&path, &json);
-                }
+        if let Err(e) = self.generate_markdown(endpoint, None, json) {
+            tracing::warn!("Failed to generate markdown for {}: {}", endpoint, e);
+        }
+    }
 
-                // Gen Markdown & Extract Tables
-                let tables =
-                    match self.generate_markdown_returning_tables(endpoint, Some(ticker), &json) {
-                        Ok(t) => t,
-                        Err(e) => {
-                            tracing::warn!(
-                                "Failed to process markdown/tables for {}: {}",
-                                endpoint,
-                                e
-                            );
-                            progress
-                                .finish_fetch(
-                                    endpoint,
-                                    ticker.as_str(),
-                                    true,
-                                    Some("Partial success (layout error)"),
-                                )
-                                .await;
-                            return Ok((true, None)); // Fetch succeeded, but parse failed??
-                                                     // Actually generate_markdown calls parse_json_to_tables. If that fails, we can't diff.
-                        }
-                    };
+    /// Saves a ticker response and appends its markdown, returning the tables for
+    /// schema diffs (`None` if they could not be built).
+    fn process_ticker(
+        &self,
+        persister: &FileSystemJsonPersister,
+        endpoint: EndpointName,
+        ticker: &TickerSymbol,
+        json: &serde_json::Value,
+    ) -> Option<Vec<SchemaTable>> {
+        if self.config.save_raw {
+            let path = self.raw_path(endpoint, Some(ticker));
+            let _ = persister.save_raw_json(&path, json);
+        }
 
-                progress
-                    .finish_fetch(endpoint, ticker.as_str(), true, None)
-                    .await;
-                Ok((true, Some(tables)))
-            }
+        // Gen Markdown & Extract Tables
+        match self.generate_markdown_returning_tables(endpoint, Some(ticker), json) {
+            Ok(tables) => Some(tables),
             Err(e) => {
-                progress
-                    .finish_fetch(endpoint, ticker.as_str(), false, Some(&e.to_string()))
-                    .await;
-                Ok((false, None))
+                // The fetch succeeded, but without tables there is nothing to diff
+                tracing::warn!(
+                    "Partial success for {} {}: failed to process markdown/tables: {}",
+                    endpoint,
+                    ticker,
+                    e
+                );
+                None
             }
         }
     }
@@ -286,6 +382,10 @@ impl Executor {
 
         let mut path = self.config.out_dir.clone();
         if let Some(t) = ticker {
+            // Analytics use the whole response and stay out of schema diffs
+            for table in Self::analytics_tables(endpoint, t, json) {
+                writer.write_table(&table)?;
+            }
             path.push("tickers");
             path.push(format!("{}.md", t.as_str()));
             Self::append_to_file(&path, writer.as_str())?;
@@ -297,6 +397,27 @@ impl Executor {
         Ok(tables)
     }
 
+    /// Tables computed from an endpoint's response for the ticker report. A response
+    /// the analytics cannot read only loses these tables, not the endpoint's own.
+    fn analytics_tables(
+        endpoint: EndpointName,
+        ticker: &TickerSymbol,
+        json: &serde_json::Value,
+    ) -> Vec<SchemaTable> {
+        let tables = match endpoint {
+            EndpointName::NewsSentiment => NewsFeed::from_json(json)
+                .map(|feed| feed.ticker_tables(ticker.as_str(), TOP_ARTICLES)),
+            EndpointName::InsiderTransactions => {
+                InsiderActivity::from_json(json).map(|activity| activity.tables())
+            }
+            _ => Ok(Vec::new()),
+        };
+        tables.unwrap_or_else(|e| {
+            tracing::warn!("Skipping {endpoint} analytics for {ticker}: {e}");
+            Vec::new()
+        })
+    }
+
     fn append_to_file(path: &PathBuf, content: &str) -> Result<()> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
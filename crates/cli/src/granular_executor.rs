@@ -1,14 +1,22 @@
 use crate::cli_args::Commands;
 use crate::config::Config;
 use alphavantage_client::{CsvHandler, FileSystemJsonPersister, MarkdownWriterImpl};
-use alphavantage_core::domain::{EndpointName, QuarterParam, TickerSymbol};
-use alphavantage_core::error::Result;
+use alphavantage_core::domain::{EndpointName, TickerSymbol, YearQuarter};
+use alphavantage_core::error::{ExplorerError, Result};
 use alphavantage_core::logic::json_to_table::parse_json_to_tables;
 use alphavantage_core::ports::{ApiClient, JsonPersister, MarkdownWriter};
 use alphavantage_core::util::generate_timestamp;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// Endpoint, symbol, query parameters and output directory for one command
+type RoutedCommand = (
+    EndpointName,
+    TickerSymbol,
+    HashMap<String, String>,
+    Option<PathBuf>,
+);
+
 /// Executor for granular (single-endpoint) commands
 pub struct GranularExecutor<'a> {
     config: &'a Config,
@@ -26,7 +34,7 @@ impl<'a> GranularExecutor<'a> {
     /// # Errors
     /// Returns error if API call fails, file I/O fails, or parsing fails
     pub async fn execute(&self, command: &Commands) -> Result<()> {
-        let (endpoint, symbol, params, output_dir) = Self::route_command(command);
+        let (endpoint, symbol, params, output_dir) = Self::route_command(command)?;
 
         // Make API call (note: API client returns JSON Value, not raw string for now)
         let json_value = self
@@ -37,7 +45,12 @@ impl<'a> GranularExecutor<'a> {
         // Generate timestamped filename
         let timestamp = generate_timestamp();
         let endpoint_name = format!("{endpoint}").to_lowercase().replace('_', "-");
-        let base_filename = format!("{endpoint_name}_{symbol}_{timestamp}");
+        // A transcript's quarter is part of the name, as for ranges, so it can be replayed
+        let quarter = params
+            .get("quarter")
+            .map(|q| format!("{q}_"))
+            .unwrap_or_default();
+        let base_filename = format!("{endpoint_name}_{symbol}_{quarter}{timestamp}");
 
         // Determine output directory (use custom or default)
         let out_dir = output_dir.unwrap_or_else(|| self.config.out_dir.clone());
@@ -66,14 +79,7 @@ impl<'a> GranularExecutor<'a> {
     }
 
     /// Route command to endpoint and extract parameters
-    fn route_command(
-        command: &Commands,
-    ) -> (
-        EndpointName,
-        TickerSymbol,
-        HashMap<String, String>,
-        Option<PathBuf>,
-    ) {
+    fn route_command(command: &Commands) -> Result<RoutedCommand> {
         let mut params = HashMap::new();
 
         let (endpoint, symbol, output) = match command {
@@ -142,15 +148,18 @@ impl<'a> GranularExecutor<'a> {
                 year,
                 quarter,
                 output,
+                ..
             } => {
-                let q_num = match quarter {
-                    QuarterParam::Q1 => "1",
-                    QuarterParam::Q2 => "2",
-                    QuarterParam::Q3 => "3",
-                    QuarterParam::Q4 => "4",
+                // Ranges and --latest are fetched by `transcripts::run`
+                let (Some(year), Some(quarter)) = (year, quarter) else {
+                    return Err(ExplorerError::Validation(
+                        "A single transcript needs --year and --quarter".to_string(),
+                    ));
                 };
                 // API expects quarter=YYYYQx (e.g., 2024Q1)
-                let combined_quarter = format!("{year}Q{q_num}");
+                let combined_quarter = YearQuarter::new(*year, *quarter)
+                    .map_err(ExplorerError::Validation)?
+                    .to_string();
                 params.insert("quarter".to_string(), combined_quarter);
                 (
                     EndpointName::EarningsCallTranscript,
@@ -158,9 +167,19 @@ impl<'a> GranularExecutor<'a> {
                     output.clone(),
                 )
             }
+            Commands::Quota { .. }
+            | Commands::Cache { .. }
+            | Commands::Config { .. }
+            | Commands::Doctor { .. }
+            | Commands::Transcripts { .. } => {
+                return Err(ExplorerError::Validation(
+                    "`quota`, `cache`, `config`, `doctor` and `transcripts` do not call an endpoint"
+                        .to_string(),
+                ));
+            }
         };
 
-        (endpoint, symbol, params, output)
+        Ok((endpoint, symbol, params, output))
     }
 
     /// Detect content type from response
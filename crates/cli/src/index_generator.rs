@@ -1,7 +1,7 @@
 #![allow(clippy::expect_used)]
 
 use alphavantage_client::schema_analyzer::SchemaDiff;
-use alphavantage_core::domain::{EndpointName, TickerSymbol};
+use alphavantage_core::domain::{EndpointName, KeyUsage, TickerSymbol};
 use anyhow::Result;
 use std::collections::HashMap;
 use std::io::Write;
@@ -13,6 +13,7 @@ pub struct ExecutionResults {
     // (success, error_msg)
     pub ticker_status: HashMap<TickerSymbol, HashMap<EndpointName, (bool, Option<String>)>>,
     pub schema_diffs: Vec<SchemaDiff>,
+    pub key_usage: Vec<KeyUsage>,
     pub start_time: chrono::DateTime<chrono::Local>,
     pub end_time: Option<chrono::DateTime<chrono::Local>>,
 }
@@ -25,6 +26,7 @@ impl ExecutionResults {
             market_status: HashMap::new(),
             ticker_status: HashMap::new(),
             schema_diffs: Vec::new(),
+            key_usage: Vec::new(),
             start_time: chrono::Local::now(),
             end_time: None,
         }
@@ -103,6 +105,27 @@ pub fn generate_index(results: &ExecutionResults, out_dir: &Path) -> Result<()>
     }
     writeln!(file)?;
 
+    // API Key Usage Section
+    if !results.key_usage.is_empty() {
+        writeln!(file, "## API Key Usage")?;
+        writeln!(
+            file,
+            "| Key | Calls This Run | Calls Today | Remaining Today |"
+        )?;
+        writeln!(file, "|---|---|---|---|")?;
+        for usage in &results.key_usage {
+            let remaining = usage
+                .remaining_today
+                .map_or_else(|| "-".to_string(), |r| r.to_string());
+            writeln!(
+                file,
+                "| `{}` | {} | {} | {} |",
+                usage.fingerprint, usage.calls_this_run, usage.calls_today, remaining
+            )?;
+        }
+        writeln!(file)?;
+    }
+
     // Schema Diff Section
     if !results.schema_diffs.is_empty() {
         writeln!(file, "## Schema Discrepancies")?;
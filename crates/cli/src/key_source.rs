@@ -0,0 +1,212 @@
+use age::armor::ArmoredReader;
+use age::secrecy::SecretString;
+use alphavantage_core::domain::ApiKey;
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Environment variable holding the passphrase of an age-encrypted key file,
+/// unless `api_key_passphrase_env` names another.
+pub const DEFAULT_PASSPHRASE_ENV: &str = "ALPHA_VANTAGE_KEY_PASSPHRASE";
+
+/// Where the primary API key in `alphavantage.toml` comes from.
+///
+/// Indirections are resolved only when a live client needs the key, so a password
+/// manager is not prompted when `--api-key` already supplies one.
+#[derive(Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// `api_key`: the key itself
+    Inline(String),
+    /// `api_key_file`: a file holding the key
+    File(PathBuf),
+    /// `api_key_env`: an environment variable holding the key
+    Env(String),
+    /// `api_key_cmd`: a shell command printing the key, e.g. a password manager CLI
+    Command(String),
+    /// `api_key_age_file`: a passphrase-encrypted age file holding the key
+    Age {
+        file: PathBuf,
+        passphrase_env: String,
+    },
+}
+
+impl std::fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.describe())
+    }
+}
+
+impl KeySource {
+    /// Where the key comes from, without the key itself.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Inline(_) => "api_key".to_string(),
+            Self::File(path) => format!("api_key_file {}", path.display()),
+            Self::Env(name) => format!("api_key_env {name}"),
+            Self::Command(cmd) => format!("api_key_cmd `{cmd}`"),
+            Self::Age { file, .. } => format!("api_key_age_file {}", file.display()),
+        }
+    }
+
+    /// Reads the key. Surrounding whitespace, such as a trailing newline, is dropped.
+    ///
+    /// # Errors
+    /// Returns error if the file, variable or command yields no key, the command
+    /// fails, or the age file cannot be decrypted.
+    pub fn resolve(&self) -> Result<ApiKey> {
+        let key = match self {
+            Self::Inline(key) => key.clone(),
+            Self::File(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read api_key_file {}", path.display()))?,
+            Self::Env(name) => std::env::var(name)
+                .with_context(|| format!("Environment variable {name} (api_key_env) is not set"))?,
+            Self::Command(cmd) => run_command(cmd)?,
+            Self::Age {
+                file,
+                passphrase_env,
+            } => decrypt_age_file(file, passphrase_env)?,
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            anyhow::bail!("{} yielded an empty API key", self.describe());
+        }
+        Ok(ApiKey::new(key))
+    }
+}
+
+fn run_command(cmd: &str) -> Result<String> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    let output = command
+        .arg(cmd)
+        .output()
+        .with_context(|| format!("Failed to run api_key_cmd `{cmd}`"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "api_key_cmd `{cmd}` failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout)
+        .with_context(|| format!("api_key_cmd `{cmd}` printed invalid UTF-8"))
+}
+
+/// Decrypts an age file made with `age --passphrase` (armored or binary).
+fn decrypt_age_file(file: &Path, passphrase_env: &str) -> Result<String> {
+    let passphrase = std::env::var(passphrase_env).with_context(|| {
+        format!(
+            "Set {passphrase_env} to the passphrase of api_key_age_file {}",
+            file.display()
+        )
+    })?;
+    let encrypted = std::fs::read(file)
+        .with_context(|| format!("Failed to read api_key_age_file {}", file.display()))?;
+    let decrypt = || -> Result<String> {
+        let identity = age::scrypt::Identity::new(SecretString::from(passphrase));
+        let decryptor = age::Decryptor::new_buffered(ArmoredReader::new(encrypted.as_slice()))?;
+        let mut reader = decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity))?;
+        let mut key = String::new();
+        reader.read_to_string(&mut key)?;
+        Ok(key)
+    };
+    decrypt().with_context(|| format!("Failed to decrypt api_key_age_file {}", file.display()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use age::armor::{ArmoredWriter, Format};
+    use std::io::Write;
+
+    fn encrypt(passphrase: &str, plaintext: &str) -> Vec<u8> {
+        let mut recipient = age::scrypt::Recipient::new(SecretString::from(passphrase.to_string()));
+        // Keep the test fast; real files use age's default work factor
+        recipient.set_work_factor(2);
+        let encryptor =
+            age::Encryptor::with_recipients(std::iter::once(&recipient as &dyn age::Recipient))
+                .unwrap();
+        let mut out = Vec::new();
+        let armor = ArmoredWriter::wrap_output(&mut out, Format::AsciiArmor).unwrap();
+        let mut writer = encryptor.wrap_output(armor).unwrap();
+        writer.write_all(plaintext.as_bytes()).unwrap();
+        writer.finish().unwrap().finish().unwrap();
+        out
+    }
+
+    #[test]
+    fn test_file_and_command_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("key.txt"), "FILE_KEY\n").unwrap();
+        let source = KeySource::File(dir.path().join("key.txt"));
+        assert_eq!(source.resolve().unwrap().secret(), "FILE_KEY");
+
+        std::fs::write(dir.path().join("blank.txt"), "\n").unwrap();
+        let err = KeySource::File(dir.path().join("blank.txt"))
+            .resolve()
+            .unwrap_err();
+        assert!(err.to_string().contains("empty API key"), "{err}");
+
+        let cmd = KeySource::Command("echo CMD_KEY".to_string());
+        assert_eq!(cmd.resolve().unwrap().secret(), "CMD_KEY");
+        let err = KeySource::Command("echo locked >&2; exit 3".to_string())
+            .resolve()
+            .unwrap_err();
+        assert!(err.to_string().contains("locked"), "{err}");
+    }
+
+    #[test]
+    fn test_env_source() {
+        std::env::set_var("ALPHAVANTAGE_TEST_ENV_KEY", " ENV_KEY ");
+        let source = KeySource::Env("ALPHAVANTAGE_TEST_ENV_KEY".to_string());
+        assert_eq!(source.resolve().unwrap().secret(), "ENV_KEY");
+
+        let err = KeySource::Env("ALPHAVANTAGE_TEST_UNSET_KEY".to_string())
+            .resolve()
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("ALPHAVANTAGE_TEST_UNSET_KEY"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_age_file_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("key.age");
+        std::fs::write(&file, encrypt("correct horse", "AGE_KEY\n")).unwrap();
+        std::env::set_var("ALPHAVANTAGE_TEST_PASSPHRASE", "correct horse");
+        let source = KeySource::Age {
+            file: file.clone(),
+            passphrase_env: "ALPHAVANTAGE_TEST_PASSPHRASE".to_string(),
+        };
+        assert_eq!(source.resolve().unwrap().secret(), "AGE_KEY");
+
+        std::fs::write(&file, encrypt("another passphrase", "AGE_KEY")).unwrap();
+        let err = format!("{:#}", source.resolve().unwrap_err());
+        assert!(err.contains("Failed to decrypt"), "{err}");
+
+        let missing = KeySource::Age {
+            file,
+            passphrase_env: "ALPHAVANTAGE_TEST_UNSET_PASSPHRASE".to_string(),
+        };
+        let err = missing.resolve().unwrap_err().to_string();
+        assert!(err.contains("ALPHAVANTAGE_TEST_UNSET_PASSPHRASE"), "{err}");
+    }
+
+    #[test]
+    fn test_debug_hides_inline_key() {
+        let debug = format!("{:?}", KeySource::Inline("SECRET".to_string()));
+        assert_eq!(debug, "api_key");
+    }
+}
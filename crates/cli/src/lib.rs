@@ -1,8 +1,19 @@
 // Library interface for integration tests
 pub mod api_config;
+pub mod cache;
 pub mod cli_args;
 pub mod config;
+pub mod config_init;
+pub mod doctor;
 pub mod executor;
 pub mod granular_executor;
 pub mod index_generator;
+pub mod key_source;
+pub mod layered_config;
+pub mod manifest;
+pub mod planner;
 pub mod progress;
+pub mod quota;
+pub mod symbols;
+pub mod transcript_report;
+pub mod transcripts;
@@ -1,10 +1,19 @@
-use alphavantage_cli::cli_args::{CliArgs, LogFormat};
+use alphavantage_cli::cache;
+use alphavantage_cli::cli_args::{CliArgs, Commands, ConfigAction, LogFormat};
 use alphavantage_cli::config::Config;
+use alphavantage_cli::config_init::{self, KeySetting, KEY_PLACEHOLDER};
+use alphavantage_cli::doctor::{self, DoctorOptions, Status};
 use alphavantage_cli::executor::Executor;
 use alphavantage_cli::granular_executor;
+use alphavantage_cli::layered_config::{self, LayeredConfig};
+use alphavantage_cli::quota;
+use alphavantage_cli::transcript_report;
+use alphavantage_cli::transcripts;
+use anyhow::Context;
 use clap::Parser;
 use std::process;
 use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 #[tokio::main]
 async fn main() {
@@ -12,9 +21,26 @@ async fn main() {
 
     // Setup Tracing
     let log_level = tracing::Level::from(args.log_level);
+    // `quota`, `cache`, `config` and `doctor` print their report on stdout, so keep
+    // logs out of the way
+    let writer = if matches!(
+        args.command,
+        Some(
+            Commands::Quota { .. }
+                | Commands::Cache { .. }
+                | Commands::Config { .. }
+                | Commands::Doctor { .. }
+                | Commands::Transcripts { .. }
+        )
+    ) {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     let subscriber_builder = tracing_subscriber::fmt()
         .with_max_level(log_level)
-        .with_span_events(FmtSpan::CLOSE);
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(writer);
 
     match args.log_format {
         LogFormat::Json => {
@@ -25,11 +51,58 @@ async fn main() {
         }
     }
 
+    // `config init` replaces the config files and `doctor` reports their errors, so
+    // neither may fail on loading them
+    match &args.command {
+        Some(Commands::Config {
+            action: ConfigAction::Init { .. },
+        }) => {
+            if let Err(e) = config_init(&args) {
+                tracing::error!("Config init failed: {:#}", e);
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Doctor { .. }) => {
+            if !doctor(&args).await {
+                process::exit(1);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    // Quota, cache and config inspection and the transcripts report need the
+    // config files but no client
+    if matches!(
+        args.command,
+        Some(
+            Commands::Quota { .. }
+                | Commands::Cache { .. }
+                | Commands::Config { .. }
+                | Commands::Transcripts { .. }
+        )
+    ) {
+        let layered = match LayeredConfig::load(&args) {
+            Ok(layered) => layered,
+            Err(e) => {
+                tracing::error!("Configuration error: {:#}", e);
+                process::exit(1);
+            }
+        };
+        let result = inspect(&args, &layered);
+        if let Err(e) = result {
+            tracing::error!("{:#}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
     // Load Config
     let config = match Config::from_args(args.clone()) {
         Ok(c) => c,
         Err(e) => {
-            tracing::error!("Configuration error: {}", e);
+            tracing::error!("Configuration error: {:#}", e);
             process::exit(1);
         }
     };
@@ -39,16 +112,9 @@ async fn main() {
 
     // Check if granular command or bulk mode
     if let Some(command) = args.command {
-        // Granular mode: single endpoint
-        let client = alphavantage_client::create_client(
-            config.client_mode,
-            config.daily_limit,
-            config.min_delay_ms,
-        );
-        let granular_exec = granular_executor::GranularExecutor::new(&config, client.as_ref());
-
-        if let Err(e) = granular_exec.execute(&command).await {
-            tracing::error!("Granular execution failed: {}", e);
+        // Granular mode: single endpoint, or a range of transcripts
+        if let Err(e) = granular(&config, &command).await {
+            tracing::error!("{:#}", e);
             process::exit(1);
         }
     } else {
@@ -63,3 +129,129 @@ async fn main() {
 
     tracing::info!("Done.");
 }
+
+/// Runs a granular command; a transcript range spans several calls and is
+/// planned against the quota.
+async fn granular(config: &Config, command: &Commands) -> anyhow::Result<()> {
+    let client = config.create_client().context("Configuration error")?;
+    if let Some(request) =
+        transcripts::RangeRequest::from_command(command).context("Configuration error")?
+    {
+        return transcripts::run(config, client.as_ref(), &request)
+            .await
+            .context("Transcript range failed");
+    }
+    granular_executor::GranularExecutor::new(config, client.as_ref())
+        .execute(command)
+        .await
+        .context("Granular execution failed")
+}
+
+/// Writes the config file described by `config init`.
+fn config_init(args: &CliArgs) -> anyhow::Result<()> {
+    let Some(Commands::Config {
+        action:
+            ConfigAction::Init {
+                tier,
+                key_env,
+                key_file,
+                key_cmd,
+                path,
+                user,
+                force,
+            },
+    }) = &args.command
+    else {
+        return Ok(());
+    };
+    let key = match (key_env, key_file, key_cmd) {
+        (Some(var), _, _) => KeySetting::Env(var.clone()),
+        (_, Some(file), _) => KeySetting::File(file.clone()),
+        (_, _, Some(cmd)) => KeySetting::Command(cmd.clone()),
+        _ => KeySetting::Inline(
+            args.api_key
+                .clone()
+                .unwrap_or_else(|| KEY_PLACEHOLDER.to_string()),
+        ),
+    };
+    let path = if *user {
+        layered_config::user_config_path()
+            .context("No per-user config directory on this platform")?
+    } else {
+        path.clone().unwrap_or_else(|| "alphavantage.toml".into())
+    };
+    config_init::run(*tier, &key, &path, *force)
+}
+
+/// Prints the `doctor` report; `false` if any check failed.
+async fn doctor(args: &CliArgs) -> bool {
+    let Some(Commands::Doctor {
+        offline,
+        base_url,
+        symbol,
+        state_file,
+    }) = &args.command
+    else {
+        return true;
+    };
+    let options = DoctorOptions {
+        offline: *offline,
+        base_url: base_url.clone(),
+        symbol: symbol.clone(),
+        state_file: state_file.clone(),
+    };
+    let checks = doctor::run(args, &options).await;
+    println!("{}", doctor::render(&checks));
+    !checks.iter().any(|c| c.status == Status::Fail)
+}
+
+/// Runs the `quota`, `cache`, `config show` and `transcripts` commands against the
+/// loaded config.
+fn inspect(args: &CliArgs, layered: &LayeredConfig) -> anyhow::Result<()> {
+    match &args.command {
+        Some(Commands::Quota {
+            action,
+            json,
+            state_file,
+            key,
+        }) => quota::run(
+            *action,
+            *json,
+            state_file.clone(),
+            key.as_deref(),
+            &layered.config,
+        )
+        .context("Quota command failed"),
+        Some(Commands::Cache { action, cache_dir }) => {
+            cache::run(*action, cache_dir.clone(), &layered.config).context("Cache command failed")
+        }
+        Some(Commands::Config {
+            action: ConfigAction::Show,
+        }) => {
+            print!("{}", layered.render());
+            Ok(())
+        }
+        Some(Commands::Transcripts {
+            dirs,
+            terms,
+            symbols,
+            output,
+        }) => {
+            let dirs = if dirs.is_empty() {
+                vec![args.out_dir.clone()]
+            } else {
+                dirs.clone()
+            };
+            let terms = if terms.is_empty() {
+                layered.config.transcripts.terms()
+            } else {
+                terms.clone()
+            };
+            let out_dir = output.as_ref().unwrap_or(&args.out_dir);
+            transcript_report::run(&dirs, symbols, &terms, out_dir)
+                .map(|_| ())
+                .context("Transcripts report failed")
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,181 @@
+use crate::planner::PlannedCall;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILENAME: &str = "run_manifest.json";
+
+/// Outcome of one (ticker, endpoint) task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub status: TaskStatus,
+    pub updated_at: DateTime<Utc>,
+    /// Resolved parameters the task was fetched with, e.g. `quarter = "2024Q3"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+/// Checkpoint of a bulk run, written to `out_dir` after every task so an
+/// interrupted run can be resumed with `--resume`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub started_at: DateTime<Utc>,
+    /// Keyed by `TICKER/ENDPOINT`, or `MARKET/ENDPOINT` for market-wide calls
+    pub tasks: BTreeMap<String, TaskRecord>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl RunManifest {
+    /// An empty manifest for a new run in `out_dir`.
+    #[must_use]
+    pub fn new(out_dir: &Path) -> Self {
+        Self {
+            started_at: Utc::now(),
+            tasks: BTreeMap::new(),
+            path: out_dir.join(MANIFEST_FILENAME),
+        }
+    }
+
+    /// The manifest left in `out_dir` by a previous run, or a new one if there is none.
+    ///
+    /// # Errors
+    /// Returns error if a manifest exists but cannot be read or parsed.
+    pub fn load_or_new(out_dir: &Path) -> Result<Self> {
+        let path = out_dir.join(MANIFEST_FILENAME);
+        if !path.exists() {
+            return Ok(Self::new(out_dir));
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read run manifest at {}", path.display()))?;
+        let mut manifest: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse run manifest at {}", path.display()))?;
+        manifest.path = path;
+        Ok(manifest)
+    }
+
+    fn key(call: &PlannedCall) -> String {
+        let target = call.ticker.as_ref().map_or("MARKET", |t| t.as_str());
+        format!("{target}/{}", call.endpoint)
+    }
+
+    /// Whether `call` completed successfully in an earlier run with the same `params`.
+    /// A task fetched with other parameters (e.g. after `[bulk.params]` changed or a
+    /// `latest` quarter moved on) is pending again.
+    #[must_use]
+    pub fn is_done(&self, call: &PlannedCall, params: &BTreeMap<String, String>) -> bool {
+        self.tasks
+            .get(&Self::key(call))
+            .is_some_and(|r| r.status == TaskStatus::Done && &r.params == params)
+    }
+
+    /// Records the outcome of `call` fetched with `params` and writes the manifest to disk.
+    ///
+    /// # Errors
+    /// Returns error if the manifest cannot be written.
+    pub fn record(
+        &mut self,
+        call: &PlannedCall,
+        params: BTreeMap<String, String>,
+        success: bool,
+    ) -> Result<()> {
+        let status = if success {
+            TaskStatus::Done
+        } else {
+            TaskStatus::Failed
+        };
+        self.tasks.insert(
+            Self::key(call),
+            TaskRecord {
+                status,
+                updated_at: Utc::now(),
+                params,
+            },
+        );
+        self.save()
+    }
+
+    /// Writes the manifest via a temporary file so a crash never leaves it truncated.
+    ///
+    /// # Errors
+    /// Returns error if the manifest cannot be written.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to write run manifest at {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use alphavantage_core::domain::{EndpointName, TickerSymbol};
+
+    fn call(ticker: Option<&str>, endpoint: EndpointName) -> PlannedCall {
+        PlannedCall {
+            endpoint,
+            ticker: ticker.map(|t| TickerSymbol::new(t).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let overview = call(Some("AAPL"), EndpointName::Overview);
+        let splits = call(Some("AAPL"), EndpointName::Splits);
+        let market = call(None, EndpointName::MarketStatus);
+
+        let none = BTreeMap::new();
+
+        let mut manifest = RunManifest::new(dir.path());
+        manifest.record(&overview, none.clone(), true).unwrap();
+        manifest.record(&splits, none.clone(), false).unwrap();
+        manifest.record(&market, none.clone(), true).unwrap();
+
+        let loaded = RunManifest::load_or_new(dir.path()).unwrap();
+        assert!(loaded.is_done(&overview, &none));
+        assert!(!loaded.is_done(&splits, &none));
+        assert!(loaded.is_done(&market, &none));
+        assert!(!loaded.is_done(&call(Some("NVDA"), EndpointName::Overview), &none));
+        assert!(loaded.tasks.contains_key("MARKET/MARKET_STATUS"));
+    }
+
+    #[test]
+    fn test_changed_params_are_pending() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = call(Some("MU"), EndpointName::EarningsCallTranscript);
+        let quarter = |q: &str| BTreeMap::from([("quarter".to_string(), q.to_string())]);
+
+        let mut manifest = RunManifest::new(dir.path());
+        manifest
+            .record(&transcript, quarter("2024Q2"), true)
+            .unwrap();
+
+        let loaded = RunManifest::load_or_new(dir.path()).unwrap();
+        assert!(loaded.is_done(&transcript, &quarter("2024Q2")));
+        assert!(!loaded.is_done(&transcript, &quarter("2024Q3")));
+        assert!(!loaded.is_done(&transcript, &BTreeMap::new()));
+    }
+
+    #[test]
+    fn test_missing_manifest_starts_fresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = RunManifest::load_or_new(dir.path()).unwrap();
+        assert!(manifest.tasks.is_empty());
+    }
+}
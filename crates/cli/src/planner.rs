@@ -0,0 +1,600 @@
+use crate::cli_args::OverQuota;
+use alphavantage_client::RateLimits;
+use alphavantage_core::domain::{EndpointName, TickerSymbol};
+use alphavantage_core::util::format_duration;
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Market-wide endpoints fetched once per bulk run.
+pub const MARKET_ENDPOINTS: [EndpointName; 4] = [
+    EndpointName::MarketStatus,
+    EndpointName::TopGainersLosers,
+    EndpointName::ListingStatus,
+    EndpointName::NewsSentiment,
+];
+
+/// Ticker-specific endpoints fetched for every symbol (13 per PRD).
+pub const TICKER_ENDPOINTS: [EndpointName; 13] = [
+    EndpointName::Overview,
+    EndpointName::IncomeStatement,
+    EndpointName::BalanceSheet,
+    EndpointName::CashFlow,
+    EndpointName::Earnings,
+    EndpointName::EarningsEstimates,
+    EndpointName::NewsSentiment,
+    EndpointName::InsiderTransactions,
+    EndpointName::Dividends,
+    EndpointName::Splits,
+    EndpointName::SharesOutstanding,
+    EndpointName::EarningsCalendar,
+    EndpointName::EarningsCallTranscript,
+];
+
+/// Order in which calls survive truncation: company fundamentals for every ticker
+/// first, then market snapshots, then the supplementary endpoints.
+const PRIORITY: [EndpointName; 16] = [
+    EndpointName::Overview,
+    EndpointName::IncomeStatement,
+    EndpointName::BalanceSheet,
+    EndpointName::CashFlow,
+    EndpointName::Earnings,
+    EndpointName::MarketStatus,
+    EndpointName::TopGainersLosers,
+    EndpointName::EarningsEstimates,
+    EndpointName::NewsSentiment,
+    EndpointName::InsiderTransactions,
+    EndpointName::Dividends,
+    EndpointName::Splits,
+    EndpointName::SharesOutstanding,
+    EndpointName::EarningsCalendar,
+    EndpointName::EarningsCallTranscript,
+    EndpointName::ListingStatus,
+];
+
+/// The endpoints a bulk run fetches and the parameters sent with its ticker calls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkEndpoints {
+    pub market: Vec<EndpointName>,
+    pub ticker: Vec<EndpointName>,
+    params: HashMap<EndpointName, BTreeMap<String, String>>,
+}
+
+impl Default for BulkEndpoints {
+    /// Every bulk endpoint, without parameters.
+    fn default() -> Self {
+        Self {
+            market: MARKET_ENDPOINTS.to_vec(),
+            ticker: TICKER_ENDPOINTS.to_vec(),
+            params: HashMap::new(),
+        }
+    }
+}
+
+impl BulkEndpoints {
+    /// The bulk endpoints in `include` (all of them if it is empty) that are not in
+    /// `exclude`. An endpoint in both lists, like `NEWS_SENTIMENT`, is selected or
+    /// excluded for the market and the tickers alike.
+    ///
+    /// # Errors
+    /// Returns error if either list names an endpoint bulk runs do not fetch, or
+    /// the selection leaves nothing to fetch.
+    pub fn select(include: &[EndpointName], exclude: &[EndpointName]) -> Result<Self> {
+        if let Some(unknown) = include
+            .iter()
+            .chain(exclude)
+            .find(|e| !MARKET_ENDPOINTS.contains(e) && !TICKER_ENDPOINTS.contains(e))
+        {
+            anyhow::bail!(
+                "{unknown} is not fetched by bulk runs (bulk endpoints: {})",
+                bulk_endpoint_names().join(", ")
+            );
+        }
+        let keep =
+            |e: &EndpointName| (include.is_empty() || include.contains(e)) && !exclude.contains(e);
+        let selected = Self {
+            market: MARKET_ENDPOINTS.into_iter().filter(keep).collect(),
+            ticker: TICKER_ENDPOINTS.into_iter().filter(keep).collect(),
+            params: HashMap::new(),
+        };
+        if selected.market.is_empty() && selected.ticker.is_empty() {
+            anyhow::bail!("The endpoint selection leaves nothing to fetch");
+        }
+        Ok(selected)
+    }
+
+    /// Sends `params` with every ticker call to `endpoint`.
+    ///
+    /// # Errors
+    /// Returns error if `endpoint` is not a ticker endpoint: market-wide calls take
+    /// no parameters.
+    pub fn set_params(
+        &mut self,
+        endpoint: EndpointName,
+        params: BTreeMap<String, String>,
+    ) -> Result<()> {
+        if !TICKER_ENDPOINTS.contains(&endpoint) {
+            anyhow::bail!("{endpoint} takes no parameters in bulk runs (only ticker endpoints do)");
+        }
+        self.params.insert(endpoint, params);
+        Ok(())
+    }
+
+    /// Parameters for ticker calls to `endpoint`, empty if none are configured.
+    #[must_use]
+    pub fn params(&self, endpoint: EndpointName) -> BTreeMap<String, String> {
+        self.params.get(&endpoint).cloned().unwrap_or_default()
+    }
+
+    /// `ENDPOINT key=value, ...` for every endpoint with parameters, sorted.
+    #[must_use]
+    pub fn describe_params(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .params
+            .iter()
+            .filter(|(_, params)| !params.is_empty())
+            .map(|(endpoint, params)| {
+                let pairs: Vec<String> = params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+                format!("{endpoint} {}", pairs.join(", "))
+            })
+            .collect();
+        lines.sort();
+        lines
+    }
+}
+
+fn bulk_endpoint_names() -> Vec<String> {
+    let mut names: Vec<String> = MARKET_ENDPOINTS
+        .iter()
+        .chain(&TICKER_ENDPOINTS)
+        .map(ToString::to_string)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// One API call a bulk run intends to make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedCall {
+    pub endpoint: EndpointName,
+    /// `None` for market-wide endpoints
+    pub ticker: Option<TickerSymbol>,
+}
+
+impl PlannedCall {
+    fn priority(&self) -> usize {
+        PRIORITY
+            .iter()
+            .position(|e| *e == self.endpoint)
+            .unwrap_or(PRIORITY.len())
+    }
+
+    fn target(&self) -> &str {
+        self.ticker.as_ref().map_or("MARKET", TickerSymbol::as_str)
+    }
+}
+
+/// Every call a bulk run of `endpoints` over `symbols` makes: market endpoints
+/// first, then each ticker.
+#[must_use]
+pub fn bulk_calls(symbols: &[TickerSymbol], endpoints: &BulkEndpoints) -> Vec<PlannedCall> {
+    let market = endpoints.market.iter().map(|&endpoint| PlannedCall {
+        endpoint,
+        ticker: None,
+    });
+    let tickers = symbols.iter().flat_map(|ticker| {
+        endpoints.ticker.iter().map(move |&endpoint| PlannedCall {
+            endpoint,
+            ticker: Some(ticker.clone()),
+        })
+    });
+    market.chain(tickers).collect()
+}
+
+/// The calls a bulk run will make, checked against the remaining quota.
+#[derive(Debug)]
+pub struct RunPlan {
+    /// Calls grouped by day; the first entry is what runs now
+    pub days: Vec<Vec<PlannedCall>>,
+    /// Calls dropped to fit the quota
+    pub skipped: Vec<PlannedCall>,
+    pub total_calls: usize,
+    /// Quota left today, `None` when unlimited
+    pub remaining_today: Option<u32>,
+    /// Daily limit of the whole key pool, like `remaining_today`
+    pub daily_limit: Option<u32>,
+    pub policy: OverQuota,
+}
+
+impl RunPlan {
+    /// Fits `calls` into `remaining_today` according to `policy`. Both `remaining_today`
+    /// and `daily_limit` cover every key in the pool.
+    ///
+    /// # Errors
+    /// Returns error if the calls do not fit and `policy` is `OverQuota::Refuse`.
+    pub fn build(
+        calls: Vec<PlannedCall>,
+        remaining_today: Option<u32>,
+        daily_limit: Option<u32>,
+        policy: OverQuota,
+    ) -> Result<Self> {
+        let total_calls = calls.len();
+        let mut plan = Self {
+            days: Vec::new(),
+            skipped: Vec::new(),
+            total_calls,
+            remaining_today,
+            daily_limit,
+            policy,
+        };
+
+        let capacity = remaining_today.map_or(usize::MAX, |r| r as usize);
+        if total_calls <= capacity {
+            plan.days.push(calls);
+            return Ok(plan);
+        }
+
+        match policy {
+            OverQuota::Refuse => anyhow::bail!(
+                "Bulk run needs {total_calls} API calls but only {capacity} remain today. \
+                 Use --over-quota truncate or --over-quota spread, or --dry-run to see the plan."
+            ),
+            OverQuota::Truncate => {
+                let mut ranked: Vec<usize> = (0..total_calls).collect();
+                ranked
+                    .sort_by_key(|&i| (calls.get(i).map_or(usize::MAX, PlannedCall::priority), i));
+                let mut keep = vec![false; total_calls];
+                for &i in ranked.iter().take(capacity) {
+                    if let Some(k) = keep.get_mut(i) {
+                        *k = true;
+                    }
+                }
+
+                let mut today = Vec::new();
+                for (call, keep) in calls.into_iter().zip(keep) {
+                    if keep {
+                        today.push(call);
+                    } else {
+                        plan.skipped.push(call);
+                    }
+                }
+                plan.days.push(today);
+            }
+            OverQuota::Spread => {
+                let per_day = daily_limit.map_or(capacity, |l| l as usize).max(1);
+                plan.days = spread(calls, capacity, per_day);
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Calls to make in this run.
+    #[must_use]
+    pub fn today(&self) -> &[PlannedCall] {
+        self.days.first().map_or(&[], Vec::as_slice)
+    }
+
+    /// Calls planned for this run that will not be made: skipped or left for later days.
+    pub fn not_run(&self) -> impl Iterator<Item = &PlannedCall> {
+        self.skipped
+            .iter()
+            .chain(self.days.iter().skip(1).flatten())
+    }
+
+    /// Human-readable plan with the estimated wall time of today's calls.
+    #[must_use]
+    pub fn render(&self, limits: &RateLimits) -> String {
+        let market = self.count(|c| c.ticker.is_none());
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Run plan: {} API calls ({} market, {} ticker)",
+            self.total_calls,
+            market,
+            self.total_calls - market
+        );
+        let remaining = match (self.remaining_today, self.daily_limit) {
+            (Some(r), Some(l)) => format!("{r}/{l}"),
+            (Some(r), None) => r.to_string(),
+            (None, _) => "unlimited".to_string(),
+        };
+        let _ = writeln!(out, "Remaining quota today: {remaining}");
+        let _ = writeln!(out, "Over-quota policy: {:?}", self.policy);
+        let _ = writeln!(
+            out,
+            "Estimated wall time: {} for {} calls (min delay {}ms)",
+            format_duration(estimate_wall_time(self.today().len(), limits).as_secs()),
+            self.today().len(),
+            limits.min_delay_ms
+        );
+
+        for (day, calls) in self.days.iter().enumerate() {
+            let title = if day == 0 {
+                "Today".to_string()
+            } else {
+                format!("Day {}", day + 1)
+            };
+            let _ = writeln!(out, "\n{title}: {} calls", calls.len());
+            render_calls(&mut out, calls);
+            if day > 0 {
+                let symbols: Vec<&str> = unique_targets(calls)
+                    .into_iter()
+                    .filter(|t| *t != "MARKET")
+                    .collect();
+                if !symbols.is_empty() {
+                    let _ = writeln!(out, "  Run with: --symbols {}", symbols.join(","));
+                }
+            }
+        }
+
+        if !self.skipped.is_empty() {
+            let _ = writeln!(out, "\nSkipped (over quota): {} calls", self.skipped.len());
+            render_calls(&mut out, &self.skipped);
+        }
+        out.trim_end().to_string()
+    }
+
+    fn count(&self, predicate: impl Fn(&PlannedCall) -> bool) -> usize {
+        self.days
+            .iter()
+            .flatten()
+            .chain(&self.skipped)
+            .filter(|c| predicate(c))
+            .count()
+    }
+}
+
+/// Packs calls into days, keeping each ticker's (and the market's) calls together
+/// unless they alone exceed a day's quota.
+fn spread(calls: Vec<PlannedCall>, today: usize, per_day: usize) -> Vec<Vec<PlannedCall>> {
+    let mut groups: Vec<Vec<PlannedCall>> = Vec::new();
+    for call in calls {
+        match groups.last_mut() {
+            Some(group) if group.last().is_some_and(|c| c.ticker == call.ticker) => {
+                group.push(call);
+            }
+            _ => groups.push(vec![call]),
+        }
+    }
+
+    let mut days = Vec::new();
+    let mut current = Vec::new();
+    let mut room = today;
+    for mut group in groups {
+        if group.len() > room && group.len() <= per_day {
+            days.push(std::mem::take(&mut current));
+            room = per_day;
+        }
+        while !group.is_empty() {
+            if room == 0 {
+                days.push(std::mem::take(&mut current));
+                room = per_day;
+            }
+            let rest = group.split_off(room.min(group.len()));
+            room -= group.len();
+            current.append(&mut group);
+            group = rest;
+        }
+    }
+    days.push(current);
+    days
+}
+
+fn unique_targets(calls: &[PlannedCall]) -> Vec<&str> {
+    let mut targets: Vec<&str> = Vec::new();
+    for call in calls {
+        if !targets.contains(&call.target()) {
+            targets.push(call.target());
+        }
+    }
+    targets
+}
+
+fn render_calls(out: &mut String, calls: &[PlannedCall]) {
+    for target in unique_targets(calls) {
+        let endpoints: Vec<String> = calls
+            .iter()
+            .filter(|c| c.target() == target)
+            .map(|c| c.endpoint.to_string())
+            .collect();
+        let _ = writeln!(out, "  {target}: {}", endpoints.join(", "));
+    }
+}
+
+/// Lower bound on how long `calls` take given `min_delay_ms` and the sliding windows.
+#[must_use]
+pub fn estimate_wall_time(calls: usize, limits: &RateLimits) -> Duration {
+    let Some(gaps) = (calls as u64).checked_sub(1) else {
+        return Duration::ZERO;
+    };
+    let mut millis = gaps.saturating_mul(limits.min_delay_ms);
+    for window in &limits.windows {
+        // Every `limit` calls after the first window's worth costs one full period
+        let periods = gaps / u64::from(window.limit.max(1));
+        let period_ms = u64::try_from(window.per.as_millis()).unwrap_or(0);
+        millis = millis.max(periods.saturating_mul(period_ms));
+    }
+    Duration::from_millis(millis)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use alphavantage_client::{RateWindow, WindowPeriod};
+
+    fn symbols(names: &[&str]) -> Vec<TickerSymbol> {
+        names
+            .iter()
+            .map(|s| TickerSymbol::new(*s).unwrap())
+            .collect()
+    }
+
+    fn three_tickers() -> Vec<PlannedCall> {
+        bulk_calls(&symbols(&["AAPL", "NVDA", "MU"]), &BulkEndpoints::default())
+    }
+
+    #[test]
+    fn test_bulk_calls() {
+        let calls = three_tickers();
+        assert_eq!(calls.len(), 43);
+        assert_eq!(calls.first().unwrap().ticker, None);
+        assert_eq!(
+            calls.get(4).unwrap().ticker.as_ref().unwrap().as_str(),
+            "AAPL"
+        );
+    }
+
+    #[test]
+    fn test_endpoint_selection() {
+        let fundamentals = [
+            EndpointName::Overview,
+            EndpointName::IncomeStatement,
+            EndpointName::BalanceSheet,
+            EndpointName::CashFlow,
+        ];
+        let selected = BulkEndpoints::select(&fundamentals, &[]).unwrap();
+        assert!(selected.market.is_empty());
+        assert_eq!(selected.ticker, fundamentals);
+        assert_eq!(bulk_calls(&symbols(&["AAPL", "MU"]), &selected).len(), 8);
+
+        // News is both a market and a ticker endpoint
+        let no_news = BulkEndpoints::select(&[], &[EndpointName::NewsSentiment]).unwrap();
+        assert_eq!(no_news.market.len(), 3);
+        assert_eq!(no_news.ticker.len(), 12);
+
+        let err = BulkEndpoints::select(&[EndpointName::GlobalQuote], &[]).unwrap_err();
+        assert!(
+            err.to_string().contains("GLOBAL_QUOTE is not fetched"),
+            "{err}"
+        );
+        assert!(BulkEndpoints::select(&fundamentals, &fundamentals).is_err());
+    }
+
+    #[test]
+    fn test_endpoint_params() {
+        let mut endpoints = BulkEndpoints::default();
+        let limit = BTreeMap::from([("limit".to_string(), "200".to_string())]);
+        endpoints
+            .set_params(EndpointName::NewsSentiment, limit.clone())
+            .unwrap();
+        assert_eq!(endpoints.params(EndpointName::NewsSentiment), limit);
+        assert!(endpoints.params(EndpointName::Overview).is_empty());
+        assert_eq!(endpoints.describe_params(), ["NEWS_SENTIMENT limit=200"]);
+        assert!(endpoints
+            .set_params(EndpointName::ListingStatus, limit)
+            .is_err());
+    }
+
+    #[test]
+    fn test_fits_within_quota() {
+        let plan = RunPlan::build(three_tickers(), Some(50), Some(50), OverQuota::Refuse).unwrap();
+        assert_eq!(plan.today().len(), 43);
+        assert_eq!(plan.not_run().count(), 0);
+
+        let unlimited = RunPlan::build(three_tickers(), None, None, OverQuota::Refuse).unwrap();
+        assert_eq!(unlimited.today().len(), 43);
+    }
+
+    #[test]
+    fn test_refuse_when_over_quota() {
+        let err = RunPlan::build(three_tickers(), Some(25), Some(25), OverQuota::Refuse)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("needs 43 API calls but only 25 remain"));
+    }
+
+    #[test]
+    fn test_truncate_keeps_highest_priority() {
+        let plan =
+            RunPlan::build(three_tickers(), Some(25), Some(25), OverQuota::Truncate).unwrap();
+        assert_eq!(plan.today().len(), 25);
+        assert_eq!(plan.skipped.len(), 18);
+
+        // Fundamentals for every ticker survive, in the original order
+        for ticker in ["AAPL", "NVDA", "MU"] {
+            assert!(plan
+                .today()
+                .iter()
+                .any(|c| c.endpoint == EndpointName::CashFlow
+                    && c.ticker.as_ref().unwrap().as_str() == ticker));
+        }
+        assert_eq!(
+            plan.today().first().unwrap().endpoint,
+            EndpointName::MarketStatus
+        );
+        assert!(plan
+            .skipped
+            .iter()
+            .any(|c| c.endpoint == EndpointName::ListingStatus));
+    }
+
+    #[test]
+    fn test_spread_keeps_tickers_together() {
+        let plan = RunPlan::build(three_tickers(), Some(25), Some(25), OverQuota::Spread).unwrap();
+        // Market (4) + AAPL (13) today; NVDA doesn't fit the remaining 8, so it moves
+        // to day 2, and MU doesn't fit what is left of that day either
+        assert_eq!(plan.days.len(), 3);
+        assert_eq!(plan.today().len(), 17);
+        assert_eq!(plan.not_run().count(), 26);
+        let text = plan.render(&RateLimits::daily(25, 1000));
+        assert!(text.contains("Day 2: 13 calls"));
+        assert!(text.contains("Run with: --symbols NVDA"));
+        assert!(text.contains("Run with: --symbols MU"));
+    }
+
+    #[test]
+    fn test_spread_over_key_pool() {
+        // Three keys at 25/day: 75 today and 75 on each later day
+        let calls: Vec<PlannedCall> = (0..4).flat_map(|_| three_tickers()).collect();
+        let plan = RunPlan::build(calls, Some(75), Some(75), OverQuota::Spread).unwrap();
+        assert!(plan.days.iter().all(|d| d.len() <= 75));
+        assert!(plan.days.get(1).unwrap().len() > 25);
+        let text = plan.render(&RateLimits::daily(25, 1000));
+        assert!(text.contains("Remaining quota today: 75/75"), "{text}");
+    }
+
+    #[test]
+    fn test_spread_with_exhausted_quota() {
+        let plan = RunPlan::build(three_tickers(), Some(0), Some(25), OverQuota::Spread).unwrap();
+        assert!(plan.today().is_empty());
+        assert_eq!(plan.days.iter().map(Vec::len).sum::<usize>(), 43);
+        assert!(plan.days.iter().skip(1).all(|d| d.len() <= 25));
+    }
+
+    #[test]
+    fn test_wall_time_estimate() {
+        assert_eq!(
+            estimate_wall_time(43, &RateLimits::daily(25, 1000)),
+            Duration::from_secs(42)
+        );
+        assert_eq!(
+            estimate_wall_time(0, &RateLimits::daily(25, 1000)),
+            Duration::ZERO
+        );
+
+        let per_minute = RateLimits {
+            windows: vec![RateWindow {
+                limit: 5,
+                per: WindowPeriod::Minute,
+            }],
+            ..RateLimits::daily(25, 0)
+        };
+        // 12 calls at 5/min: two full minutes of waiting
+        assert_eq!(estimate_wall_time(12, &per_minute), Duration::from_mins(2));
+    }
+
+    #[test]
+    fn test_render() {
+        let plan =
+            RunPlan::build(three_tickers(), Some(25), Some(25), OverQuota::Truncate).unwrap();
+        let text = plan.render(&RateLimits::daily(25, 1000));
+        assert!(text.starts_with("Run plan: 43 API calls (4 market, 39 ticker)"));
+        assert!(text.contains("Remaining quota today: 25/25"));
+        assert!(text.contains("Estimated wall time: 24s for 25 calls"));
+        assert!(text.contains("Skipped (over quota): 18 calls"));
+        assert!(text.contains("  AAPL: OVERVIEW, INCOME_STATEMENT"));
+    }
+}
@@ -1,11 +1,20 @@
-use alphavantage_core::domain::EndpointName;
+use alphavantage_core::domain::{EndpointName, KeyUsage};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+#[derive(Debug, Default, Clone, Copy)]
+struct Counts {
+    completed: usize,
+    failed: usize,
+}
+
 /// Tracks progress of endpoint-fetching
+///
+/// Safe to share between concurrent fetches: each one is counted exactly once,
+/// when it finishes.
 pub struct ProgressReporter {
     total_endpoints: usize,
-    completed_endpoints: Arc<Mutex<usize>>,
+    counts: Arc<Mutex<Counts>>,
 }
 
 impl ProgressReporter {
@@ -14,7 +23,7 @@ impl ProgressReporter {
     pub fn new(total_endpoints: usize) -> Self {
         Self {
             total_endpoints,
-            completed_endpoints: Arc::new(Mutex::new(0)),
+            counts: Arc::new(Mutex::new(Counts::default())),
         }
     }
 
@@ -29,28 +38,66 @@ impl ProgressReporter {
         success: bool,
         error: Option<&str>,
     ) {
+        let completed = {
+            let mut counts = self.counts.lock().await;
+            counts.completed += 1;
+            if !success {
+                counts.failed += 1;
+            }
+            counts.completed
+        };
+        let total = self.total_endpoints;
+
         if success {
-            tracing::info!("✓ {} for {} succeeded", endpoint, ticker);
+            tracing::info!(
+                "[{}/{}] ✓ {} for {} succeeded",
+                completed,
+                total,
+                endpoint,
+                ticker
+            );
         } else {
             // Use error level for failures so they are visible even in non-verbose modes if needed
             tracing::error!(
-                "✗ {} for {} failed: {}",
+                "[{}/{}] ✗ {} for {} failed: {}",
+                completed,
+                total,
                 endpoint,
                 ticker,
                 error.unwrap_or("unknown error")
             );
         }
-
-        let mut completed = self.completed_endpoints.lock().await;
-        *completed += 1;
-
-        // Basic log of tokens is handled by the client rate limiter logger,
-        // but we could duplicate it here if we had access to the limiter state directly.
-        // For now, focusing on completion status.
     }
 
-    pub async fn summary(&self) {
-        let completed = *self.completed_endpoints.lock().await;
-        tracing::info!("Completed {}/{} endpoints", completed, self.total_endpoints);
+    pub async fn summary(&self, key_usage: &[KeyUsage]) {
+        let counts = *self.counts.lock().await;
+        if counts.failed > 0 {
+            tracing::warn!(
+                "Completed {}/{} endpoints ({} failed)",
+                counts.completed,
+                self.total_endpoints,
+                counts.failed
+            );
+        } else {
+            tracing::info!(
+                "Completed {}/{} endpoints",
+                counts.completed,
+                self.total_endpoints
+            );
+        }
+
+        for usage in key_usage {
+            let remaining = usage
+                .remaining_today
+                .map_or_else(|| "no daily cap".to_string(), |r| format!("{r} remaining"));
+            tracing::info!(
+                key = %usage.fingerprint,
+                "Key {}: {} calls this run, {} today ({})",
+                usage.fingerprint,
+                usage.calls_this_run,
+                usage.calls_today,
+                remaining
+            );
+        }
     }
 }
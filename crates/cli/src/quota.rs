@@ -0,0 +1,189 @@
+use crate::api_config::ApiConfig;
+use crate::cli_args::QuotaAction;
+use alphavantage_client::{KeyHistory, QuotaStatus, RateLimiter};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Runs a `quota` subcommand against the rate limiter state file.
+///
+/// Uses the limits of the effective configuration, so resets and remaining counts
+/// match what a live run would enforce.
+///
+/// # Errors
+/// Returns error if the state file cannot be locked, read or written, or the
+/// requested change is invalid (unknown key, remaining above the daily limit).
+pub fn run(
+    action: QuotaAction,
+    json: bool,
+    state_file: Option<PathBuf>,
+    key: Option<&str>,
+    config: &ApiConfig,
+) -> Result<()> {
+    let limits = config.rate_limit.to_rate_limits();
+    let state_path = state_file.unwrap_or_else(RateLimiter::resolve_state_path);
+    let limiter = RateLimiter::with_limits(state_path, limits);
+
+    match action {
+        QuotaAction::Status => {}
+        QuotaAction::Reset => {
+            let updated = limiter.reset(key)?;
+            tracing::info!("Reset quota for {} key(s)", updated);
+        }
+        QuotaAction::SetRemaining { remaining } => {
+            let updated = limiter.set_remaining(key, remaining)?;
+            tracing::info!(
+                "Set remaining calls to {} for {} key(s)",
+                remaining,
+                updated
+            );
+        }
+        QuotaAction::History => {
+            let history = filter_key(limiter.history()?, key, |h| &h.fingerprint);
+            let output = if json {
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "state_file": limiter.state_path(),
+                    "keys": history,
+                }))?
+            } else {
+                render_history(&history)
+            };
+            println!("{output}");
+            return Ok(());
+        }
+    }
+
+    let status = filter_key(limiter.status()?, key, |s| &s.fingerprint);
+    let output = if json {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "state_file": limiter.state_path(),
+            "keys": status,
+        }))?
+    } else {
+        format!(
+            "Quota state: {}\n\n{}",
+            limiter.state_path().display(),
+            render_status(&status)
+        )
+    };
+    println!("{output}");
+    Ok(())
+}
+
+fn filter_key<T>(items: Vec<T>, key: Option<&str>, fingerprint: impl Fn(&T) -> &str) -> Vec<T> {
+    items
+        .into_iter()
+        .filter(|item| key.is_none_or(|k| fingerprint(item) == k))
+        .collect()
+}
+
+fn format_time(time: Option<DateTime<Utc>>) -> String {
+    time.map_or_else(
+        || "-".to_string(),
+        |t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    )
+}
+
+/// Human-readable status, one block per key.
+#[must_use]
+pub fn render_status(status: &[QuotaStatus]) -> String {
+    if status.is_empty() {
+        return "No API calls recorded yet.".to_string();
+    }
+
+    let mut out = String::new();
+    for s in status {
+        let remaining = match (s.remaining, s.daily_limit) {
+            (Some(remaining), Some(limit)) => format!("{remaining}/{limit}"),
+            _ => "unlimited".to_string(),
+        };
+        let _ = writeln!(out, "Key {}", s.fingerprint);
+        let _ = writeln!(out, "  Remaining:   {remaining}");
+        let _ = writeln!(out, "  Calls today: {}", s.calls_today);
+        let _ = writeln!(out, "  Next reset:  {}", format_time(s.next_reset));
+        let _ = writeln!(out, "  Last call:   {}", format_time(s.last_call));
+    }
+    out.trim_end().to_string()
+}
+
+/// Human-readable calls per day, one block per key.
+#[must_use]
+pub fn render_history(history: &[KeyHistory]) -> String {
+    if history.is_empty() {
+        return "No API calls recorded yet.".to_string();
+    }
+
+    let mut out = String::new();
+    for h in history {
+        let total: u32 = h.days.iter().map(|d| d.calls).sum();
+        let _ = writeln!(
+            out,
+            "Key {} ({total} calls in the last {} days)",
+            h.fingerprint,
+            h.days.len()
+        );
+        for day in &h.days {
+            let _ = writeln!(out, "  {}  {:>5}", day.date, day.calls);
+        }
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use alphavantage_client::DailyCalls;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_status() {
+        let status = vec![
+            QuotaStatus {
+                fingerprint: "aaaa".to_string(),
+                daily_limit: Some(25),
+                remaining: Some(20),
+                calls_today: 5,
+                last_call: Some("2024-06-15T13:45:00Z".parse().unwrap()),
+                next_reset: Some("2024-06-16T00:00:00Z".parse().unwrap()),
+            },
+            QuotaStatus {
+                fingerprint: "bbbb".to_string(),
+                daily_limit: None,
+                remaining: None,
+                calls_today: 0,
+                last_call: None,
+                next_reset: None,
+            },
+        ];
+
+        let text = render_status(&status);
+        assert!(text.contains("Key aaaa\n  Remaining:   20/25\n  Calls today: 5"));
+        assert!(text.contains("Next reset:  2024-06-16 00:00:00 UTC"));
+        assert!(text.contains("Last call:   2024-06-15 13:45:00 UTC"));
+        assert!(text.contains("Key bbbb\n  Remaining:   unlimited"));
+        assert_eq!(render_status(&[]), "No API calls recorded yet.");
+    }
+
+    #[test]
+    fn test_render_history() {
+        let history = vec![KeyHistory {
+            fingerprint: "aaaa".to_string(),
+            days: vec![
+                DailyCalls {
+                    date: NaiveDate::from_ymd_opt(2024, 6, 14).unwrap(),
+                    calls: 3,
+                },
+                DailyCalls {
+                    date: NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+                    calls: 12,
+                },
+            ],
+        }];
+
+        let text = render_history(&history);
+        assert!(text.starts_with("Key aaaa (15 calls in the last 2 days)"));
+        assert!(text.contains("  2024-06-15     12"));
+    }
+}
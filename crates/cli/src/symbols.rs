@@ -0,0 +1,278 @@
+use crate::api_config::ApiConfig;
+use crate::cli_args::{CliArgs, Universe};
+use alphavantage_core::domain::{EndpointName, TickerSymbol};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Bulk run symbols when no symbol source is given.
+pub const DEFAULT_SYMBOLS: [&str; 3] = ["AAPL", "NVDA", "MU"];
+
+/// The symbols selected by `--symbols`, `--watchlist`, `--symbols-file` and
+/// `--universe`, in that order and without duplicates, or [`DEFAULT_SYMBOLS`] if
+/// none of them is given.
+///
+/// # Errors
+/// Returns error if a watchlist is not defined, the symbols file cannot be read or
+/// holds an invalid symbol, the universe is not cached, or the sources select no
+/// symbols at all.
+pub fn resolve(args: &CliArgs, config: &ApiConfig) -> Result<Vec<TickerSymbol>> {
+    let given = args.symbols.is_some()
+        || !args.watchlist.is_empty()
+        || args.symbols_file.is_some()
+        || args.universe.is_some();
+    if !given {
+        return Ok(DEFAULT_SYMBOLS
+            .iter()
+            .filter_map(|s| TickerSymbol::new(*s).ok())
+            .collect());
+    }
+
+    let mut symbols = args.symbols.clone().unwrap_or_default();
+    for name in &args.watchlist {
+        let list = config.watchlists.get(name).with_context(|| {
+            let known: Vec<&str> = config.watchlists.keys().map(String::as_str).collect();
+            format!(
+                "Unknown watchlist '{name}' (defined in [watchlists]: {})",
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+        })?;
+        symbols.extend(list.iter().cloned());
+    }
+    if let Some(path) = &args.symbols_file {
+        symbols.extend(read_symbols_file(path)?);
+    }
+    if args.universe == Some(Universe::ListingStatus) {
+        symbols.extend(listing_status_universe(args, config)?);
+    }
+
+    let mut seen = HashSet::new();
+    symbols.retain(|s| seen.insert(s.clone()));
+    if symbols.is_empty() {
+        anyhow::bail!("The given symbol sources select no symbols");
+    }
+    Ok(symbols)
+}
+
+/// Reads a symbols file: CSV if it has a `.csv` extension, plain text otherwise.
+///
+/// # Errors
+/// Returns error if the file cannot be read or holds an invalid symbol.
+pub fn read_symbols_file(path: &Path) -> Result<Vec<TickerSymbol>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read symbols file {}", path.display()))?;
+    let csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    parse_symbols(&text, csv).with_context(|| format!("Invalid symbols file {}", path.display()))
+}
+
+/// Parses symbols, ignoring blank lines and everything after `#`.
+///
+/// Plain text holds symbols separated by whitespace or commas. CSV takes its
+/// `symbol` (or `ticker`) column if the first row names one, or else the first column.
+///
+/// # Errors
+/// Returns error naming the line of the first invalid symbol.
+pub fn parse_symbols(text: &str, csv: bool) -> Result<Vec<TickerSymbol>> {
+    let mut rows = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .peekable();
+
+    let mut column = None;
+    if csv {
+        if let Some((_, header)) = rows.peek() {
+            column = split_csv(header).iter().position(|cell| {
+                ["symbol", "ticker"].contains(&cell.to_ascii_lowercase().as_str())
+            });
+            if column.is_some() {
+                rows.next();
+            }
+        }
+    }
+
+    let mut symbols = Vec::new();
+    for (number, line) in rows {
+        let tokens: Vec<&str> = if csv {
+            split_csv(line)
+                .get(column.unwrap_or(0))
+                .copied()
+                .into_iter()
+                .collect()
+        } else {
+            line.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|t| !t.is_empty())
+                .collect()
+        };
+        for token in tokens.into_iter().filter(|t| !t.is_empty()) {
+            let symbol = TickerSymbol::new(token)
+                .with_context(|| format!("Line {number}: invalid symbol '{token}'"))?;
+            symbols.push(symbol);
+        }
+    }
+    Ok(symbols)
+}
+
+/// Active listings in the cached `LISTING_STATUS` response matching `--exchange`
+/// and `--asset-type`.
+fn listing_status_universe(args: &CliArgs, config: &ApiConfig) -> Result<Vec<TickerSymbol>> {
+    let cache = config.cache.to_cache()?;
+    let (response, fetched_at) = cache
+        .get_stale(EndpointName::ListingStatus, None, &BTreeMap::new())
+        .with_context(|| {
+            format!(
+                "No cached LISTING_STATUS response in {}. Add LISTING_STATUS = \"7d\" to \
+                 [cache.ttl] and make a live bulk run, which fetches it",
+                cache.dir().display()
+            )
+        })?;
+    let csv = response
+        .get("csv_content")
+        .and_then(serde_json::Value::as_str)
+        .context("The cached LISTING_STATUS response holds no CSV listing")?;
+    let symbols = listing_status_symbols(csv, &args.exchange, &args.asset_type);
+    tracing::info!(
+        "Universe: {} symbols from LISTING_STATUS fetched {}",
+        symbols.len(),
+        fetched_at.format("%Y-%m-%d")
+    );
+    Ok(symbols)
+}
+
+/// Active listings in a `LISTING_STATUS` CSV on one of `exchanges` with one of
+/// `asset_types` (case-insensitive; an empty filter keeps everything).
+///
+/// Symbols the explorer cannot query are skipped.
+#[must_use]
+pub fn listing_status_symbols(
+    csv: &str,
+    exchanges: &[String],
+    asset_types: &[String],
+) -> Vec<TickerSymbol> {
+    let mut lines = csv.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let header: Vec<String> = split_csv(header)
+        .iter()
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let position = |name: &str| header.iter().position(|c| c == name);
+    let name_col = position("name");
+
+    let matches = |filter: &[String], value: Option<&str>| {
+        filter.is_empty() || value.is_some_and(|v| filter.iter().any(|f| f.eq_ignore_ascii_case(v)))
+    };
+
+    let mut skipped = 0;
+    let symbols: Vec<TickerSymbol> = lines
+        .filter_map(|line| {
+            let cells = split_csv(line);
+            // Company names are not quoted and may contain commas, so columns after
+            // `name` are counted from the end of the row
+            let extra = cells.len().saturating_sub(header.len());
+            let cell = |name: &str| {
+                let index = position(name)?;
+                let index = if name_col.is_some_and(|n| index > n) {
+                    index + extra
+                } else {
+                    index
+                };
+                cells.get(index).copied()
+            };
+            let active = cell("status").is_none_or(|s| s.eq_ignore_ascii_case("active"));
+            (active
+                && matches(exchanges, cell("exchange"))
+                && matches(asset_types, cell("assettype")))
+            .then(|| cell("symbol"))
+            .flatten()
+        })
+        .filter_map(|symbol| {
+            let parsed = TickerSymbol::new(symbol).ok();
+            if parsed.is_none() {
+                skipped += 1;
+            }
+            parsed
+        })
+        .collect();
+    if skipped > 0 {
+        tracing::debug!("Skipped {} listings with unsupported symbols", skipped);
+    }
+    symbols
+}
+
+/// Splits a CSV row on commas outside double quotes and unquotes the cells.
+fn split_csv(line: &str) -> Vec<&str> {
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                cells.push(unquote(line.get(start..i).unwrap_or_default()));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    cells.push(unquote(line.get(start..).unwrap_or_default()));
+    cells
+}
+
+fn unquote(cell: &str) -> &str {
+    cell.trim().trim_matches('"').trim()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn strs(symbols: &[TickerSymbol]) -> Vec<&str> {
+        symbols.iter().map(TickerSymbol::as_str).collect()
+    }
+
+    #[test]
+    fn test_parse_plain_text_and_csv() {
+        let text = "# semis\nNVDA MU\nAMD, INTC  # trailing comment\n\n";
+        assert_eq!(
+            strs(&parse_symbols(text, false).unwrap()),
+            ["NVDA", "MU", "AMD", "INTC"]
+        );
+
+        let csv =
+            "# exported watchlist\nName,Ticker,Weight\nNvidia,NVDA,0.6\n\"Micron, Inc\",MU,0.4\n";
+        assert_eq!(strs(&parse_symbols(csv, true).unwrap()), ["NVDA", "MU"]);
+        assert_eq!(
+            strs(&parse_symbols("IBM,International\nAAPL,Apple\n", true).unwrap()),
+            ["IBM", "AAPL"]
+        );
+
+        let err = parse_symbols("AAPL\nNOT A SYMBOL!\n", false).unwrap_err();
+        assert!(format!("{err:#}").contains("Line 2"), "{err:#}");
+    }
+
+    #[test]
+    fn test_listing_status_filters() {
+        let csv = "symbol,name,exchange,assetType,ipoDate,delistingDate,status\r\n\
+                   AAPL,Apple Inc,NASDAQ,Stock,1980-12-12,null,Active\r\n\
+                   QQQ,Invesco QQQ Trust, Series 1,NASDAQ,ETF,1999-03-10,null,Active\r\n\
+                   IBM,International Business Machines Corp,NYSE,Stock,1962-01-02,null,Active\r\n\
+                   OLD,Delisted Co,NASDAQ,Stock,2000-01-01,2010-01-01,Delisted\r\n\
+                   BRK-B,Berkshire Hathaway Inc,NYSE,Stock,1996-05-09,null,Active\r\n";
+        let nasdaq = listing_status_symbols(csv, &["nasdaq".to_string()], &[]);
+        assert_eq!(strs(&nasdaq), ["AAPL", "QQQ"]);
+        let etfs = listing_status_symbols(csv, &["NASDAQ".to_string()], &["ETF".to_string()]);
+        assert_eq!(strs(&etfs), ["QQQ"]);
+        let all = listing_status_symbols(csv, &[], &[]);
+        assert_eq!(strs(&all), ["AAPL", "QQQ", "IBM", "BRK-B"]);
+    }
+}
@@ -0,0 +1,480 @@
+use alphavantage_core::domain::{TickerSymbol, YearQuarter};
+use alphavantage_core::logic::transcript::{KeywordCount, SpeakerStats, SpeakerTurn, Transcript};
+use alphavantage_core::util::{escape_cell, generate_timestamp};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Bulk runs, `--record` and the fixtures store transcripts under this name.
+const BULK_FILE: &str = "EARNINGS_CALL_TRANSCRIPT.json";
+/// Granular runs prefix their files with this.
+const GRANULAR_PREFIX: &str = "earnings-call-transcript_";
+
+/// A transcript read from a saved response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredTranscript {
+    pub symbol: TickerSymbol,
+    pub quarter: YearQuarter,
+    pub transcript: Transcript,
+    pub path: PathBuf,
+}
+
+/// Builds the report from the transcripts saved under `dirs` and writes it to
+/// `out_dir`.
+///
+/// # Errors
+/// Returns error if a directory cannot be read, no transcripts are found, or the
+/// report cannot be written.
+pub fn run(
+    dirs: &[PathBuf],
+    symbols: &[TickerSymbol],
+    terms: &[String],
+    out_dir: &Path,
+) -> Result<PathBuf> {
+    let transcripts = load(dirs, symbols)?;
+    if transcripts.is_empty() {
+        let dirs: Vec<String> = dirs.iter().map(|d| d.display().to_string()).collect();
+        anyhow::bail!(
+            "No saved transcripts found in {}. Fetch some with `earnings-call-transcript` \
+             or a bulk run first",
+            dirs.join(", ")
+        );
+    }
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let path = out_dir.join(format!("transcripts_{}.md", generate_timestamp()));
+    std::fs::write(&path, render(&transcripts, terms))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!(
+        "✓ Saved markdown: {} ({} transcripts)",
+        path.display(),
+        transcripts.len()
+    );
+    Ok(path)
+}
+
+/// Every non-empty transcript saved under `dirs`, optionally only for `symbols`,
+/// sorted by symbol and quarter.
+///
+/// Files that do not parse or name no quarter are skipped. When a call is saved
+/// more than once, the most recently written file is used.
+///
+/// # Errors
+/// Returns error if a directory does not exist or cannot be read.
+pub fn load(dirs: &[PathBuf], symbols: &[TickerSymbol]) -> Result<Vec<StoredTranscript>> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        if !dir.is_dir() {
+            anyhow::bail!("Transcript directory {} does not exist", dir.display());
+        }
+        find_files(dir, &mut files)?;
+    }
+
+    let mut newest: BTreeMap<(TickerSymbol, YearQuarter), (SystemTime, StoredTranscript)> =
+        BTreeMap::new();
+    for path in files {
+        let Some(stored) = read(&path) else {
+            continue;
+        };
+        if stored.transcript.turns.is_empty()
+            || (!symbols.is_empty() && !symbols.contains(&stored.symbol))
+        {
+            continue;
+        }
+        let modified = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let key = (stored.symbol.clone(), stored.quarter);
+        if newest.get(&key).is_none_or(|(seen, _)| modified >= *seen) {
+            newest.insert(key, (modified, stored));
+        }
+    }
+    Ok(newest.into_values().map(|(_, stored)| stored).collect())
+}
+
+fn find_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_files(&path, files)?;
+        } else if path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| {
+                name == BULK_FILE
+                    || (name.starts_with(GRANULAR_PREFIX)
+                        && Path::new(name)
+                            .extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+            })
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a saved response, taking the symbol and quarter from the body or, for
+/// older files without them, from the file name.
+fn read(path: &Path) -> Option<StoredTranscript> {
+    let json: serde_json::Value = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .or_else(|| {
+            tracing::warn!("Skipping {}: not valid JSON", path.display());
+            None
+        })?;
+    let transcript = Transcript::from_json(&json)
+        .map_err(|e| tracing::warn!("Skipping {}: {e}", path.display()))
+        .ok()?;
+
+    let name = path.file_name()?.to_str()?;
+    // earnings-call-transcript_<SYM>[_<YYYYQn>]_<timestamp>.json
+    let name_parts: Vec<&str> = name
+        .strip_prefix(GRANULAR_PREFIX)
+        .map(|rest| rest.split('_').collect())
+        .unwrap_or_default();
+    let symbol = Some(transcript.symbol.as_str())
+        .filter(|s| !s.is_empty())
+        .or_else(|| name_parts.first().copied())
+        .or_else(|| path.parent()?.file_name()?.to_str())
+        .and_then(|s| TickerSymbol::new(s).ok());
+    let quarter = transcript
+        .quarter
+        .parse()
+        .ok()
+        .or_else(|| name_parts.get(1)?.parse().ok());
+    let (Some(symbol), Some(quarter)) = (symbol, quarter) else {
+        tracing::debug!("Skipping {}: no symbol or quarter", path.display());
+        return None;
+    };
+    Some(StoredTranscript {
+        symbol,
+        quarter,
+        transcript,
+        path: path.to_path_buf(),
+    })
+}
+
+/// The report: keyword trends by quarter, ticker and call, the structure of each
+/// call, and who spoke.
+#[must_use]
+pub fn render(transcripts: &[StoredTranscript], terms: &[String]) -> String {
+    let mut out = String::from("# Earnings Call Transcript Analytics\n\n");
+    let symbols: BTreeSet<&TickerSymbol> = transcripts.iter().map(|t| &t.symbol).collect();
+    let quarters: BTreeSet<YearQuarter> = transcripts.iter().map(|t| t.quarter).collect();
+    let _ = writeln!(
+        out,
+        "{} transcripts for {} symbols, {} to {}.",
+        transcripts.len(),
+        symbols.len(),
+        quarters
+            .first()
+            .map_or_else(String::new, ToString::to_string),
+        quarters
+            .last()
+            .map_or_else(String::new, ToString::to_string),
+    );
+    let _ = writeln!(
+        out,
+        "Terms are counted as whole words, ignoring case: {}.",
+        terms.join(", ")
+    );
+
+    let counts: Vec<Vec<KeywordCount>> = transcripts
+        .iter()
+        .map(|t| {
+            terms
+                .iter()
+                .map(|term| t.transcript.keyword_count(term))
+                .collect()
+        })
+        .collect();
+    let header = |first: &str| {
+        let mut cells = vec![first.to_string(), "Calls".to_string()];
+        cells.extend(terms.iter().map(|t| escape_cell(t)));
+        table_header(&cells)
+    };
+
+    out.push_str("\n## Keyword Trends\n\nMentions per quarter across all tickers.\n\n");
+    out.push_str(&header("Quarter"));
+    for quarter in &quarters {
+        let rows: Vec<usize> = (0..transcripts.len())
+            .filter(|&i| transcripts.get(i).is_some_and(|t| t.quarter == *quarter))
+            .collect();
+        let _ = writeln!(
+            out,
+            "| {quarter} | {} | {} |",
+            rows.len(),
+            sum_cells(&counts, &rows, terms.len())
+        );
+    }
+
+    out.push_str("\n## Keywords by Ticker\n\nMentions across all quarters.\n\n");
+    out.push_str(&header("Ticker"));
+    for symbol in &symbols {
+        let rows: Vec<usize> = (0..transcripts.len())
+            .filter(|&i| transcripts.get(i).is_some_and(|t| t.symbol == **symbol))
+            .collect();
+        let _ = writeln!(
+            out,
+            "| {symbol} | {} | {} |",
+            rows.len(),
+            sum_cells(&counts, &rows, terms.len())
+        );
+    }
+
+    out.push_str("\n## Keywords by Call\n\nMentions in prepared remarks + Q&A.\n\n");
+    let mut cells = vec!["Ticker".to_string(), "Quarter".to_string()];
+    cells.extend(terms.iter().map(|t| escape_cell(t)));
+    out.push_str(&table_header(&cells));
+    for (stored, call_counts) in transcripts.iter().zip(&counts) {
+        let cells: Vec<String> = call_counts
+            .iter()
+            .map(|c| format!("{} + {}", c.prepared, c.qa))
+            .collect();
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} |",
+            stored.symbol,
+            stored.quarter,
+            cells.join(" | ")
+        );
+    }
+
+    render_structure(&mut out, transcripts);
+    render_speakers(&mut out, transcripts);
+    out
+}
+
+fn render_structure(out: &mut String, transcripts: &[StoredTranscript]) {
+    out.push_str("\n## Call Structure\n\n");
+    out.push_str(&table_header(&[
+        "Ticker",
+        "Quarter",
+        "Turns",
+        "Prepared Words",
+        "Q&A Words",
+        "Q&A Share",
+        "Analysts",
+        "Mean Sentiment",
+    ]));
+    for stored in transcripts {
+        let t = &stored.transcript;
+        let words = |turns: &[_]| -> usize { turns.iter().map(SpeakerTurn::word_count).sum() };
+        let (prepared, qa) = (
+            words(t.prepared_remarks()),
+            words(t.questions_and_answers()),
+        );
+        let analysts: BTreeSet<&str> = t
+            .turns
+            .iter()
+            .filter(|turn| turn.is_analyst())
+            .map(|turn| turn.speaker.as_str())
+            .collect();
+        #[allow(clippy::cast_precision_loss)] // word counts are far below 2^52
+        let share = if prepared + qa == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.0}%", qa as f64 * 100.0 / (prepared + qa) as f64)
+        };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {prepared} | {qa} | {share} | {} | {} |",
+            stored.symbol,
+            stored.quarter,
+            t.turns.len(),
+            analysts.len(),
+            t.sentiment_summary()
+                .mean
+                .map_or_else(|| "-".to_string(), |m| format!("{m:.2}")),
+        );
+    }
+}
+
+fn render_speakers(out: &mut String, transcripts: &[StoredTranscript]) {
+    out.push_str("\n## Speakers\n\nEvery speaker but the operator, most words first.\n\n");
+    out.push_str(&table_header(&[
+        "Ticker",
+        "Speaker",
+        "Title",
+        "Calls",
+        "Turns",
+        "Q&A Turns",
+        "Words",
+    ]));
+
+    // Totals over every call of the ticker, with the number of calls and the
+    // latest title
+    let mut speakers: Vec<(&TickerSymbol, usize, SpeakerStats)> = Vec::new();
+    for stored in transcripts {
+        for s in stored.transcript.speakers() {
+            let existing = speakers
+                .iter_mut()
+                .find(|(symbol, _, total)| *symbol == &stored.symbol && total.speaker == s.speaker);
+            match existing {
+                Some((_, calls, total)) => {
+                    *calls += 1;
+                    total.turns += s.turns;
+                    total.qa_turns += s.qa_turns;
+                    total.words += s.words;
+                    if s.title.is_some() {
+                        total.title = s.title;
+                    }
+                }
+                None => speakers.push((&stored.symbol, 1, s)),
+            }
+        }
+    }
+    speakers.sort_by(|(ta, _, a), (tb, _, b)| {
+        ta.cmp(tb)
+            .then(b.words.cmp(&a.words))
+            .then_with(|| a.speaker.cmp(&b.speaker))
+    });
+    for (symbol, calls, s) in speakers {
+        let _ = writeln!(
+            out,
+            "| {symbol} | {} | {} | {calls} | {} | {} | {} |",
+            escape_cell(&s.speaker),
+            escape_cell(s.title.as_deref().unwrap_or_default()),
+            s.turns,
+            s.qa_turns,
+            s.words
+        );
+    }
+}
+
+/// Totals of each term over the calls at `rows`, as table cells.
+fn sum_cells(counts: &[Vec<KeywordCount>], rows: &[usize], terms: usize) -> String {
+    (0..terms)
+        .map(|term| {
+            rows.iter()
+                .filter_map(|&row| counts.get(row)?.get(term))
+                .map(|c| c.total())
+                .sum::<usize>()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn table_header<S: AsRef<str>>(cells: &[S]) -> String {
+    let cells: Vec<&str> = cells.iter().map(AsRef::as_ref).collect();
+    format!(
+        "| {} |\n|{}\n",
+        cells.join(" | "),
+        "---|".repeat(cells.len())
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(symbol: &str, quarter: &str, answer: &str) -> serde_json::Value {
+        json!({
+            "symbol": symbol,
+            "quarter": quarter,
+            "transcript": [
+                {"speaker": "Operator", "title": "Operator", "content": "Welcome."},
+                {"speaker": "Pat Lee", "title": "CEO", "content": "AI demand and AI supply grew.", "sentiment": "0.6"},
+                {"speaker": "Operator", "title": "Operator", "content": "Now for questions."},
+                {"speaker": "Sam Ray", "title": "Analyst, UBS", "content": "Any guidance on supply?", "sentiment": "0.0"},
+                {"speaker": "Pat Lee", "title": "CEO", "content": answer, "sentiment": "0.4"}
+            ]
+        })
+    }
+
+    fn write(path: &Path, json: &serde_json::Value) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, json.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            &root.join("raw/tickers/MU/EARNINGS_CALL_TRANSCRIPT.json"),
+            &call("MU", "2024Q1", "Supply is tight."),
+        );
+        // Symbol and quarter from the file name
+        let mut nameless = call("", "", "No AI here.");
+        nameless.as_object_mut().unwrap().remove("quarter");
+        write(
+            &root.join("raw/earnings-call-transcript_NVDA_2023Q4_20240101_120000.json"),
+            &nameless,
+        );
+        write(
+            &root.join("raw/earnings-call-transcript_NVDA_2024Q1_20240101_120000.json"),
+            &json!({"symbol": "NVDA", "quarter": "2024Q1", "transcript": []}),
+        );
+        std::fs::write(
+            root.join("raw/earnings-call-transcript_AAPL_x.json"),
+            "{oops",
+        )
+        .unwrap();
+        write(&root.join("raw/tickers/MU/OVERVIEW.json"), &json!({}));
+
+        let all = load(&[root.to_path_buf()], &[]).unwrap();
+        let keys: Vec<String> = all
+            .iter()
+            .map(|t| format!("{} {}", t.symbol, t.quarter))
+            .collect();
+        assert_eq!(keys, ["MU 2024Q1", "NVDA 2023Q4"]);
+
+        let mu = load(&[root.to_path_buf()], &[TickerSymbol::new("MU").unwrap()]).unwrap();
+        assert_eq!(mu.len(), 1);
+        assert!(load(&[root.join("missing")], &[]).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let stored = |symbol: &str, quarter: &str, answer: &str| StoredTranscript {
+            symbol: TickerSymbol::new(symbol).unwrap(),
+            quarter: quarter.parse().unwrap(),
+            transcript: Transcript::from_json(&call(symbol, quarter, answer)).unwrap(),
+            path: PathBuf::new(),
+        };
+        let transcripts = [
+            stored("MU", "2023Q4", "Supply is tight."),
+            stored("MU", "2024Q1", "AI, AI and more AI."),
+            stored("NVDA", "2024Q1", "Guidance is unchanged."),
+        ];
+        let terms = [
+            "AI".to_string(),
+            "supply".to_string(),
+            "guidance".to_string(),
+        ];
+        let report = render(&transcripts, &terms);
+
+        assert!(report.contains("3 transcripts for 2 symbols, 2023Q4 to 2024Q1."));
+        assert!(report
+            .contains("| Quarter | Calls | AI | supply | guidance |\n|---|---|---|---|---|\n"));
+        assert!(report.contains("| 2023Q4 | 1 | 2 | 3 | 1 |"), "{report}");
+        assert!(report.contains("| 2024Q1 | 2 | 7 | 4 | 3 |"), "{report}");
+        assert!(report.contains("| MU | 2 | 7 | 5 | 2 |"), "{report}");
+        assert!(
+            report.contains("| MU | 2024Q1 | 2 + 3 | 1 + 1 | 0 + 1 |"),
+            "{report}"
+        );
+        assert!(
+            report.contains("| NVDA | 2024Q1 | 5 | 7 | 10 | 59% | 1 | 0.33 |"),
+            "{report}"
+        );
+        assert!(
+            report.contains("| MU | Pat Lee | CEO | 2 | 4 | 2 | 20 |"),
+            "{report}"
+        );
+        assert!(
+            report.contains("| MU | Sam Ray | Analyst, UBS | 2 | 2 | 2 | 8 |"),
+            "{report}"
+        );
+    }
+}
@@ -0,0 +1,420 @@
+use crate::cli_args::{Commands, OverQuota};
+use crate::config::Config;
+use crate::planner::estimate_wall_time;
+use alphavantage_client::FileSystemJsonPersister;
+use alphavantage_core::domain::{EndpointName, QuarterRange, TickerSymbol, YearQuarter};
+use alphavantage_core::logic::transcript::{CallSection, Transcript};
+use alphavantage_core::ports::{ApiClient, JsonPersister};
+use alphavantage_core::util::{escape_cell, format_duration, generate_timestamp};
+use alphavantage_core::ExplorerError;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const ENDPOINT: EndpointName = EndpointName::EarningsCallTranscript;
+
+/// Most quarters a range may span (25 years).
+pub const MAX_RANGE_QUARTERS: u32 = 100;
+
+/// An `earnings-call-transcript` command asking for more than one quarter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeRequest {
+    pub symbol: TickerSymbol,
+    pub range: QuarterRange,
+    pub output: Option<PathBuf>,
+}
+
+impl RangeRequest {
+    /// The range asked for by `--from`/`--to` or `--latest`, `None` for a single
+    /// `--year`/`--quarter` transcript or any other command.
+    ///
+    /// # Errors
+    /// Returns error if `--from` is after `--to`, or the range is longer than
+    /// `MAX_RANGE_QUARTERS`.
+    pub fn from_command(command: &Commands) -> Result<Option<Self>> {
+        let Commands::EarningsCallTranscript {
+            symbol,
+            from,
+            to,
+            latest,
+            output,
+            ..
+        } = command
+        else {
+            return Ok(None);
+        };
+        let range = match (from, to, latest) {
+            (Some(from), Some(to), _) => {
+                QuarterRange::new(*from, *to).map_err(anyhow::Error::msg)?
+            }
+            (_, _, Some(count)) => {
+                QuarterRange::latest(count.get(), chrono::Utc::now().date_naive())
+            }
+            _ => return Ok(None),
+        };
+        if range.quarter_count() > MAX_RANGE_QUARTERS {
+            anyhow::bail!(
+                "Transcript range {range} has {} quarters; at most {MAX_RANGE_QUARTERS} can be fetched at once",
+                range.quarter_count()
+            );
+        }
+        Ok(Some(Self {
+            symbol: symbol.clone(),
+            range,
+            output: output.clone(),
+        }))
+    }
+}
+
+/// What happened to one quarter of the range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Fetched(Transcript),
+    Failed(String),
+    /// Left out of this run, with the reason
+    NotRun(&'static str),
+}
+
+/// Fetches every quarter of the request that the quota allows and writes them into
+/// one markdown report.
+///
+/// Quarters answered by the response cache cost no quota. The rest are fetched
+/// newest first, so a truncated run keeps the most recent calls.
+///
+/// # Errors
+/// Returns error if the quarters do not fit the remaining quota under
+/// `--over-quota refuse`, the quota state cannot be read, or the report cannot
+/// be written.
+pub async fn run(config: &Config, client: &dyn ApiClient, request: &RangeRequest) -> Result<()> {
+    let symbol = &request.symbol;
+    let mut newest_first = request.range.quarters();
+    newest_first.reverse();
+
+    let (cached, pending): (Vec<YearQuarter>, Vec<YearQuarter>) =
+        newest_first.into_iter().partition(|quarter| {
+            !config.refresh_cache
+                && config.cache.as_ref().is_some_and(|cache| {
+                    cache.is_fresh(ENDPOINT, Some(symbol.as_str()), &quarter_params(*quarter))
+                })
+        });
+    let remaining = config.remaining_quota()?;
+    let (today, deferred) = fit_quota(pending, remaining, config.over_quota)?;
+
+    if config.dry_run {
+        print_plan(config, request, &cached, &today, &deferred);
+        return Ok(());
+    }
+    if !deferred.is_empty() {
+        tracing::warn!(
+            "Quota allows {} of {} transcripts today; rerun with --from {} --to {} after the reset",
+            today.len(),
+            today.len() + deferred.len(),
+            deferred
+                .iter()
+                .min()
+                .map_or_else(String::new, ToString::to_string),
+            deferred
+                .iter()
+                .max()
+                .map_or_else(String::new, ToString::to_string),
+        );
+    }
+
+    let timestamp = generate_timestamp();
+    let out_dir = request
+        .output
+        .clone()
+        .unwrap_or_else(|| config.out_dir.clone());
+    let mut outcomes = BTreeMap::new();
+    let mut rate_limited = false;
+    for quarter in cached.into_iter().chain(today) {
+        if rate_limited {
+            outcomes.insert(quarter, Outcome::NotRun("rate limited"));
+            continue;
+        }
+        let params: HashMap<String, String> = quarter_params(quarter).into_iter().collect();
+        let outcome = match client
+            .fetch_ticker_endpoint(ENDPOINT, symbol, Some(&params), &config.api_key)
+            .await
+        {
+            Ok(json) => {
+                if config.save_raw {
+                    let raw_path = out_dir
+                        .join("raw")
+                        .join(format!("{}_{quarter}_{timestamp}.json", base_name(symbol)));
+                    FileSystemJsonPersister::new().save_raw_json(&raw_path, &json)?;
+                }
+                Transcript::from_json(&json)
+                    .map_or_else(|e| Outcome::Failed(e.to_string()), Outcome::Fetched)
+            }
+            Err(e) => {
+                rate_limited = matches!(e, ExplorerError::RateLimited);
+                tracing::warn!("{symbol} {quarter}: {e}");
+                Outcome::Failed(e.to_string())
+            }
+        };
+        outcomes.insert(quarter, outcome);
+    }
+    for quarter in deferred {
+        outcomes.insert(quarter, Outcome::NotRun("over daily quota"));
+    }
+
+    let md_path = out_dir.join(format!(
+        "{}_{}-{}_{timestamp}.md",
+        base_name(symbol),
+        request.range.from,
+        request.range.to
+    ));
+    std::fs::create_dir_all(&out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    std::fs::write(&md_path, render(symbol, &request.range, &outcomes))
+        .with_context(|| format!("Failed to write {}", md_path.display()))?;
+    let fetched = outcomes
+        .values()
+        .filter(|o| matches!(o, Outcome::Fetched(_)))
+        .count();
+    println!(
+        "✓ Saved markdown: {} ({fetched} of {} quarters)",
+        md_path.display(),
+        outcomes.len()
+    );
+    Ok(())
+}
+
+/// The `--dry-run` output: which quarters come from the cache, run now or do not fit.
+fn print_plan(
+    config: &Config,
+    request: &RangeRequest,
+    cached: &[YearQuarter],
+    today: &[YearQuarter],
+    deferred: &[YearQuarter],
+) {
+    println!(
+        "Transcript plan: {} {} ({} quarters)",
+        request.symbol,
+        request.range,
+        cached.len() + today.len() + deferred.len()
+    );
+    if !cached.is_empty() {
+        println!("From cache: {} (no quota used)", join(cached));
+    }
+    println!("Fetch now: {}", join(today));
+    if !deferred.is_empty() {
+        println!("Not run (over quota): {}", join(deferred));
+    }
+    println!(
+        "Estimated wall time: {} for {} calls",
+        format_duration(estimate_wall_time(today.len(), &config.rate_limits).as_secs()),
+        today.len()
+    );
+}
+
+/// Splits `pending` (newest first) into the quarters to fetch in this run and
+/// those that do not fit the `remaining` quota.
+///
+/// # Errors
+/// Returns error if `policy` is `OverQuota::Spread`, which ranges do not support, or
+/// if they do not fit and `policy` is `OverQuota::Refuse`.
+pub fn fit_quota(
+    mut pending: Vec<YearQuarter>,
+    remaining: Option<u32>,
+    policy: OverQuota,
+) -> Result<(Vec<YearQuarter>, Vec<YearQuarter>)> {
+    if policy == OverQuota::Spread {
+        anyhow::bail!(
+            "--over-quota spread plans bulk runs over several days and is not supported for \
+             transcript ranges. Use --over-quota truncate to fetch the most recent quarters \
+             that fit and rerun the rest after the reset."
+        );
+    }
+    let capacity = remaining.map_or(usize::MAX, |r| r as usize);
+    if pending.len() <= capacity {
+        return Ok((pending, Vec::new()));
+    }
+    if policy == OverQuota::Refuse {
+        anyhow::bail!(
+            "Fetching {} transcripts needs {} API calls but only {capacity} remain today. \
+             Use --over-quota truncate to fetch the most recent quarters that fit, a shorter \
+             range, or --dry-run to see the plan.",
+            pending.len(),
+            pending.len()
+        );
+    }
+    // The rest is left for a rerun after the reset
+    let deferred = pending.split_off(capacity);
+    Ok((pending, deferred))
+}
+
+/// The consolidated report: a sentiment summary table, then every call split
+/// into prepared remarks and Q&A and those into speaker sections, oldest quarter first.
+#[must_use]
+pub fn render(
+    symbol: &TickerSymbol,
+    range: &QuarterRange,
+    outcomes: &BTreeMap<YearQuarter, Outcome>,
+) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {symbol} Earnings Call Transcripts, {range}\n");
+
+    out.push_str("## Sentiment Summary\n\n");
+    out.push_str("| Quarter | Speaker Turns | Scored | Mean | Min | Max | Status |\n");
+    out.push_str("|---|---|---|---|---|---|---|\n");
+    for (quarter, outcome) in outcomes {
+        match outcome {
+            Outcome::Fetched(transcript) if transcript.turns.is_empty() => {
+                let _ = writeln!(out, "| {quarter} | 0 | 0 | - | - | - | No transcript |");
+            }
+            Outcome::Fetched(transcript) => {
+                let s = transcript.sentiment_summary();
+                let _ = writeln!(
+                    out,
+                    "| {quarter} | {} | {} | {} | {} | {} | ✅ |",
+                    s.turns,
+                    s.scored,
+                    score(s.mean),
+                    score(s.min),
+                    score(s.max)
+                );
+            }
+            Outcome::Failed(error) => {
+                let _ = writeln!(
+                    out,
+                    "| {quarter} | - | - | - | - | - | ❌ {} |",
+                    escape_cell(error)
+                );
+            }
+            Outcome::NotRun(reason) => {
+                let _ = writeln!(out, "| {quarter} | - | - | - | - | - | Not run: {reason} |");
+            }
+        }
+    }
+
+    for (quarter, outcome) in outcomes {
+        let Outcome::Fetched(transcript) = outcome else {
+            continue;
+        };
+        let _ = writeln!(out, "\n## {quarter}");
+        for (section, turns) in [
+            (CallSection::PreparedRemarks, transcript.prepared_remarks()),
+            (
+                CallSection::QuestionsAndAnswers,
+                transcript.questions_and_answers(),
+            ),
+        ] {
+            if turns.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "\n### {section}");
+            for turn in turns {
+                // Operators are often titled "Operator" as well
+                match turn.title.as_ref().filter(|t| **t != turn.speaker) {
+                    Some(title) => {
+                        let _ = writeln!(out, "\n#### {}, {title}\n", turn.speaker);
+                    }
+                    None => {
+                        let _ = writeln!(out, "\n#### {}\n", turn.speaker);
+                    }
+                }
+                if let Some(sentiment) = turn.sentiment {
+                    let _ = writeln!(out, "*Sentiment: {sentiment:.2}*\n");
+                }
+                let _ = writeln!(out, "{}", turn.content);
+            }
+        }
+    }
+    out
+}
+
+fn quarter_params(quarter: YearQuarter) -> BTreeMap<String, String> {
+    BTreeMap::from([("quarter".to_string(), quarter.to_string())])
+}
+
+fn base_name(symbol: &TickerSymbol) -> String {
+    format!("earnings-call-transcript_{symbol}")
+}
+
+fn score(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"))
+}
+
+fn join(quarters: &[YearQuarter]) -> String {
+    if quarters.is_empty() {
+        return "none".to_string();
+    }
+    quarters
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn quarter(s: &str) -> YearQuarter {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_fit_quota() {
+        let pending = vec![quarter("2024Q4"), quarter("2024Q3"), quarter("2024Q2")];
+        let (today, deferred) = fit_quota(pending.clone(), None, OverQuota::Refuse).unwrap();
+        assert_eq!((today.len(), deferred.len()), (3, 0));
+
+        let err = fit_quota(pending.clone(), Some(2), OverQuota::Refuse).unwrap_err();
+        assert!(err.to_string().contains("only 2 remain"), "{err}");
+
+        let (today, deferred) = fit_quota(pending.clone(), Some(2), OverQuota::Truncate).unwrap();
+        assert_eq!(today, [quarter("2024Q4"), quarter("2024Q3")]);
+        assert_eq!(deferred, [quarter("2024Q2")]);
+
+        // Spread is refused rather than quietly truncating, even when the range fits
+        for remaining in [None, Some(2)] {
+            let err = fit_quota(pending.clone(), remaining, OverQuota::Spread).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("not supported for transcript ranges"),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let transcript = Transcript::from_json(&json!({
+            "symbol": "IBM",
+            "quarter": "2024Q1",
+            "transcript": [
+                {"speaker": "Operator", "content": "Welcome.", "sentiment": "0.2"},
+                {"speaker": "Arvind Krishna", "title": "CEO", "content": "Strong quarter.", "sentiment": "0.8"}
+            ]
+        }))
+        .unwrap();
+        let symbol = TickerSymbol::new("IBM").unwrap();
+        let range = QuarterRange::new(quarter("2023Q4"), quarter("2024Q2")).unwrap();
+        let outcomes = BTreeMap::from([
+            (quarter("2024Q2"), Outcome::NotRun("over daily quota")),
+            (quarter("2024Q1"), Outcome::Fetched(transcript)),
+            (
+                quarter("2023Q4"),
+                Outcome::Failed("HTTP 500 | retry".to_string()),
+            ),
+        ]);
+        let report = render(&symbol, &range, &outcomes);
+
+        assert!(report.starts_with("# IBM Earnings Call Transcripts, 2023Q4 to 2024Q2\n"));
+        assert!(report.contains("| 2023Q4 | - | - | - | - | - | ❌ HTTP 500 \\| retry |"));
+        assert!(report.contains("| 2024Q1 | 2 | 2 | 0.50 | 0.20 | 0.80 | ✅ |"));
+        assert!(report.contains("| 2024Q2 | - | - | - | - | - | Not run: over daily quota |"));
+        assert!(report.contains(
+            "## 2024Q1\n\n### Prepared Remarks\n\n#### Operator\n\n*Sentiment: 0.20*\n\nWelcome.\n"
+        ));
+        assert!(report.contains("#### Arvind Krishna, CEO\n"));
+        assert!(!report.contains("### Q&A"));
+        assert!(!report.contains("## 2023Q4\n"));
+    }
+}
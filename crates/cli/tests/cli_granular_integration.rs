@@ -3,11 +3,13 @@
 #![allow(clippy::option_if_let_else)]
 //! Integration tests for granular mode
 
-use alphavantage_cli::cli_args::{Commands, LogFormat};
+use alphavantage_cli::cli_args::{Commands, LogFormat, OverQuota};
 use alphavantage_cli::config::Config;
 use alphavantage_cli::granular_executor::GranularExecutor;
-use alphavantage_client::{ClientMode, MockClient};
+use alphavantage_cli::planner::BulkEndpoints;
+use alphavantage_client::{ClientMode, HttpSettings, KeyStrategy, MockClient, RateLimits};
 use alphavantage_core::domain::{ApiKey, TickerSymbol};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use tempfile::TempDir;
 use tracing::Level;
@@ -19,12 +21,24 @@ fn create_test_config(temp_dir: &TempDir) -> Config {
 
     Config {
         symbols: vec![],
+        endpoints: BulkEndpoints::default(),
         api_key,
+        api_keys: vec![],
+        key_strategy: KeyStrategy::default(),
         out_dir,
         client_mode: ClientMode::Mock,
+        replay_from: None,
+        mock_scenario: None,
+        record_to: None,
         save_raw: true,
-        daily_limit: 25,
-        min_delay_ms: 0,
+        rate_limits: RateLimits::daily(25, 0),
+        http: HttpSettings::default(),
+        cache: None,
+        refresh_cache: false,
+        dry_run: false,
+        resume: false,
+        over_quota: OverQuota::default(),
+        concurrency: NonZeroUsize::MIN,
         log_format: LogFormat::Human,
         log_level: Level::INFO,
     }
@@ -197,8 +211,11 @@ async fn test_granular_earnings_transcript_with_params() {
 
     let cmd = Commands::EarningsCallTranscript {
         symbol,
-        year: 2024,
-        quarter: QuarterParam::Q1,
+        year: Some(2024),
+        quarter: Some(QuarterParam::Q1),
+        from: None,
+        to: None,
+        latest: None,
         output: None,
     };
 
@@ -1,6 +1,8 @@
 #![allow(clippy::unwrap_used)]
+#![allow(clippy::indexing_slicing)]
 #![allow(deprecated)]
 
+use alphavantage_core::domain::EndpointName;
 use assert_cmd::Command;
 use predicates::prelude::*;
 use std::fs;
@@ -59,3 +61,956 @@ fn test_invalid_symbol() {
         .failure()
         .stderr(predicate::str::contains("Ticker too long"));
 }
+
+#[test]
+fn test_replay_from_previous_run() {
+    let recorded = tempfile::tempdir().unwrap();
+    let replayed = tempfile::tempdir().unwrap();
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "AAPL", "--out-dir"])
+        .arg(recorded.path())
+        .assert()
+        .success();
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "AAPL", "--no-raw", "--replay-from"])
+        .arg(recorded.path())
+        .arg("--out-dir")
+        .arg(replayed.path())
+        .assert()
+        .success();
+
+    let original = fs::read_to_string(recorded.path().join("tickers/AAPL.md")).unwrap();
+    let replay = fs::read_to_string(replayed.path().join("tickers/AAPL.md")).unwrap();
+    assert_eq!(original, replay);
+
+    // Granular commands replay too, and nothing is found for other symbols
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["overview", "--symbol", "AAPL", "--replay-from"])
+        .arg(recorded.path().join("raw"))
+        .arg("--out-dir")
+        .arg(replayed.path())
+        .assert()
+        .success();
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["overview", "--symbol", "MSFT", "--replay-from"])
+        .arg(recorded.path())
+        .arg("--out-dir")
+        .arg(replayed.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_record_requires_live_api() {
+    let dir = tempfile::tempdir().unwrap();
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "AAPL", "--record"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--live-api"));
+}
+
+#[test]
+fn test_api_key_cmd_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("alphavantage.toml"),
+        "[api]\napi_key_cmd = \"echo vault locked >&2; exit 1\"\n\n[rate_limit]\ndaily_limit = 25\n",
+    )
+    .unwrap();
+    let dry_run = || {
+        let mut cmd = Command::cargo_bin("alphavantage_cli").unwrap();
+        cmd.current_dir(dir.path())
+            .env_remove("ALPHA_VANTAGE_API_KEY")
+            .args(["--live-api", "--dry-run", "--symbols", "IBM"]);
+        cmd
+    };
+
+    // The command is only run when no key is given explicitly
+    dry_run()
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("api_key_cmd"))
+        .stdout(predicate::str::contains("vault locked"));
+    dry_run().args(["--api-key", "FLAG_KEY"]).assert().success();
+    dry_run()
+        .env("ALPHA_VANTAGE_API_KEY", "ENV_KEY")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_mock_scenario_faults() {
+    let dir = tempfile::tempdir().unwrap();
+    let scenario = dir.path().join("scenario.toml");
+    fs::write(
+        &scenario,
+        "latency_ms = 0\n\n[[endpoint]]\nfunction = \"SPLITS\"\nresponses = [\"malformed_json\"]\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "AAPL", "--no-raw", "--mock-scenario"])
+        .arg(&scenario)
+        .arg("--out-dir")
+        .arg(dir.path().join("out"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "SPLITS for AAPL failed: Parse error: Malformed JSON response",
+        ));
+
+    fs::write(&scenario, "[[endpoint]]\nresponses = [\"slow\"]\n").unwrap();
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "AAPL", "--mock-scenario"])
+        .arg(&scenario)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Unknown outcome 'slow'"));
+}
+
+#[test]
+fn test_concurrent_run_matches_sequential() {
+    let sequential = tempfile::tempdir().unwrap();
+    let concurrent = tempfile::tempdir().unwrap();
+
+    for (dir, concurrency) in [(&sequential, "1"), (&concurrent, "8")] {
+        Command::cargo_bin("alphavantage_cli")
+            .unwrap()
+            .args(["--symbols", "AAPL,NVDA", "--concurrency", concurrency])
+            .arg("--out-dir")
+            .arg(dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Completed 30/30 endpoints (3 failed)",
+            ));
+    }
+
+    for report in ["tickers/AAPL.md", "tickers/NVDA.md"] {
+        let expected = fs::read_to_string(sequential.path().join(report)).unwrap();
+        let actual = fs::read_to_string(concurrent.path().join(report)).unwrap();
+        assert_eq!(expected, actual, "{report} differs");
+    }
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "AAPL", "--concurrency", "0"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cache_commands() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_dir = temp_dir.path().join("cache");
+    fs::write(
+        temp_dir.path().join("alphavantage.toml"),
+        "[api]\napi_key = \"TEST_KEY\"\n\n[rate_limit]\ndaily_limit = 25\n\n[cache.ttl]\nOVERVIEW = \"1d\"\n",
+    )
+    .unwrap();
+
+    // One entry with a TTL, one for an endpoint that is no longer cached
+    let policy =
+        alphavantage_client::CachePolicy::from_pairs([("OVERVIEW", "1d"), ("SPLITS", "1d")])
+            .unwrap();
+    let cache = alphavantage_client::ResponseCache::new(cache_dir.clone(), policy);
+    let no_params = std::collections::BTreeMap::new();
+    let body = serde_json::json!({"Symbol": "IBM"});
+    cache
+        .put(EndpointName::Overview, Some("IBM"), &no_params, &body)
+        .unwrap();
+    cache
+        .put(EndpointName::Splits, Some("IBM"), &no_params, &body)
+        .unwrap();
+
+    let run = |action: &str| {
+        let output = Command::cargo_bin("alphavantage_cli")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .args(["cache", action, "--cache-dir"])
+            .arg(&cache_dir)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    assert!(run("stats").contains("Entries: 2 (1 expired)"));
+    assert!(run("prune").contains("Removed 1 expired responses"));
+    assert!(run("stats").contains("Entries: 1 (0 expired)"));
+    assert!(run("purge").contains("Removed 1 cached responses"));
+    assert!(run("stats").contains("No cached responses."));
+}
+
+#[test]
+fn test_quota_commands() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let state_file = temp_dir.path().join("tokens.json");
+    let now = chrono::Utc::now().to_rfc3339();
+    fs::write(
+        &state_file,
+        format!(
+            r#"{{"keys":{{"abcdef0123456789":{{"tokens_remaining":10,"last_reset":"{now}","daily_limit":25,"calls_today":15,"last_call":"{now}"}}}}}}"#
+        ),
+    )
+    .unwrap();
+
+    let quota = |args: &[&str]| {
+        let output = Command::cargo_bin("alphavantage_cli")
+            .unwrap()
+            // Keep any alphavantage.toml in the repo from changing the limits
+            .current_dir(temp_dir.path())
+            .arg("quota")
+            .args(args)
+            .arg("--json")
+            .arg("--state-file")
+            .arg(&state_file)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+    };
+
+    let status = quota(&["status"]);
+    assert_eq!(status["keys"][0]["fingerprint"], "abcdef0123456789");
+    assert_eq!(status["keys"][0]["remaining"], 10);
+    assert_eq!(status["keys"][0]["calls_today"], 15);
+
+    let status = quota(&["set-remaining", "3"]);
+    assert_eq!(status["keys"][0]["remaining"], 3);
+
+    let status = quota(&["reset"]);
+    assert_eq!(status["keys"][0]["remaining"], 25);
+    assert_eq!(status["keys"][0]["calls_today"], 0);
+
+    let history = quota(&["history"]);
+    assert_eq!(history["keys"][0]["days"].as_array().unwrap().len(), 30);
+
+    // Unknown fingerprints are rejected
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args([
+            "quota",
+            "reset",
+            "--key",
+            "0000000000000000",
+            "--state-file",
+        ])
+        .arg(&state_file)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_dry_run_prints_plan_without_fetching() {
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "AAPL,NVDA", "--dry-run", "--out-dir"])
+        .arg(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Run plan: 30 API calls (4 market, 26 ticker)",
+        ))
+        .stdout(predicate::str::contains("  NVDA: OVERVIEW"));
+
+    assert!(!temp_dir.path().join("index.md").exists());
+    assert!(!temp_dir.path().join("tickers").exists());
+}
+
+#[test]
+fn test_share_class_and_exchange_symbols() {
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "brk.b,BF-B,shop.trt,7203.T", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  BRK.B: OVERVIEW"))
+        .stdout(predicate::str::contains("  BF-B: OVERVIEW"))
+        .stdout(predicate::str::contains("  SHOP.TRT: OVERVIEW"))
+        .stdout(predicate::str::contains("  7203.T: OVERVIEW"));
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["--symbols", "BRK..B", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid ticker 'BRK..B'"));
+}
+
+#[test]
+fn test_endpoint_selection_and_params() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("alphavantage.toml"),
+        "[api]\napi_key = \"FILE_KEY\"\n\n[bulk]\nexclude_endpoints = [\"LISTING_STATUS\"]\n\n\
+         [bulk.params.NEWS_SENTIMENT]\nlimit = 200\n",
+    )
+    .unwrap();
+    let cli = || {
+        let mut cmd = Command::cargo_bin("alphavantage_cli").unwrap();
+        cmd.current_dir(dir.path())
+            .env("ALPHA_VANTAGE_CONFIG_DIR", dir.path().join("user"))
+            .env_remove("ALPHA_VANTAGE_PROFILE");
+        cmd
+    };
+
+    cli()
+        .args(["--symbols", "AAPL,NVDA", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Parameters: NEWS_SENTIMENT limit=200",
+        ))
+        .stdout(predicate::str::contains(
+            "Run plan: 29 API calls (3 market, 26 ticker)",
+        ))
+        .stdout(predicate::str::contains("LISTING_STATUS").not());
+
+    cli()
+        .args(["--symbols", "AAPL,NVDA", "--dry-run"])
+        .args(["--endpoints", "overview,balance-sheet,CASH_FLOW"])
+        .args(["--exclude-endpoints", "CASH_FLOW"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Run plan: 4 API calls (0 market, 4 ticker)",
+        ))
+        .stdout(predicate::str::contains(
+            "  AAPL: OVERVIEW, BALANCE_SHEET\n",
+        ));
+
+    let shown = cli()
+        .args(["--endpoints", "OVERVIEW", "config", "show"])
+        .output()
+        .unwrap();
+    let shown = String::from_utf8(shown.stdout).unwrap();
+    let line = shown
+        .lines()
+        .find(|l| l.starts_with("bulk.endpoints = [\"OVERVIEW\"]"))
+        .unwrap_or_default();
+    assert!(line.ends_with("# --endpoints"), "{shown}");
+
+    cli()
+        .args(["--endpoints", "GLOBAL_QUOTE", "--dry-run"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "GLOBAL_QUOTE is not fetched by bulk runs",
+        ));
+    cli()
+        .args(["--endpoints", "NOPE", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown endpoint 'NOPE'"));
+
+    // Only the selected endpoints are fetched
+    let out = dir.path().join("out");
+    cli()
+        .args(["--symbols", "AAPL", "--endpoints", "OVERVIEW", "--out-dir"])
+        .arg(&out)
+        .assert()
+        .success();
+    assert!(out.join("raw/tickers/AAPL/OVERVIEW.json").exists());
+    assert!(!out.join("raw/tickers/AAPL/CASH_FLOW.json").exists());
+    assert!(!out.join("raw/market").exists());
+}
+
+#[test]
+fn test_resume_skips_completed_tasks() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        Command::cargo_bin("alphavantage_cli")
+            .unwrap()
+            .args(args)
+            .arg("--out-dir")
+            .arg(temp_dir.path())
+            .assert()
+            .success()
+    };
+
+    run(&["--symbols", "AAPL"]);
+    let manifest: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(temp_dir.path().join("run_manifest.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(manifest["tasks"]["AAPL/OVERVIEW"]["status"], "done");
+    let aapl_report = fs::read_to_string(temp_dir.path().join("tickers/AAPL.md")).unwrap();
+
+    // Only NVDA is left to fetch
+    run(&["--symbols", "AAPL,NVDA", "--resume", "--dry-run"])
+        .stdout(predicate::str::contains("Resuming: "))
+        .stdout(predicate::str::contains("  NVDA: OVERVIEW"))
+        .stdout(predicate::str::contains("AAPL: OVERVIEW").not());
+
+    run(&["--symbols", "AAPL,NVDA", "--resume"]);
+    assert!(temp_dir.path().join("tickers/NVDA.md").exists());
+    // AAPL was not fetched (and appended to) again
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("tickers/AAPL.md")).unwrap(),
+        aapl_report
+    );
+    let index = fs::read_to_string(temp_dir.path().join("index.md")).unwrap();
+    assert!(index.contains("**AAPL**"));
+    assert!(index.contains("**NVDA**"));
+}
+
+#[test]
+fn test_resume_refetches_changed_params() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("alphavantage.toml");
+    let run = |args: &[&str]| {
+        Command::cargo_bin("alphavantage_cli")
+            .unwrap()
+            .current_dir(dir.path())
+            .env("ALPHA_VANTAGE_CONFIG_DIR", dir.path().join("user"))
+            .env_remove("ALPHA_VANTAGE_PROFILE")
+            .args([
+                "--symbols",
+                "AAPL",
+                "--endpoints",
+                "NEWS_SENTIMENT",
+                "--no-cache",
+            ])
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    fs::write(&config, "[bulk.params.NEWS_SENTIMENT]\nlimit = 50\n").unwrap();
+    run(&[]);
+    run(&["--resume", "--dry-run"]).stdout(predicate::str::contains("AAPL: NEWS_SENTIMENT").not());
+
+    fs::write(&config, "[bulk.params.NEWS_SENTIMENT]\nlimit = 200\n").unwrap();
+    run(&["--resume", "--dry-run"]).stdout(predicate::str::contains("  AAPL: NEWS_SENTIMENT"));
+}
+
+#[test]
+fn test_config_show_layers() {
+    let dir = tempfile::tempdir().unwrap();
+    let user_dir = dir.path().join("user");
+    fs::create_dir(&user_dir).unwrap();
+    fs::write(
+        user_dir.join("config.toml"),
+        "[rate_limit]\nmin_delay_ms = 1500\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("alphavantage.toml"),
+        "[api]\napi_key = \"FILE_KEY\"\n\n[rate_limit]\ndaily_limit = 25\n\n[profile.premium.rate_limit]\ndaily_limit = 1000\n",
+    )
+    .unwrap();
+    let config = || {
+        let mut cmd = Command::cargo_bin("alphavantage_cli").unwrap();
+        cmd.current_dir(dir.path())
+            .env("ALPHA_VANTAGE_CONFIG_DIR", &user_dir)
+            .env_remove("ALPHA_VANTAGE_API_KEY")
+            .env_remove("ALPHA_VANTAGE_PROFILE")
+            .env_remove("ALPHA_VANTAGE_MIN_DELAY_MS");
+        cmd
+    };
+
+    let output = config()
+        .env("ALPHA_VANTAGE_PROFILE", "premium")
+        .env("ALPHA_VANTAGE_MIN_DELAY_MS", "800")
+        .args(["--api-key", "FLAG_KEY", "config", "show"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let shown = String::from_utf8(output.stdout).unwrap();
+    assert!(shown.contains("Profile: premium"), "{shown}");
+    assert!(!shown.contains("FILE_KEY"), "{shown}");
+    let line = |key: &str| {
+        shown
+            .lines()
+            .find(|l| l.starts_with(&format!("{key} =")))
+            .unwrap_or_default()
+            .to_string()
+    };
+    assert!(!shown.contains("FLAG_KEY"), "{shown}");
+    assert!(line("rate_limit.daily_limit").contains("= 1000"), "{shown}");
+    assert!(line("rate_limit.daily_limit").contains("# profile premium"));
+    assert!(line("rate_limit.min_delay_ms").contains("# env ALPHA_VANTAGE_MIN_DELAY_MS"));
+    assert!(line("api.api_key").contains("# --api-key"), "{shown}");
+    assert!(line("api.key_strategy").contains("# default"));
+
+    config()
+        .args(["--profile", "enterprise", "config", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown profile 'enterprise'"));
+
+    fs::write(dir.path().join("alphavantage.toml"), "[api\n").unwrap();
+    config()
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to parse config file"));
+}
+
+#[test]
+fn test_config_init_and_doctor() {
+    let dir = tempfile::tempdir().unwrap();
+    let cli = || {
+        let mut cmd = Command::cargo_bin("alphavantage_cli").unwrap();
+        cmd.current_dir(dir.path())
+            .env("ALPHA_VANTAGE_CONFIG_DIR", dir.path().join("user"))
+            .env("ALPHA_VANTAGE_CACHE_DIR", dir.path().join("cache"))
+            .env_remove("ALPHA_VANTAGE_API_KEY")
+            .env_remove("ALPHA_VANTAGE_PROFILE");
+        cmd
+    };
+
+    // Without a key the doctor fails and says how to add one
+    cli()
+        .args(["doctor", "--offline", "--state-file", "tokens.json"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[FAIL] api key"))
+        .stdout(predicate::str::contains("config init"));
+    // The rate limiter creates the state file's directory, so a missing one is fine
+    cli()
+        .args([
+            "doctor",
+            "--offline",
+            "--state-file",
+            "state/new/tokens.json",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[ok  ] state file"));
+    assert!(!dir.path().join("state").exists());
+
+    cli()
+        .args([
+            "config",
+            "init",
+            "--tier",
+            "premium-75",
+            "--key-env",
+            "DOCTOR_KEY",
+        ])
+        .assert()
+        .success();
+    let written = fs::read_to_string(dir.path().join("alphavantage.toml")).unwrap();
+    assert!(
+        written.contains("api_key_env = \"DOCTOR_KEY\""),
+        "{written}"
+    );
+    assert!(written.contains("limit = 75"), "{written}");
+    cli()
+        .args(["config", "init"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime
+        .block_on(fake_alphavantage::FakeServer::start_local(
+            fake_alphavantage::FakeConfig::default(),
+        ))
+        .unwrap();
+    cli()
+        .env("DOCTOR_KEY", "FAKE_KEY")
+        .args(["doctor", "--state-file", "tokens.json", "--base-url"])
+        .arg(server.base_url())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[ok  ] key check"))
+        .stdout(predicate::str::contains("0 failed"));
+    assert_eq!(server.request_count(), 1);
+
+    fs::write(dir.path().join("alphavantage.toml"), "[api]\nbogus = 1\n").unwrap();
+    cli()
+        .args(["doctor", "--offline", "--state-file", "tokens.json"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[FAIL] config"))
+        .stdout(predicate::str::contains("bogus"));
+}
+
+#[test]
+fn test_watchlist_symbols_file_and_universe() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("alphavantage.toml"),
+        "[watchlists]\nsemis = [\"NVDA\", \"MU\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("mine.csv"),
+        "# holdings\nName,Symbol\nIBM Corp,IBM\n\"Nvidia, Inc\",NVDA\n",
+    )
+    .unwrap();
+    let listing = dir.path().join("cache/LISTING_STATUS/_market");
+    fs::create_dir_all(&listing).unwrap();
+    let csv = "symbol,name,exchange,assetType,ipoDate,delistingDate,status\n\
+               AAPL,Apple Inc,NASDAQ,Stock,1980-12-12,null,Active\n\
+               QQQ,Invesco QQQ Trust, Series 1,NASDAQ,ETF,1999-03-10,null,Active\n\
+               SPY,SPDR S&P 500 ETF Trust,NYSE ARCA,ETF,1993-01-22,null,Active\n";
+    fs::write(
+        listing.join("default.json"),
+        serde_json::json!({
+            "function": "LISTING_STATUS",
+            "symbol": null,
+            "params": {},
+            "fetched_at": "2026-01-05T00:00:00Z",
+            "response": { "csv_content": csv },
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let dry_run = || {
+        let mut cmd = Command::cargo_bin("alphavantage_cli").unwrap();
+        cmd.current_dir(dir.path())
+            .env("ALPHA_VANTAGE_CONFIG_DIR", dir.path().join("user"))
+            .env("ALPHA_VANTAGE_CACHE_DIR", dir.path().join("cache"))
+            .env_remove("ALPHA_VANTAGE_PROFILE")
+            .arg("--dry-run");
+        cmd
+    };
+
+    let output = dry_run()
+        .args(["--watchlist", "semis", "--symbols-file", "mine.csv"])
+        .args([
+            "--universe",
+            "listing-status",
+            "--exchange",
+            "NASDAQ",
+            "--asset-type",
+            "etf",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let plan = String::from_utf8(output.stdout).unwrap();
+    let planned: Vec<&str> = plan
+        .lines()
+        .filter_map(|l| l.trim().split_once(": OVERVIEW").map(|(symbol, _)| symbol))
+        .collect();
+    assert_eq!(planned, ["NVDA", "MU", "IBM", "QQQ"], "{plan}");
+
+    dry_run()
+        .args(["--watchlist", "tech"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Unknown watchlist 'tech'"))
+        .stdout(predicate::str::contains("semis"));
+}
+
+#[test]
+fn test_transcript_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let cli = || {
+        let mut cmd = Command::cargo_bin("alphavantage_cli").unwrap();
+        cmd.arg("--out-dir").arg(dir.path());
+        cmd
+    };
+
+    cli()
+        .args(["--dry-run", "earnings-call-transcript", "--symbol", "aapl"])
+        .args(["--from", "2023Q3", "--to", "2024Q2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Transcript plan: AAPL 2023Q3 to 2024Q2 (4 quarters)",
+        ))
+        .stdout(predicate::str::contains(
+            "Fetch now: 2024Q2, 2024Q1, 2023Q4, 2023Q3",
+        ));
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    cli()
+        .args(["--over-quota", "spread", "earnings-call-transcript"])
+        .args(["--symbol", "AAPL", "--from", "2023Q3", "--to", "2024Q2"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "not supported for transcript ranges",
+        ));
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+
+    cli()
+        .args(["earnings-call-transcript", "--symbol", "AAPL"])
+        .args(["--from", "2023Q3", "--to", "2024Q2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(4 of 4 quarters)"));
+    let report = fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .find(|p| {
+            p.file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("earnings-call-transcript_AAPL_2023Q3-2024Q2_")
+        })
+        .unwrap();
+    let content = fs::read_to_string(report).unwrap();
+    assert!(content.contains("## Sentiment Summary"), "{content}");
+    assert!(content.contains("\n| 2023Q3 | "), "{content}");
+    assert!(content.contains("\n## 2024Q2\n"), "{content}");
+    assert!(content.contains("\n### Q&A\n"), "{content}");
+    assert!(content.contains("\n#### Operator\n"), "{content}");
+    let raw = fs::read_dir(dir.path().join("raw")).unwrap().count();
+    assert_eq!(raw, 4);
+
+    cli()
+        .args(["earnings-call-transcript", "--symbol", "AAPL"])
+        .args(["--from", "2024Q2", "--to", "2023Q3"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Invalid quarter range: 2024Q2 is after 2023Q3",
+        ));
+    cli()
+        .args(["earnings-call-transcript", "--symbol", "AAPL"])
+        .args(["--latest", "4294967295"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "at most 100 can be fetched at once",
+        ));
+    cli()
+        .args(["earnings-call-transcript", "--symbol", "AAPL"])
+        .args(["--from", "2000Q1", "--to", "2025Q1"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("has 101 quarters"));
+    cli()
+        .args([
+            "earnings-call-transcript",
+            "--symbol",
+            "AAPL",
+            "--year",
+            "2024",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--quarter"));
+}
+
+#[test]
+fn test_transcript_range_replays_each_quarter() {
+    let recorded = tempfile::tempdir().unwrap();
+    for (quarter, said) in [
+        ("2023Q3", "Summer was slow."),
+        ("2023Q4", "Winter was busy."),
+    ] {
+        let body = serde_json::json!({
+            "symbol": "AAPL",
+            "quarter": quarter,
+            "transcript": [{"speaker": "Tim Cook", "title": "CEO", "content": said, "sentiment": "0.5"}]
+        });
+        fs::write(
+            recorded.path().join(format!(
+                "earnings-call-transcript_AAPL_{quarter}_20260101_090000.json"
+            )),
+            body.to_string(),
+        )
+        .unwrap();
+    }
+    let out = tempfile::tempdir().unwrap();
+    let cli = || {
+        let mut cmd = Command::cargo_bin("alphavantage_cli").unwrap();
+        cmd.arg("--replay-from")
+            .arg(recorded.path())
+            .arg("--out-dir")
+            .arg(out.path());
+        cmd
+    };
+
+    cli()
+        .args(["earnings-call-transcript", "--symbol", "AAPL"])
+        .args(["--from", "2023Q3", "--to", "2023Q4"])
+        .assert()
+        .success();
+    let report = fs::read_dir(out.path())
+        .unwrap()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .find(|p| p.extension().is_some_and(|e| e == "md"))
+        .unwrap();
+    let report = fs::read_to_string(report).unwrap();
+    assert!(report.contains("Summer was slow."), "{report}");
+    assert!(report.contains("Winter was busy."), "{report}");
+
+    // A quarter that was never recorded is not served from another one
+    cli()
+        .args(["earnings-call-transcript", "--symbol", "AAPL"])
+        .args(["--year", "2024", "--quarter", "Q1"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_transcripts_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../client/fixtures");
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["transcripts", "--terms", "AI,supply", "--symbol", "nvda,MU"])
+        .arg("--dir")
+        .arg(&fixtures)
+        .arg("--output")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(2 transcripts)"));
+    let report = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .next()
+        .unwrap();
+    let content = fs::read_to_string(report).unwrap();
+    assert!(
+        content.contains("| Quarter | Calls | AI | supply |"),
+        "{content}"
+    );
+    assert!(content.contains("\n| NVDA | 2024Q4 | "), "{content}");
+    assert!(content.contains("\n| MU | 2024Q1 | "), "{content}");
+    assert!(!content.contains("AAPL"), "{content}");
+    assert!(
+        content.contains("| NVDA | Jensen Huang | CEO |"),
+        "{content}"
+    );
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args(["transcripts", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No saved transcripts found"));
+}
+
+#[test]
+fn test_news_sentiment_analytics() {
+    let dir = tempfile::tempdir().unwrap();
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args([
+            "--symbols",
+            "AAPL",
+            "--endpoints",
+            "NEWS_SENTIMENT",
+            "--out-dir",
+        ])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(dir.path().join("tickers/AAPL.md")).unwrap();
+    assert!(
+        content.contains("## NEWS_SENTIMENT - AAPL daily sentiment"),
+        "{content}"
+    );
+    assert!(
+        content.contains("| Date | Articles | Weighted Score | Label |"),
+        "{content}"
+    );
+    assert!(
+        content.contains("## NEWS_SENTIMENT - AAPL topics"),
+        "{content}"
+    );
+    assert!(
+        content.contains("## NEWS_SENTIMENT - AAPL sources"),
+        "{content}"
+    );
+    assert!(
+        content.contains("## NEWS_SENTIMENT - AAPL most bearish"),
+        "{content}"
+    );
+    assert!(content.contains("(https://example.com/news/"), "{content}");
+}
+
+#[test]
+fn test_insider_activity() {
+    let dir = tempfile::tempdir().unwrap();
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args([
+            "--symbols",
+            "MU",
+            "--endpoints",
+            "INSIDER_TRANSACTIONS",
+            "--out-dir",
+        ])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(dir.path().join("tickers/MU.md")).unwrap();
+    assert!(
+        content.contains("## INSIDER_TRANSACTIONS - Transactions"),
+        "{content}"
+    );
+    assert!(
+        content.contains("## Insider Activity - net by insider"),
+        "{content}"
+    );
+    assert!(
+        content.contains("## Insider Activity - rolling 90-day flow"),
+        "{content}"
+    );
+    assert!(
+        content.contains(
+            "| 2023-12-08 | 2023-12-20 | 3 | MEHROTRA SANJAY, SWAN ROBERT H, BEYER RICHARD M |"
+        ),
+        "{content}"
+    );
+    assert!(content.contains("\n| Director | 2 | 3 |"), "{content}");
+}
+
+#[test]
+fn test_unreadable_analytics_are_skipped() {
+    let replay = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+    let raw = replay.path().join("raw/tickers/MU");
+    fs::create_dir_all(&raw).unwrap();
+    fs::write(
+        raw.join("INSIDER_TRANSACTIONS.json"),
+        r#"{"symbol": "MU", "data": {"unexpected": "shape"}}"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("alphavantage_cli")
+        .unwrap()
+        .args([
+            "--symbols",
+            "MU",
+            "--endpoints",
+            "INSIDER_TRANSACTIONS",
+            "--replay-from",
+        ])
+        .arg(replay.path())
+        .arg("--out-dir")
+        .arg(out.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipping INSIDER_TRANSACTIONS analytics for MU",
+        ))
+        .stdout(predicate::str::contains("Partial success").not());
+    assert!(out.path().join("tickers/MU.md").exists());
+}
@@ -135,8 +135,11 @@ fn test_endpoint_routing_earnings_call_transcript() {
     let symbol = TickerSymbol::new("AAPL").unwrap();
     let cmd = Commands::EarningsCallTranscript {
         symbol: symbol.clone(),
-        year: 2024,
-        quarter: QuarterParam::Q1,
+        year: Some(2024),
+        quarter: Some(QuarterParam::Q1),
+        from: None,
+        to: None,
+        latest: None,
         output: None,
     };
 
@@ -148,8 +151,8 @@ fn test_endpoint_routing_earnings_call_transcript() {
             ..
         } => {
             assert_eq!(s.to_string(), "AAPL");
-            assert_eq!(year, 2024);
-            assert_eq!(quarter, QuarterParam::Q1);
+            assert_eq!(year, Some(2024));
+            assert_eq!(quarter, Some(QuarterParam::Q1));
         }
         _ => panic!("Expected EarningsCallTranscript command"),
     }
@@ -254,8 +257,11 @@ fn test_all_endpoint_commands_exist() {
         },
         Commands::EarningsCallTranscript {
             symbol: symbol.clone(),
-            year: 2024,
-            quarter: alphavantage_core::domain::QuarterParam::Q1,
+            year: Some(2024),
+            quarter: Some(alphavantage_core::domain::QuarterParam::Q1),
+            from: None,
+            to: None,
+            latest: None,
             output: None,
         },
     ];
@@ -1,50 +1,102 @@
-use crate::rate_limiter::RateLimiter;
+use crate::http_settings::HttpSettings;
+use crate::key_pool::KeyPool;
+use crate::rate_limiter::{RateLimiter, RateLimits};
+use crate::redact::{scrub, scrub_error, scrub_json};
 use crate::retry_policy::execute_with_retry;
-use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol};
+use alphavantage_core::domain::{ApiKey, EndpointName, KeyUsage, TickerSymbol};
 use alphavantage_core::ports::ApiClient;
 use alphavantage_core::{ExplorerError, Result};
 use async_trait::async_trait;
 use reqwest::Client;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::instrument;
+use tracing::{instrument, warn};
 
-const BASE_URL: &str = "https://www.alphavantage.co/query";
+pub const BASE_URL: &str = "https://www.alphavantage.co/query";
+
+/// Response headers that are never worth keeping in a recording.
+const UNRECORDED_HEADERS: [&str; 2] = ["set-cookie", "date"];
+
+/// One successful request and its response, minus the API key.
+#[derive(Debug, Clone)]
+pub struct HttpExchange {
+    /// Query parameters sent, without `apikey`
+    pub params: BTreeMap<String, String>,
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: serde_json::Value,
+    /// Key the request was made with; differs from the caller's when a pool is used
+    pub api_key: ApiKey,
+}
 
 pub struct AlphaVantageClient {
     http_client: Client,
     rate_limiter: Arc<RateLimiter>,
     base_url: String,
+    key_pool: Option<KeyPool>,
 }
 
 impl AlphaVantageClient {
-    /// # Panics
-    /// Panics if the HTTP client builder fails (e.g. TLS backend issue).
-    #[must_use]
-    #[allow(clippy::expect_used)]
-    pub fn new(daily_limit: u32, min_delay_ms: u64) -> Self {
+    /// Client for the public API with default HTTP settings.
+    ///
+    /// # Errors
+    /// Returns error if the HTTP client cannot be built (e.g. TLS backend issue).
+    pub fn new(daily_limit: u32, min_delay_ms: u64) -> Result<Self> {
         Self::with_base_url(BASE_URL, daily_limit, min_delay_ms)
     }
 
-    /// # Panics
-    /// Panics if the HTTP client builder fails.
-    #[must_use]
-    #[allow(clippy::expect_used)]
-    pub fn with_base_url(base_url: impl Into<String>, daily_limit: u32, min_delay_ms: u64) -> Self {
-        Self {
-            http_client: Client::builder()
-                .timeout(Duration::from_secs(10))
-                .build()
-                .expect("Failed to build HTTP client"),
-            rate_limiter: Arc::new(RateLimiter::new(daily_limit, min_delay_ms)),
+    /// # Errors
+    /// Returns error if the HTTP client cannot be built.
+    pub fn with_base_url(
+        base_url: impl Into<String>,
+        daily_limit: u32,
+        min_delay_ms: u64,
+    ) -> Result<Self> {
+        Self::with_limits(base_url, RateLimits::daily(daily_limit, min_delay_ms))
+    }
+
+    /// # Errors
+    /// Returns error if the HTTP client cannot be built.
+    pub fn with_limits(base_url: impl Into<String>, limits: RateLimits) -> Result<Self> {
+        Self::with_settings(base_url, limits, &HttpSettings::default())
+    }
+
+    /// Client whose HTTP behavior (timeouts, proxy, headers, CA bundle) follows `http`.
+    ///
+    /// # Errors
+    /// Returns error if `http` is invalid or the HTTP client cannot be built.
+    pub fn with_settings(
+        base_url: impl Into<String>,
+        limits: RateLimits,
+        http: &HttpSettings,
+    ) -> Result<Self> {
+        Ok(Self {
+            http_client: http.build_client()?,
+            rate_limiter: Arc::new(RateLimiter::with_limits(
+                RateLimiter::resolve_state_path(),
+                limits,
+            )),
             base_url: base_url.into(),
-        }
+            key_pool: None,
+        })
     }
-}
 
-impl Default for AlphaVantageClient {
-    fn default() -> Self {
-        Self::new(25, 1000) // Default to free tier limit and delay
+    /// Uses `limiter` instead of one persisting to the default state file.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Arc::new(limiter);
+        self
+    }
+
+    /// Uses `pool` instead of the key passed to each fetch call.
+    ///
+    /// When a key runs out of quota the request is retried with the next key in the pool.
+    #[must_use]
+    pub fn with_key_pool(mut self, pool: KeyPool) -> Self {
+        if !pool.is_empty() {
+            self.key_pool = Some(pool);
+        }
+        self
     }
 }
 
@@ -55,11 +107,12 @@ impl ApiClient for AlphaVantageClient {
         &self,
         endpoint: EndpointName,
         ticker: &TickerSymbol,
-        params: Option<&std::collections::HashMap<String, String>>,
+        params: Option<&HashMap<String, String>>,
         api_key: &ApiKey,
     ) -> Result<serde_json::Value> {
         self.execute_request(endpoint, Some(ticker.as_str()), params, api_key)
             .await
+            .map(|exchange| exchange.body)
     }
 
     #[instrument(skip(self, api_key), fields(endpoint = %endpoint.function_name()))]
@@ -68,23 +121,64 @@ impl ApiClient for AlphaVantageClient {
         endpoint: EndpointName,
         api_key: &ApiKey,
     ) -> Result<serde_json::Value> {
-        self.execute_request(endpoint, None, None, api_key).await
+        self.execute_request(endpoint, None, None, api_key)
+            .await
+            .map(|exchange| exchange.body)
+    }
+
+    fn key_usage(&self) -> Vec<KeyUsage> {
+        self.rate_limiter.usage()
     }
 }
 
 impl AlphaVantageClient {
-    async fn execute_request(
+    /// Makes one request and returns the full exchange rather than just the body.
+    ///
+    /// # Errors
+    /// Fails like the `ApiClient` fetch methods.
+    pub async fn execute_request(
         &self,
         endpoint: EndpointName,
         symbol: Option<&str>,
-        extra_params: Option<&std::collections::HashMap<String, String>>,
+        extra_params: Option<&HashMap<String, String>>,
         api_key: &ApiKey,
-    ) -> Result<serde_json::Value> {
-        let is_demo = api_key.secret() == "demo";
+    ) -> Result<HttpExchange> {
+        let Some(pool) = &self.key_pool else {
+            return self
+                .execute_with_key(endpoint, symbol, extra_params, api_key)
+                .await;
+        };
+
+        for key in pool.candidates() {
+            match self
+                .execute_with_key(endpoint, symbol, extra_params, key)
+                .await
+            {
+                Err(ExplorerError::RateLimited) => {
+                    warn!(
+                        key = %key.fingerprint(),
+                        "API key exhausted, failing over to the next key in the pool"
+                    );
+                    pool.mark_exhausted(key);
+                }
+                other => return other,
+            }
+        }
+        Err(ExplorerError::RateLimited)
+    }
+
+    async fn execute_with_key(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        extra_params: Option<&HashMap<String, String>>,
+        api_key: &ApiKey,
+    ) -> Result<HttpExchange> {
+        let is_demo = api_key.is_demo();
 
         // Skip rate limiter only for "demo" key
         if !is_demo {
-            self.rate_limiter.wait().await?;
+            self.rate_limiter.wait(api_key).await?;
         }
 
         let url = self.base_url.clone();
@@ -96,7 +190,7 @@ impl AlphaVantageClient {
         let params_owned = extra_params.cloned();
 
         // Execute with retry
-        execute_with_retry(move || {
+        let (status, headers, body) = execute_with_retry(move || {
             let client = client.clone();
             let url = url.clone();
             let function = function.to_string();
@@ -120,12 +214,20 @@ impl AlphaVantageClient {
                 let resp = req
                     .send()
                     .await
-                    .map_err(|e| ExplorerError::Network(e.to_string()))?;
+                    .map_err(|e| ExplorerError::Network(scrub(&e.to_string(), &key)))?;
 
                 let status = resp.status();
                 if !status.is_success() {
                     return Err(ExplorerError::HttpStatus(status.as_u16()));
                 }
+                let headers: BTreeMap<String, String> = resp
+                    .headers()
+                    .iter()
+                    .filter(|(name, _)| !UNRECORDED_HEADERS.contains(&name.as_str()))
+                    .filter_map(|(name, value)| {
+                        Some((name.to_string(), value.to_str().ok()?.to_string()))
+                    })
+                    .collect();
 
                 // Read response as text first
                 let content_type = resp
@@ -138,48 +240,70 @@ impl AlphaVantageClient {
                 let text = resp
                     .text()
                     .await
-                    .map_err(|e| ExplorerError::Network(e.to_string()))?;
-
-                // Try parsing as JSON
-                let json: serde_json::Value = match serde_json::from_str(&text) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        // If parsing fails, check if it looks like CSV or if Content-Type indicates CSV
-                        if content_type.contains("text/csv")
-                            || content_type.contains("application/csv")
-                            || text.contains(',')
-                        {
-                            // Wrap CSV in a special JSON structure so we can pass it through the existing pipeline
-                            serde_json::json!({ "csv_content": text })
-                        } else {
-                            // If it's not JSON and not obviously CSV, return the parse error (or the text wrapped?)
-                            // Better to error out if we can't understand it, unless it's a raw error message
-                            return Err(ExplorerError::Network(format!(
-                                "Failed to parse response as JSON: {text}"
-                            )));
-                        }
-                    }
-                };
-
-                // Check for API soft errors (200 OK but body has Error)
-                if let Some(err_msg) = json.get("Error Message") {
-                    return Err(ExplorerError::ProviderError(err_msg.to_string()));
-                }
-                if let Some(note) = json.get("Note") {
-                    // "Thank you for using Alpha Vantage!..." means rate limit hit
-                    if note.to_string().contains("call volume") {
-                        return Err(ExplorerError::RateLimited);
-                    }
-                }
-                // Check if it's an Information field (sometimes implies error/limit)
-                if json.get("Information").is_some() {
-                    // Usually not an error, but worth noting?
-                    // For now accept it unless it's clearly a limit message.
-                }
+                    .map_err(|e| ExplorerError::Network(scrub(&e.to_string(), &key)))?;
+
+                // Errors are scrubbed here, before the retry loop logs them
+                let json = parse_body(&text, &content_type).map_err(|e| scrub_error(e, &key))?;
 
-                Ok(json)
+                Ok((status.as_u16(), headers, scrub_json(json, &key)))
             }
         })
-        .await
+        .await?;
+
+        let mut params: BTreeMap<String, String> = extra_params
+            .map(|p| p.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+        params.insert("function".to_string(), function.to_string());
+        if let Some(s) = symbol {
+            params.insert("symbol".to_string(), s.to_string());
+        }
+        Ok(HttpExchange {
+            params,
+            status,
+            headers,
+            body,
+            api_key: api_key.clone(),
+        })
+    }
+}
+
+/// Interprets a response body the way Alpha Vantage uses it.
+///
+/// CSV endpoints are wrapped as `{"csv_content": ...}`. Bodies that start like JSON
+/// but do not parse (e.g. a truncated payload) are a parse error rather than CSV.
+/// `Error Message` bodies and call-volume `Note`s are mapped to errors.
+pub(crate) fn parse_body(text: &str, content_type: &str) -> Result<serde_json::Value> {
+    let json: serde_json::Value = match serde_json::from_str(text) {
+        Ok(v) => v,
+        Err(e) if text.trim_start().starts_with(['{', '[']) => {
+            return Err(ExplorerError::Parse(format!(
+                "Malformed JSON response: {e}"
+            )));
+        }
+        Err(_) => {
+            if content_type.contains("text/csv")
+                || content_type.contains("application/csv")
+                || text.contains(',')
+            {
+                // Wrap CSV in a special JSON structure so we can pass it through the existing pipeline
+                serde_json::json!({ "csv_content": text })
+            } else {
+                return Err(ExplorerError::Network(format!(
+                    "Failed to parse response as JSON: {text}"
+                )));
+            }
+        }
+    };
+
+    // Check for API soft errors (200 OK but body has Error)
+    if let Some(err_msg) = json.get("Error Message") {
+        return Err(ExplorerError::ProviderError(err_msg.to_string()));
+    }
+    if let Some(note) = json.get("Note") {
+        // "Thank you for using Alpha Vantage!..." means rate limit hit
+        if note.to_string().contains("call volume") {
+            return Err(ExplorerError::RateLimited);
+        }
     }
+    Ok(json)
 }
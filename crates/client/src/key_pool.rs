@@ -0,0 +1,116 @@
+use alphavantage_core::domain::ApiKey;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How a `KeyPool` picks the key for the next call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyStrategy {
+    /// Use one key until it is exhausted, then move to the next
+    #[default]
+    Failover,
+    /// Spread calls evenly across all keys
+    RoundRobin,
+}
+
+/// A set of API keys shared by one client.
+///
+/// The pool only decides the order in which keys are tried; quota accounting stays
+/// with the `RateLimiter`, which tracks each key separately.
+pub struct KeyPool {
+    keys: Vec<ApiKey>,
+    strategy: KeyStrategy,
+    /// Failover: index of the key currently in use. Round-robin: next starting index.
+    cursor: AtomicUsize,
+}
+
+impl KeyPool {
+    #[must_use]
+    pub const fn new(keys: Vec<ApiKey>, strategy: KeyStrategy) -> Self {
+        Self {
+            keys,
+            strategy,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns every key, in the order they should be tried for the next call.
+    #[must_use]
+    pub fn candidates(&self) -> Vec<&ApiKey> {
+        if self.keys.is_empty() {
+            return Vec::new();
+        }
+        let start = match self.strategy {
+            KeyStrategy::Failover => self.cursor.load(Ordering::Relaxed),
+            KeyStrategy::RoundRobin => self.cursor.fetch_add(1, Ordering::Relaxed),
+        } % self.keys.len();
+
+        self.keys
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(self.keys.len())
+            .collect()
+    }
+
+    /// Records that `key` is out of quota, so failover stops starting with it.
+    pub fn mark_exhausted(&self, key: &ApiKey) {
+        if self.strategy != KeyStrategy::Failover {
+            return;
+        }
+        if let Some(index) = self.keys.iter().position(|k| k.secret() == key.secret()) {
+            let next = (index + 1) % self.keys.len();
+            let _ = self
+                .cursor
+                .compare_exchange(index, next, Ordering::Relaxed, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(strategy: KeyStrategy) -> KeyPool {
+        KeyPool::new(
+            vec![ApiKey::new("a"), ApiKey::new("b"), ApiKey::new("c")],
+            strategy,
+        )
+    }
+
+    fn order(pool: &KeyPool) -> Vec<String> {
+        pool.candidates()
+            .into_iter()
+            .map(|k| k.secret().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_robin_rotates_start() {
+        let pool = pool(KeyStrategy::RoundRobin);
+        assert_eq!(order(&pool), vec!["a", "b", "c"]);
+        assert_eq!(order(&pool), vec!["b", "c", "a"]);
+        assert_eq!(order(&pool), vec!["c", "a", "b"]);
+        assert_eq!(order(&pool), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_failover_sticks_until_exhausted() {
+        let pool = pool(KeyStrategy::Failover);
+        assert_eq!(order(&pool), vec!["a", "b", "c"]);
+        assert_eq!(order(&pool), vec!["a", "b", "c"]);
+
+        pool.mark_exhausted(&ApiKey::new("a"));
+        assert_eq!(order(&pool), vec!["b", "c", "a"]);
+    }
+}
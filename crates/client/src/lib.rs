@@ -1,21 +1,38 @@
 pub mod csv_handler;
 pub mod http_client;
+pub mod http_settings;
 pub mod json_persister;
+pub mod key_pool;
 pub mod markdown_writer;
 pub mod mock_client;
+pub mod mock_scenario;
 pub mod rate_limiter;
+pub mod recording_client;
+pub mod redact;
+pub mod replay_client;
+pub mod reset_rule;
+pub mod response_cache;
 pub mod retry_policy;
 pub mod schema_analyzer;
 
 pub use csv_handler::CsvHandler;
+pub use http_client::{AlphaVantageClient, HttpExchange};
+pub use http_settings::HttpSettings;
 pub use json_persister::FileSystemJsonPersister;
+pub use key_pool::{KeyPool, KeyStrategy};
 pub use markdown_writer::MarkdownWriterImpl;
 pub use mock_client::MockAlphaVantageClient as MockClient;
-pub use rate_limiter::RateLimiter;
+pub use mock_scenario::MockScenario;
+pub use rate_limiter::{
+    DailyCalls, KeyHistory, QuotaStatus, RateLimiter, RateLimits, RateWindow, WindowPeriod,
+};
+pub use recording_client::RecordingClient;
+pub use replay_client::ReplayClient;
+pub use reset_rule::ResetRule;
+pub use response_cache::{CachePolicy, CacheStats, CachingClient, ResponseCache};
 pub use schema_analyzer::SchemaAnalyzerImpl;
 
 use alphavantage_core::ports::ApiClient;
-use http_client::AlphaVantageClient;
 use mock_client::MockAlphaVantageClient;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,10 +41,17 @@ pub enum ClientMode {
     Mock,
 }
 
-#[must_use]
-pub fn create_client(mode: ClientMode, daily_limit: u32, min_delay_ms: u64) -> Box<dyn ApiClient> {
-    match mode {
-        ClientMode::Live => Box::new(AlphaVantageClient::new(daily_limit, min_delay_ms)),
+/// # Errors
+/// Returns error if the live HTTP client cannot be built.
+pub fn create_client(
+    mode: ClientMode,
+    limits: &RateLimits,
+) -> alphavantage_core::Result<Box<dyn ApiClient>> {
+    Ok(match mode {
+        ClientMode::Live => Box::new(AlphaVantageClient::with_limits(
+            http_client::BASE_URL,
+            limits.clone(),
+        )?),
         ClientMode::Mock => Box::new(MockAlphaVantageClient::new()),
-    }
+    })
 }
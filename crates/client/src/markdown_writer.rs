@@ -1,6 +1,7 @@
 use alphavantage_core::domain::SchemaTable;
 use alphavantage_core::error::{ExplorerError, Result};
 use alphavantage_core::ports::MarkdownWriter;
+use alphavantage_core::util::escape_cell;
 use std::fmt::Write;
 use std::path::Path;
 
@@ -74,8 +75,8 @@ impl MarkdownWriter for MarkdownWriterImpl {
 
         // 4. Rows
         for row in &table.rows {
-            // Basic escaping of pipes might be needed, but sticking to simple strings for now
-            let row_line = format!("| {} |", row.join(" | "));
+            let cells: Vec<String> = row.iter().map(|cell| escape_cell(cell)).collect();
+            let row_line = format!("| {} |", cells.join(" | "));
             writeln!(self.buffer, "{row_line}")
                 .map_err(|_| ExplorerError::Io(std::io::Error::other("fmt write error")))?;
         }
@@ -143,6 +144,23 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_pipes_in_cells_are_escaped() {
+        let mut writer = MarkdownWriterImpl::new();
+        let table = SchemaTable::new(
+            "Pipes",
+            vec!["H".to_string()],
+            vec![vec!["a | b".to_string()], vec!["line\nbreak".to_string()]],
+        );
+        writer.write_table(&table).unwrap();
+        assert!(writer.buffer.contains("| a \\| b |"), "{}", writer.buffer);
+        assert!(
+            writer.buffer.contains("| line break |"),
+            "{}",
+            writer.buffer
+        );
+    }
+
     #[test]
     fn test_truncation_note() {
         let mut writer = MarkdownWriterImpl::new();
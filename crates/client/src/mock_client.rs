@@ -2,6 +2,10 @@ use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol};
 use alphavantage_core::ports::ApiClient;
 use alphavantage_core::{ExplorerError, Result};
 use async_trait::async_trait;
+
+use crate::http_client::parse_body;
+use crate::mock_scenario::{self, MockScenario, Outcome};
+use crate::retry_policy::execute_with_retry;
 use std::path::PathBuf;
 use std::time::Duration;
 use tokio::fs;
@@ -9,6 +13,7 @@ use tokio::time::sleep;
 
 pub struct MockAlphaVantageClient {
     fixture_dir: PathBuf,
+    scenario: Option<MockScenario>,
 }
 
 impl Default for MockAlphaVantageClient {
@@ -22,10 +27,24 @@ impl MockAlphaVantageClient {
     pub fn new() -> Self {
         let manifest_dir = env!("CARGO_MANIFEST_DIR");
         let path = std::path::PathBuf::from(manifest_dir).join("fixtures");
-        Self { fixture_dir: path }
+        Self {
+            fixture_dir: path,
+            scenario: None,
+        }
     }
 
-    async fn load_fixture(&self, filename: &str) -> Result<serde_json::Value> {
+    /// Scripts latency and faults per endpoint instead of answering every call
+    /// with its fixture after 150ms.
+    ///
+    /// Scenario responses go through the live client's body checks and retry policy,
+    /// so a `note` becomes `RateLimited` and an `http:503` is retried.
+    #[must_use]
+    pub fn with_scenario(mut self, scenario: MockScenario) -> Self {
+        self.scenario = Some(scenario);
+        self
+    }
+
+    async fn read_fixture(&self, filename: &str) -> Result<String> {
         let path = self.fixture_dir.join(filename);
         if !path.exists() {
             return Err(ExplorerError::Io(std::io::Error::new(
@@ -33,8 +52,34 @@ impl MockAlphaVantageClient {
                 format!("Fixture not found: {}", path.display()),
             )));
         }
-        let content = fs::read_to_string(path).await.map_err(ExplorerError::Io)?;
-        serde_json::from_str(&content).map_err(ExplorerError::Json)
+        fs::read_to_string(path).await.map_err(ExplorerError::Io)
+    }
+
+    async fn respond(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        filename: &str,
+    ) -> Result<serde_json::Value> {
+        let fixture = self.read_fixture(filename).await;
+        let Some(scenario) = &self.scenario else {
+            // Simulate network latency
+            sleep(Duration::from_millis(150)).await;
+            return serde_json::from_str(&fixture?).map_err(ExplorerError::Json);
+        };
+        let fixture = fixture?;
+
+        execute_with_retry(|| async {
+            let step = scenario.next_step(endpoint, symbol);
+            if step.outcome == Outcome::Timeout {
+                sleep(scenario.timeout()).await;
+            } else {
+                sleep(step.latency).await;
+            }
+            let body = mock_scenario::render(step.outcome, &fixture)?;
+            parse_body(&body, "application/json")
+        })
+        .await
     }
 }
 
@@ -47,15 +92,13 @@ impl ApiClient for MockAlphaVantageClient {
         _params: Option<&std::collections::HashMap<String, String>>,
         _api_key: &ApiKey,
     ) -> Result<serde_json::Value> {
-        // Simulate network latency
-        sleep(Duration::from_millis(150)).await;
-
         let filename = format!(
             "tickers/{}/{}.json",
             ticker.as_str(),
             endpoint.function_name()
         );
-        self.load_fixture(&filename).await
+        self.respond(endpoint, Some(ticker.as_str()), &filename)
+            .await
     }
 
     async fn fetch_market_endpoint(
@@ -63,9 +106,7 @@ impl ApiClient for MockAlphaVantageClient {
         endpoint: EndpointName,
         _api_key: &ApiKey,
     ) -> Result<serde_json::Value> {
-        sleep(Duration::from_millis(150)).await;
-
         let filename = format!("market/{}.json", endpoint.function_name());
-        self.load_fixture(&filename).await
+        self.respond(endpoint, None, &filename).await
     }
 }
@@ -0,0 +1,389 @@
+use alphavantage_core::domain::EndpointName;
+use alphavantage_core::{ExplorerError, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const DEFAULT_LATENCY_MS: u64 = 150;
+const DEFAULT_TIMEOUT_MS: u64 = 1_000;
+
+const NOTE_BODY: &str = "Thank you for using Alpha Vantage! Our standard API call frequency is 5 calls per minute and 25 calls per day. Please visit https://www.alphavantage.co/premium/ if you would like to target a higher API call volume.";
+const INFORMATION_BODY: &str = "We have detected your API key as demo and our standard API rate limit is 25 requests per day. Please subscribe to any of the premium plans at https://www.alphavantage.co/premium/ to instantly remove all daily rate limits.";
+const ERROR_MESSAGE_BODY: &str = "Invalid API call. Please retry or visit the documentation (https://www.alphavantage.co/documentation/) for the function.";
+
+/// Simulated response latency, in milliseconds.
+///
+/// Written as a number (`latency_ms = 150`) or a uniform range
+/// (`latency_ms = { min = 50, max = 400 }`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Latency {
+    Fixed(u64),
+    Uniform { min: u64, max: u64 },
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self::Fixed(DEFAULT_LATENCY_MS)
+    }
+}
+
+impl Latency {
+    fn sample(self, rng: &mut StdRng) -> Duration {
+        let ms = match self {
+            Self::Fixed(ms) => ms,
+            Self::Uniform { min, max } if min < max => rng.gen_range(min..=max),
+            Self::Uniform { min, .. } => min,
+        };
+        Duration::from_millis(ms)
+    }
+}
+
+/// What a single scripted call returns.
+///
+/// Parsed from strings such as `"ok"`, `"http:503"` or `"truncated"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The fixture, unchanged
+    Ok,
+    /// A non-success HTTP status
+    Http(u16),
+    /// A call-volume `Note` body, as sent when the per-minute limit is hit
+    Note,
+    /// An `Information` body, as sent when the daily limit is hit
+    Information,
+    /// An `Error Message` body
+    ErrorMessage,
+    /// A body that is not valid JSON
+    MalformedJson,
+    /// The first half of the fixture
+    Truncated,
+    /// No response within the scenario's `timeout_ms`
+    Timeout,
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Some(status) = s.strip_prefix("http:") {
+            return status
+                .parse::<u16>()
+                .ok()
+                .filter(|code| (100..=599).contains(code))
+                .map(Self::Http)
+                .ok_or_else(|| format!("Invalid HTTP status in '{s}'"));
+        }
+        match s {
+            "ok" => Ok(Self::Ok),
+            "note" => Ok(Self::Note),
+            "information" => Ok(Self::Information),
+            "error_message" => Ok(Self::ErrorMessage),
+            "malformed_json" => Ok(Self::MalformedJson),
+            "truncated" => Ok(Self::Truncated),
+            "timeout" => Ok(Self::Timeout),
+            _ => Err(format!(
+                "Unknown outcome '{s}' (expected ok, http:<status>, note, information, \
+                 error_message, malformed_json, truncated or timeout)"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Outcome {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl<'de> Deserialize<'de> for Outcome {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Behavior for the calls matching `function` and `symbol`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointRule {
+    /// Alpha Vantage function name; any endpoint when omitted
+    pub function: Option<String>,
+    /// Ticker symbol; any symbol (and market endpoints) when omitted
+    pub symbol: Option<String>,
+    /// Overrides the scenario's default latency
+    pub latency_ms: Option<Latency>,
+    /// Outcomes of successive calls, per endpoint and symbol; the last one repeats
+    #[serde(default)]
+    pub responses: Vec<Outcome>,
+}
+
+impl EndpointRule {
+    fn matches(&self, function: &str, symbol: Option<&str>) -> bool {
+        self.function
+            .as_deref()
+            .is_none_or(|f| f.eq_ignore_ascii_case(function))
+            && self
+                .symbol
+                .as_deref()
+                .is_none_or(|s| symbol.is_some_and(|sym| s.eq_ignore_ascii_case(sym)))
+    }
+}
+
+/// The scripted behavior of one call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub latency: Duration,
+    pub outcome: Outcome,
+}
+
+/// A scripted mock run, loaded from a TOML scenario file.
+///
+/// ```toml
+/// seed = 7
+/// latency_ms = { min = 50, max = 250 }
+///
+/// [[endpoint]]
+/// function = "OVERVIEW"
+/// symbol = "AAPL"
+/// responses = ["http:503", "http:503", "ok"]
+/// ```
+///
+/// The first matching `[[endpoint]]` rule applies. Latencies are drawn from an RNG
+/// seeded with `seed`, so a sequential run is reproducible.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockScenario {
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub latency_ms: Latency,
+    /// How long a `timeout` outcome waits before failing
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default, rename = "endpoint")]
+    pub endpoints: Vec<EndpointRule>,
+    #[serde(skip)]
+    state: Mutex<ScenarioState>,
+}
+
+#[derive(Debug, Default)]
+struct ScenarioState {
+    rng: Option<StdRng>,
+    /// Calls seen so far per (rule, function, symbol)
+    calls: HashMap<(usize, String, Option<String>), usize>,
+}
+
+const fn default_timeout_ms() -> u64 {
+    DEFAULT_TIMEOUT_MS
+}
+
+impl Clone for MockScenario {
+    /// A fresh copy of the script: call counters and RNG start over.
+    fn clone(&self) -> Self {
+        Self {
+            seed: self.seed,
+            latency_ms: self.latency_ms,
+            timeout_ms: self.timeout_ms,
+            endpoints: self.endpoints.clone(),
+            state: Mutex::default(),
+        }
+    }
+}
+
+impl MockScenario {
+    /// Loads and validates a scenario file.
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be read or is not a valid scenario.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(ExplorerError::Io)?;
+        content.parse().map_err(|e| {
+            ExplorerError::Validation(format!("Invalid mock scenario {}: {e}", path.display()))
+        })
+    }
+
+    /// Decides latency and outcome of the next call to `endpoint`.
+    #[must_use]
+    pub fn next_step(&self, endpoint: EndpointName, symbol: Option<&str>) -> Step {
+        let function = endpoint.function_name();
+        let rule = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.matches(function, symbol));
+
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let seed = self.seed;
+        let latency = rule
+            .and_then(|(_, r)| r.latency_ms)
+            .unwrap_or(self.latency_ms)
+            .sample(state.rng.get_or_insert_with(|| StdRng::seed_from_u64(seed)));
+
+        let outcome = rule.map_or(Outcome::Ok, |(index, rule)| {
+            let key = (index, function.to_string(), symbol.map(str::to_uppercase));
+            let count = state.calls.entry(key).or_default();
+            let outcome = rule
+                .responses
+                .get(*count)
+                .or_else(|| rule.responses.last())
+                .copied()
+                .unwrap_or(Outcome::Ok);
+            *count += 1;
+            outcome
+        });
+        drop(state);
+
+        Step { latency, outcome }
+    }
+
+    #[must_use]
+    pub const fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+impl FromStr for MockScenario {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let scenario: Self = toml::from_str(s).map_err(|e| e.to_string())?;
+        for rule in &scenario.endpoints {
+            if let Some(function) = &rule.function {
+                if EndpointName::from_str(&function.to_uppercase()).is_err() {
+                    return Err(format!("Unknown function '{function}'"));
+                }
+            }
+        }
+        Ok(scenario)
+    }
+}
+
+/// The raw body an outcome produces from the fixture text, or the error it fails with
+/// before any body arrives.
+///
+/// # Errors
+/// Returns `HttpStatus` for `http:<status>` outcomes and `Network` for timeouts.
+pub fn render(outcome: Outcome, fixture: &str) -> Result<String> {
+    match outcome {
+        Outcome::Ok => Ok(fixture.to_string()),
+        Outcome::Http(status) => Err(ExplorerError::HttpStatus(status)),
+        Outcome::Note => Ok(serde_json::json!({ "Note": NOTE_BODY }).to_string()),
+        Outcome::Information => {
+            Ok(serde_json::json!({ "Information": INFORMATION_BODY }).to_string())
+        }
+        Outcome::ErrorMessage => {
+            Ok(serde_json::json!({ "Error Message": ERROR_MESSAGE_BODY }).to_string())
+        }
+        Outcome::MalformedJson => Ok("{\"Symbol\": \"IBM\", \"Name\": International".to_string()),
+        Outcome::Truncated => {
+            let mut cut = fixture.len() / 2;
+            while !fixture.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            Ok(fixture.get(..cut).unwrap_or_default().to_string())
+        }
+        Outcome::Timeout => Err(ExplorerError::Network("operation timed out".to_string())),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+seed = 1
+latency_ms = { min = 10, max = 20 }
+
+[[endpoint]]
+function = "OVERVIEW"
+symbol = "AAPL"
+latency_ms = 0
+responses = ["http:500", "note", "ok"]
+
+[[endpoint]]
+function = "SPLITS"
+responses = ["truncated"]
+"#;
+
+    #[test]
+    fn test_steps_follow_script() {
+        let scenario: MockScenario = SCENARIO.parse().unwrap();
+        let outcomes: Vec<Outcome> = (0..4)
+            .map(|_| {
+                let step = scenario.next_step(EndpointName::Overview, Some("AAPL"));
+                assert_eq!(step.latency, Duration::ZERO);
+                step.outcome
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            [Outcome::Http(500), Outcome::Note, Outcome::Ok, Outcome::Ok]
+        );
+
+        // Other symbols fall through to the default: ok with the default latency
+        let step = scenario.next_step(EndpointName::Overview, Some("MSFT"));
+        assert_eq!(step.outcome, Outcome::Ok);
+        assert!((10..=20).contains(&step.latency.as_millis()));
+
+        // The last response repeats
+        for symbol in ["MU", "MU", "NVDA"] {
+            let step = scenario.next_step(EndpointName::Splits, Some(symbol));
+            assert_eq!(step.outcome, Outcome::Truncated);
+        }
+    }
+
+    #[test]
+    fn test_latencies_are_reproducible() {
+        let sample = |scenario: &MockScenario| -> Vec<Duration> {
+            (0..5)
+                .map(|_| {
+                    scenario
+                        .next_step(EndpointName::Earnings, Some("NVDA"))
+                        .latency
+                })
+                .collect()
+        };
+        let scenario: MockScenario = SCENARIO.parse().unwrap();
+        assert_eq!(sample(&scenario), sample(&scenario.clone()));
+    }
+
+    #[test]
+    fn test_invalid_scenarios() {
+        let err = "[[endpoint]]\nresponses = [\"http:999\"]"
+            .parse::<MockScenario>()
+            .unwrap_err();
+        assert!(err.contains("Invalid HTTP status"), "{err}");
+        let err = "[[endpoint]]\nresponses = [\"slow\"]"
+            .parse::<MockScenario>()
+            .unwrap_err();
+        assert!(err.contains("Unknown outcome 'slow'"), "{err}");
+        let err = "[[endpoint]]\nfunction = \"NOPE\""
+            .parse::<MockScenario>()
+            .unwrap_err();
+        assert!(err.contains("Unknown function 'NOPE'"), "{err}");
+    }
+
+    #[test]
+    fn test_render_truncates_fixture() {
+        let body = render(Outcome::Truncated, r#"{"Symbol": "IBM"}"#).unwrap();
+        assert_eq!(body, r#"{"Symbol"#);
+        assert!(matches!(
+            render(Outcome::Http(429), ""),
+            Err(ExplorerError::HttpStatus(429))
+        ));
+    }
+}
@@ -1,24 +1,187 @@
+use crate::reset_rule::ResetRule;
+use alphavantage_core::domain::{ApiKey, KeyUsage};
 use alphavantage_core::ExplorerError;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Days, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::time::Duration;
-use tokio::fs;
 use tracing::{info, warn};
 
 const DEFAULT_DAILY_LIMIT: u32 = 25;
+const DEFAULT_MAX_WAIT_MS: u64 = 5 * 60 * 1000;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// Number of days of per-day call counts kept in the state file.
+pub const HISTORY_DAYS: u64 = 30;
 const STATE_FILENAME: &str = ".alphavantage-explorer-tokens.json";
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Length of a sliding rate limit window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowPeriod {
+    Second,
+    Minute,
+    Day,
+}
+
+impl WindowPeriod {
+    #[must_use]
+    pub const fn as_millis(self) -> i64 {
+        match self {
+            Self::Second => 1000,
+            Self::Minute => 60 * 1000,
+            Self::Day => 24 * 60 * 60 * 1000,
+        }
+    }
+}
+
+/// A sliding window allowing at most `limit` calls in any span of one `per`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateWindow {
+    pub limit: u32,
+    pub per: WindowPeriod,
+}
+
+/// The set of limits enforced by a `RateLimiter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimits {
+    /// Daily quota, restored according to `reset`. `None` means no daily cap.
+    pub daily_limit: Option<u32>,
+    /// When the daily quota resets.
+    pub reset: ResetRule,
+    /// Minimum spacing between two consecutive calls.
+    pub min_delay_ms: u64,
+    /// Sliding windows (per-second, per-minute, per-day).
+    pub windows: Vec<RateWindow>,
+    /// Longest `wait()` will sleep for a window to free up before giving up.
+    pub max_wait_ms: u64,
+}
+
+impl RateLimits {
+    /// Limits with only a daily quota and a minimum delay (the free tier shape).
+    #[must_use]
+    pub const fn daily(daily_limit: u32, min_delay_ms: u64) -> Self {
+        Self {
+            daily_limit: Some(daily_limit),
+            reset: ResetRule::UTC_MIDNIGHT,
+            min_delay_ms,
+            windows: Vec::new(),
+            max_wait_ms: DEFAULT_MAX_WAIT_MS,
+        }
+    }
+
+    /// How long calls stay in the call log: the longest sliding window, and at least a
+    /// day when there is a daily quota.
+    fn call_log_retention_ms(&self) -> i64 {
+        let floor = if self.daily_limit.is_some() {
+            DAY_MS
+        } else {
+            0
+        };
+        self.windows
+            .iter()
+            .map(|w| w.per.as_millis())
+            .fold(floor, i64::max)
+    }
+
+    /// Applies the configured daily limit to state written under a different one.
+    fn reconcile(&self, state: &mut TokenState) {
+        if state.daily_limit != self.daily_limit {
+            info!(
+                "Daily limit changed from {:?} to {:?}",
+                state.daily_limit, self.daily_limit
+            );
+            state.daily_limit = self.daily_limit;
+            // Reset tokens if the limit increased, otherwise keep current tokens
+            if let Some(limit) = self.daily_limit {
+                if limit > state.tokens_remaining {
+                    state.tokens_remaining = limit;
+                }
+            }
+        }
+    }
+
+    /// Restores quota according to the configured reset rule.
+    fn roll_over(&self, state: &mut TokenState, now: DateTime<Utc>) {
+        let period_start = self.reset.period_start(now);
+        match self.reset {
+            ResetRule::Fixed { .. } => {
+                if state.last_reset < period_start {
+                    info!("Resetting rate limit tokens ({} passed)", self.reset);
+                    state.tokens_remaining = state.daily_limit.unwrap_or(0);
+                    state.calls_today = 0;
+                    state.last_reset = now;
+                }
+            }
+            ResetRule::Rolling => {
+                // Usage is whatever the call log holds for the last 24 hours
+                let used = state.call_log.iter().filter(|t| **t > period_start).count();
+                let used = u32::try_from(used).unwrap_or(u32::MAX);
+                state.calls_today = used;
+                state.tokens_remaining = state.daily_limit.unwrap_or(0).saturating_sub(used);
+                state.last_reset = period_start;
+            }
+        }
+    }
+
+    /// How long until a call is allowed by `min_delay_ms` and every sliding window.
+    #[allow(clippy::cast_possible_wrap)]
+    #[allow(clippy::cast_sign_loss)]
+    fn required_delay(&self, state: &TokenState, now: DateTime<Utc>) -> Duration {
+        let mut free_at = now;
+
+        if let Some(last_call) = state.last_call {
+            // min_delay_ms is u64, we assume it fits in i64 for comparison with chrono duration
+            let earliest = last_call + chrono::Duration::milliseconds(self.min_delay_ms as i64);
+            free_at = free_at.max(earliest);
+        }
+
+        for window in &self.windows {
+            let period = chrono::Duration::milliseconds(window.per.as_millis());
+            let in_window: Vec<&DateTime<Utc>> = state
+                .call_log
+                .iter()
+                .filter(|t| **t > now - period)
+                .collect();
+            let limit = window.limit as usize;
+            if in_window.len() >= limit {
+                // The window frees once enough of its oldest calls have aged out.
+                if let Some(oldest) = in_window.get(in_window.len() - limit) {
+                    free_at = free_at.max(**oldest + period);
+                }
+            }
+        }
+
+        let millis = free_at.signed_duration_since(now).num_milliseconds();
+        // We know this is non-negative because free_at starts at now
+        Duration::from_millis(millis.max(0) as u64)
+    }
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self::daily(DEFAULT_DAILY_LIMIT, 1000)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct TokenState {
     tokens_remaining: u32,
     last_reset: DateTime<Utc>,
-    daily_limit: u32,
+    daily_limit: Option<u32>,
     #[serde(default)]
     calls_today: u32,
     #[serde(default)]
     last_call: Option<DateTime<Utc>>,
+    /// Timestamps of recent calls, kept for the longest sliding window and at least a
+    /// day under a daily quota. Drives sliding windows and rolling resets.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    call_log: Vec<DateTime<Utc>>,
+    /// Calls per UTC day, for the last `HISTORY_DAYS` days.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    daily_calls: BTreeMap<NaiveDate, u32>,
 }
 
 impl Default for TokenState {
@@ -26,17 +189,115 @@ impl Default for TokenState {
         Self {
             tokens_remaining: DEFAULT_DAILY_LIMIT,
             last_reset: Utc::now(),
-            daily_limit: DEFAULT_DAILY_LIMIT,
+            daily_limit: Some(DEFAULT_DAILY_LIMIT),
             calls_today: 0,
             last_call: None,
+            call_log: Vec::new(),
+            daily_calls: BTreeMap::new(),
+        }
+    }
+}
+
+/// On-disk layout: one `TokenState` per API key fingerprint.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateFile {
+    keys: BTreeMap<String, TokenState>,
+    /// State written before per-key tracking existed; adopted by the first key that
+    /// loads it, since it almost certainly belongs to the key used at the time.
+    #[serde(skip)]
+    legacy: Option<TokenState>,
+}
+
+impl StateFile {
+    /// State for `fingerprint`, starting a fresh one at `daily_limit` for unseen keys.
+    fn entry(&mut self, fingerprint: &str, daily_limit: Option<u32>) -> &mut TokenState {
+        let legacy = &mut self.legacy;
+        self.keys.entry(fingerprint.to_string()).or_insert_with(|| {
+            legacy.take().unwrap_or_else(|| TokenState {
+                tokens_remaining: daily_limit.unwrap_or(0),
+                daily_limit,
+                ..TokenState::default()
+            })
+        })
+    }
+}
+
+/// A key's quota as recorded in the state file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuotaStatus {
+    /// Fingerprint of the API key (never the key itself)
+    pub fingerprint: String,
+    pub daily_limit: Option<u32>,
+    /// Calls left until the next reset; `None` without a daily limit
+    pub remaining: Option<u32>,
+    /// Calls counted since the last reset (the last 24 hours for a rolling reset)
+    pub calls_today: u32,
+    pub last_call: Option<DateTime<Utc>>,
+    pub next_reset: Option<DateTime<Utc>>,
+}
+
+/// Calls made with one key on one UTC day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DailyCalls {
+    pub date: NaiveDate,
+    pub calls: u32,
+}
+
+/// Per-day call counts for one key, oldest day first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyHistory {
+    pub fingerprint: String,
+    pub days: Vec<DailyCalls>,
+}
+
+/// Outcome of a single attempt to take a call slot.
+enum Slot {
+    Acquired(u32),
+    Wait(Duration),
+}
+
+/// Exclusive advisory lock on the `.lock` file next to the state file.
+///
+/// The state file itself is replaced by rename on every save, so the lock lives on a
+/// separate file whose inode never changes. Released when dropped.
+struct StateLock {
+    _file: std::fs::File,
+}
+
+impl StateLock {
+    fn acquire(state_path: &Path) -> std::io::Result<Self> {
+        let mut lock_name = state_path.as_os_str().to_owned();
+        lock_name.push(".lock");
+        let lock_path = PathBuf::from(lock_name);
+        if let Some(parent) = lock_path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
         }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(lock_path)?;
+        file.lock()?;
+        Ok(Self { _file: file })
     }
 }
 
+/// Enforces API quotas, persisting token state to a JSON file.
+///
+/// The state file is shared by every explorer process using the same path. Each
+/// `wait()` takes an advisory lock, re-reads the file, updates it and atomically
+/// replaces it, all on a blocking thread, so concurrent processes never overspend
+/// the shared key.
+///
+/// Counters are tracked separately for each API key, identified by its fingerprint.
 pub struct RateLimiter {
-    state: Mutex<TokenState>,
     state_path: PathBuf,
-    min_delay_ms: u64,
+    limits: RateLimits,
+    /// Calls authorized by this process, per key fingerprint
+    session_calls: Mutex<BTreeMap<String, u32>>,
 }
 
 impl Default for RateLimiter {
@@ -48,57 +309,35 @@ impl Default for RateLimiter {
 impl RateLimiter {
     #[must_use]
     pub fn new(daily_limit: u32, min_delay_ms: u64) -> Self {
-        Self::with_path(Self::resolve_state_path(), daily_limit, min_delay_ms)
+        Self::with_limits(
+            Self::resolve_state_path(),
+            RateLimits::daily(daily_limit, min_delay_ms),
+        )
     }
 
     #[must_use]
-    pub fn with_path(state_path: PathBuf, daily_limit: u32, min_delay_ms: u64) -> Self {
-        // We load synchronously on creation because new() is not async.
-        // This is acceptable for initialization or we should accept a constructor that returns Future.
-        // For CLI tools, sync init is fine.
-        let mut state = Self::load_state_sync(&state_path).unwrap_or_default();
-
-        // Update the daily limit if it has changed
-        if state.daily_limit != daily_limit {
-            info!(
-                "Daily limit changed from {} to {}",
-                state.daily_limit, daily_limit
-            );
-            state.daily_limit = daily_limit;
-            // Reset tokens if the limit increased, otherwise keep current tokens
-            if daily_limit > state.tokens_remaining {
-                state.tokens_remaining = daily_limit;
-            }
-        }
-
-        // Correct calls_today if it seems wrong relative to tokens_remaining (basic sanity check)
-        // If tokens_remaining is X, calls_today should be daily_limit - X (roughly)
-        // But we trust the persisted state mostly.
+    pub const fn with_path(state_path: PathBuf, daily_limit: u32, min_delay_ms: u64) -> Self {
+        Self::with_limits(state_path, RateLimits::daily(daily_limit, min_delay_ms))
+    }
 
-        // Check reset logic requires mutation.
-        let limiter = Self {
-            state: Mutex::new(state),
+    /// Creates a limiter enforcing `limits`, persisting its state at `state_path`.
+    #[must_use]
+    pub const fn with_limits(state_path: PathBuf, limits: RateLimits) -> Self {
+        Self {
             state_path,
-            min_delay_ms,
-        };
-        limiter.check_reset();
-        limiter
+            limits,
+            session_calls: Mutex::new(BTreeMap::new()),
+        }
     }
 
-    fn resolve_state_path() -> PathBuf {
+    /// Resolves the default state file location (home directory, falling back to `./`).
+    #[must_use]
+    pub fn resolve_state_path() -> PathBuf {
         if let Some(base_dirs) = directories::BaseDirs::new() {
             let home = base_dirs.home_dir();
             let path = home.join(STATE_FILENAME);
-            if (path.exists()
-                && !path
-                    .metadata()
-                    .map(|m| m.permissions().readonly())
-                    .unwrap_or(false))
-                || (!path.exists()
-                    && home
-                        .metadata()
-                        .map(|m| !m.permissions().readonly())
-                        .unwrap_or(false))
+            if (path.exists() && !path.metadata().is_ok_and(|m| m.permissions().readonly()))
+                || (!path.exists() && home.metadata().is_ok_and(|m| !m.permissions().readonly()))
             {
                 return path;
             }
@@ -106,127 +345,383 @@ impl RateLimiter {
         PathBuf::from(format!("./{STATE_FILENAME}"))
     }
 
-    fn load_state_sync(path: &Path) -> Option<TokenState> {
-        if path.exists() {
-            if let Ok(file) = std::fs::File::open(path) {
-                let reader = std::io::BufReader::new(file);
-                return serde_json::from_reader(reader).ok();
+    /// Path of the persisted token state.
+    #[must_use]
+    pub fn state_path(&self) -> &Path {
+        &self.state_path
+    }
+
+    fn load_state(path: &Path) -> StateFile {
+        let Ok(bytes) = std::fs::read(path) else {
+            return StateFile::default();
+        };
+        if let Ok(file) = serde_json::from_slice::<StateFile>(&bytes) {
+            return file;
+        }
+        match serde_json::from_slice::<TokenState>(&bytes) {
+            Ok(legacy) => StateFile {
+                keys: BTreeMap::new(),
+                legacy: Some(legacy),
+            },
+            Err(e) => {
+                warn!(
+                    "Ignoring unreadable rate limiter state at {}: {}",
+                    path.display(),
+                    e
+                );
+                StateFile::default()
             }
         }
-        None
     }
 
-    async fn save_state_async(&self, state: &TokenState) {
-        if let Ok(json) = serde_json::to_string(state) {
-            let _ = fs::write(&self.state_path, json).await;
+    /// Writes the state to a temporary file and renames it over the old one, so
+    /// readers never observe a partially written file.
+    fn save_state(path: &Path, state: &StateFile) -> Result<(), ExplorerError> {
+        let json = serde_json::to_vec(state)?;
+        let mut tmp_name = path.as_os_str().to_owned();
+        tmp_name.push(format!(".tmp.{}", std::process::id()));
+        let tmp_path = PathBuf::from(tmp_name);
+
+        {
+            let mut file = std::fs::File::create(&tmp_path)?;
+            file.write_all(&json)?;
+            file.sync_all()?;
         }
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
     }
 
-    fn check_reset(&self) {
-        if let Ok(mut state) = self.state.lock() {
-            let now = Utc::now();
-            let last = state.last_reset;
-            let today = now.date_naive();
-            let last_day = last.date_naive();
+    /// Read-modify-write of the state file under the advisory lock.
+    ///
+    /// The lock is held only for the file I/O, never while sleeping. Blocks on the
+    /// lock, so `wait()` runs it on a blocking thread.
+    fn try_acquire(
+        state_path: &Path,
+        limits: &RateLimits,
+        fingerprint: &str,
+    ) -> Result<Slot, ExplorerError> {
+        let _lock = StateLock::acquire(state_path)?;
+        let mut file = Self::load_state(state_path);
+        let state = file.entry(fingerprint, limits.daily_limit);
+        limits.reconcile(state);
 
-            if today > last_day {
-                info!("Resetting rate limit tokens for new day");
-                state.tokens_remaining = state.daily_limit;
-                state.calls_today = 0;
-                state.last_reset = now;
-            }
+        let now = Utc::now();
+        limits.roll_over(state, now);
+
+        if state.daily_limit.is_some() && state.tokens_remaining == 0 {
+            warn!(
+                key = fingerprint,
+                "Rate limit exceeded (0 tokens remaining)"
+            );
+            return Err(ExplorerError::RateLimited);
+        }
+
+        let delay = limits.required_delay(state, now);
+        if !delay.is_zero() {
+            return Ok(Slot::Wait(delay));
+        }
+
+        if state.daily_limit.is_some() {
+            state.tokens_remaining -= 1;
+        }
+        state.calls_today += 1;
+        state.last_call = Some(now);
+
+        let horizon = now - chrono::Duration::milliseconds(limits.call_log_retention_ms());
+        state.call_log.retain(|t| *t > horizon);
+        state.call_log.push(now);
+
+        let today = now.date_naive();
+        *state.daily_calls.entry(today).or_default() += 1;
+        if let Some(oldest) = today.checked_sub_days(Days::new(HISTORY_DAYS - 1)) {
+            state.daily_calls.retain(|date, _| *date >= oldest);
         }
+        let calls_today = state.calls_today;
+
+        if let Err(e) = Self::save_state(state_path, &file) {
+            warn!(
+                "Failed to save rate limiter state to {}: {}",
+                state_path.display(),
+                e
+            );
+        }
+        Ok(Slot::Acquired(calls_today))
     }
 
-    /// Blocks until a token is available or returns error if limit exhausted.
+    /// Reports usage for every key this limiter authorized calls for.
+    #[must_use]
+    pub fn usage(&self) -> Vec<KeyUsage> {
+        let session = self
+            .session_calls
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default();
+        if session.is_empty() {
+            return Vec::new();
+        }
+
+        let file = {
+            let _lock = StateLock::acquire(&self.state_path).ok();
+            Self::load_state(&self.state_path)
+        };
+        let now = Utc::now();
+
+        session
+            .into_iter()
+            .map(|(fingerprint, calls_this_run)| {
+                let state = self.current(
+                    file.keys.get(&fingerprint).cloned().unwrap_or_default(),
+                    now,
+                );
+                KeyUsage {
+                    fingerprint,
+                    calls_this_run,
+                    calls_today: state.calls_today,
+                    remaining_today: state.daily_limit.map(|_| state.tokens_remaining),
+                }
+            })
+            .collect()
+    }
+
+    /// `state` with the configured limit and any due reset applied.
+    fn current(&self, mut state: TokenState, now: DateTime<Utc>) -> TokenState {
+        self.limits.reconcile(&mut state);
+        self.limits.roll_over(&mut state, now);
+        state
+    }
+
+    /// Current quota of every key in the state file, with due resets applied.
+    ///
+    /// Read-only: the state file is not modified.
     ///
     /// # Errors
-    /// Returns `ExplorerError::RateLimited` if the daily quota is exhausted.
-    /// Returns `ExplorerError::Unknown` if the lock is poisoned.
-    #[allow(clippy::option_if_let_else)]
-    #[allow(clippy::cast_possible_wrap)]
-    #[allow(clippy::cast_sign_loss)]
-    pub async fn wait(&self) -> Result<(), ExplorerError> {
-        // Step 1: Check if we need to sleep (enforce min_delay_ms)
-        let time_to_sleep = {
-            let state = self
-                .state
-                .lock()
-                .map_err(|_| ExplorerError::Unknown("Lock poisoned".into()))?;
-
-            if let Some(last_call) = state.last_call {
-                let now = Utc::now();
-                let elapsed = now.signed_duration_since(last_call).num_milliseconds();
-                // min_delay_ms is u64, we assume it fits in i64 for comparison with chrono duration
-                let min_delay = self.min_delay_ms as i64;
-                if elapsed < min_delay {
-                    // We know this is positive because elapsed < min_delay
-                    Some(Duration::from_millis((min_delay - elapsed) as u64))
-                } else {
-                    None
+    /// Returns `ExplorerError::Io` if the state file cannot be locked.
+    pub fn status(&self) -> Result<Vec<QuotaStatus>, ExplorerError> {
+        let file = {
+            let _lock = StateLock::acquire(&self.state_path)?;
+            Self::load_state(&self.state_path)
+        };
+        let now = Utc::now();
+
+        Ok(file
+            .keys
+            .into_iter()
+            .map(|(fingerprint, state)| {
+                let state = self.current(state, now);
+                QuotaStatus {
+                    fingerprint,
+                    daily_limit: state.daily_limit,
+                    remaining: state.daily_limit.map(|_| state.tokens_remaining),
+                    calls_today: state.calls_today,
+                    last_call: state.last_call,
+                    next_reset: self.limits.reset.next_reset(now, &state.call_log),
                 }
-            } else {
-                None
-            }
+            })
+            .collect())
+    }
+
+    /// Calls left today for `api_key`, or `None` without a daily limit.
+    ///
+    /// Keys that have never been used report the full daily limit. Read-only.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Io` if the state file cannot be locked.
+    pub fn remaining(&self, api_key: &ApiKey) -> Result<Option<u32>, ExplorerError> {
+        let mut file = {
+            let _lock = StateLock::acquire(&self.state_path)?;
+            Self::load_state(&self.state_path)
         };
+        let state = file
+            .entry(&api_key.fingerprint(), self.limits.daily_limit)
+            .clone();
+        let state = self.current(state, Utc::now());
+        Ok(state.daily_limit.map(|_| state.tokens_remaining))
+    }
 
-        if let Some(duration) = time_to_sleep {
-            let ms = duration.as_millis();
-            info!(
-                delay_ms = ms,
-                "Enforcing rate limit delay: waiting {}ms", ms
-            );
-            tokio::time::sleep(duration).await;
+    /// Calls per UTC day for each key over the last `HISTORY_DAYS` days, oldest first.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Io` if the state file cannot be locked.
+    pub fn history(&self) -> Result<Vec<KeyHistory>, ExplorerError> {
+        let file = {
+            let _lock = StateLock::acquire(&self.state_path)?;
+            Self::load_state(&self.state_path)
+        };
+        let today = Utc::now().date_naive();
+
+        Ok(file
+            .keys
+            .into_iter()
+            .map(|(fingerprint, state)| {
+                let days = (0..HISTORY_DAYS)
+                    .rev()
+                    .filter_map(|ago| today.checked_sub_days(Days::new(ago)))
+                    .map(|date| DailyCalls {
+                        date,
+                        calls: state.daily_calls.get(&date).copied().unwrap_or(0),
+                    })
+                    .collect();
+                KeyHistory { fingerprint, days }
+            })
+            .collect())
+    }
+
+    /// Restores the full daily quota, for `fingerprint` or every key if `None`.
+    ///
+    /// Returns the number of keys updated.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Validation` if `fingerprint` has no recorded state, or
+    /// `ExplorerError::Io` if the state file cannot be locked or written.
+    pub fn reset(&self, fingerprint: Option<&str>) -> Result<usize, ExplorerError> {
+        self.update(fingerprint, |state, now| {
+            state.tokens_remaining = state.daily_limit.unwrap_or(0);
+            state.calls_today = 0;
+            state.last_reset = now;
+            // A rolling reset derives usage from the log, so it has to go too
+            state.call_log.clear();
+        })
+    }
+
+    /// Overrides the calls left until the next reset, e.g. to match what the provider
+    /// reports. Applies to `fingerprint`, or every key if `None`.
+    ///
+    /// Returns the number of keys updated.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Validation` if there is no daily limit, `remaining`
+    /// exceeds it, or `fingerprint` has no recorded state.
+    /// Returns `ExplorerError::Io` if the state file cannot be locked or written.
+    pub fn set_remaining(
+        &self,
+        fingerprint: Option<&str>,
+        remaining: u32,
+    ) -> Result<usize, ExplorerError> {
+        let Some(limit) = self.limits.daily_limit else {
+            return Err(ExplorerError::Validation(
+                "No daily limit is configured, so there is no remaining quota to set".to_string(),
+            ));
+        };
+        if remaining > limit {
+            return Err(ExplorerError::Validation(format!(
+                "Remaining calls ({remaining}) cannot exceed the daily limit ({limit})"
+            )));
         }
 
-        // Step 2: Check daily limit and consume token
-        let (should_save, new_state_copy, calls_today, limit) = {
-            let mut state = self
-                .state
-                .lock()
-                .map_err(|_| ExplorerError::Unknown("Lock poisoned".into()))?;
-
-            let now = Utc::now();
-            if now.date_naive() > state.last_reset.date_naive() {
-                info!("Resetting rate limit tokens (midnight UTC passed)");
-                state.tokens_remaining = state.daily_limit;
-                state.calls_today = 0;
-                state.last_reset = now;
+        let reset = self.limits.reset;
+        self.update(fingerprint, |state, now| {
+            state.tokens_remaining = remaining;
+            if reset == ResetRule::Rolling {
+                // Usage is derived from the log: keep exactly `limit - remaining` calls in
+                // the current window, dropping the oldest or padding with `now`.
+                let start = reset.period_start(now);
+                let used = (limit - remaining) as usize;
+                let mut counted: Vec<_> = state
+                    .call_log
+                    .iter()
+                    .copied()
+                    .filter(|t| *t > start)
+                    .collect();
+                state.call_log.retain(|t| *t <= start);
+                counted.drain(..counted.len().saturating_sub(used));
+                counted.resize(used, now);
+                state.call_log.extend(counted);
             }
+        })
+    }
 
-            if state.tokens_remaining > 0 {
-                state.tokens_remaining -= 1;
-                state.calls_today += 1;
-                state.last_call = Some(now);
+    /// Applies `change` to the state of `fingerprint` (or every key) under the lock.
+    fn update<F>(&self, fingerprint: Option<&str>, change: F) -> Result<usize, ExplorerError>
+    where
+        F: Fn(&mut TokenState, DateTime<Utc>),
+    {
+        let _lock = StateLock::acquire(&self.state_path)?;
+        let mut file = Self::load_state(&self.state_path);
+        let now = Utc::now();
 
-                let copy = TokenState {
-                    tokens_remaining: state.tokens_remaining,
-                    last_reset: state.last_reset,
-                    daily_limit: state.daily_limit,
-                    calls_today: state.calls_today,
-                    last_call: state.last_call,
-                };
+        if let Some(fp) = fingerprint {
+            if !file.keys.contains_key(fp) {
+                return Err(ExplorerError::Validation(format!(
+                    "No quota state recorded for key {fp}"
+                )));
+            }
+        }
 
-                (true, copy, state.calls_today, state.daily_limit)
-            } else {
-                warn!("Rate limit exceeded (0 tokens remaining)");
-                return Err(ExplorerError::RateLimited);
+        let mut updated = 0;
+        for (fp, state) in &mut file.keys {
+            if fingerprint.is_some_and(|wanted| wanted != fp) {
+                continue;
             }
-        };
+            *state = self.current(state.clone(), now);
+            change(state, now);
+            updated += 1;
+        }
 
-        // Log machine-readable status message for SDK/CLI consumption
-        info!(
-            calls_today = calls_today,
-            daily_limit = limit,
-            "API call authorized. Calls today: {}/{}",
-            calls_today,
-            limit
-        );
+        Self::save_state(&self.state_path, &file)?;
+        Ok(updated)
+    }
 
-        if should_save {
-            self.save_state_async(&new_state_copy).await;
+    /// Blocks until every configured limit allows another call with `api_key`.
+    ///
+    /// Sleeps for `min_delay_ms` and for sliding windows to free up, as long as the
+    /// required wait stays within `max_wait_ms`.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::RateLimited` if the daily quota is exhausted or a window
+    /// would not free up within `max_wait_ms`.
+    /// Returns `ExplorerError::Io` if the state file cannot be locked.
+    pub async fn wait(&self, api_key: &ApiKey) -> Result<(), ExplorerError> {
+        let fingerprint = api_key.fingerprint();
+        loop {
+            let (state_path, limits) = (self.state_path.clone(), self.limits.clone());
+            let key = fingerprint.clone();
+            let slot =
+                tokio::task::spawn_blocking(move || Self::try_acquire(&state_path, &limits, &key))
+                    .await
+                    .map_err(std::io::Error::other)??;
+            match slot {
+                Slot::Acquired(calls_today) => {
+                    if let Ok(mut session) = self.session_calls.lock() {
+                        *session.entry(fingerprint.clone()).or_default() += 1;
+                    }
+                    // Log machine-readable status message for SDK/CLI consumption
+                    if let Some(limit) = self.limits.daily_limit {
+                        info!(
+                            key = %fingerprint,
+                            calls_today = calls_today,
+                            daily_limit = limit,
+                            "API call authorized. Calls today: {}/{}",
+                            calls_today,
+                            limit
+                        );
+                    } else {
+                        info!(
+                            key = %fingerprint,
+                            calls_today = calls_today,
+                            "API call authorized. Calls today: {}", calls_today
+                        );
+                    }
+                    return Ok(());
+                }
+                Slot::Wait(duration) => {
+                    let ms = duration.as_millis();
+                    if ms > u128::from(self.limits.max_wait_ms) {
+                        warn!(
+                            delay_ms = ms,
+                            "Rate limit window frees in {}ms, longer than max wait of {}ms",
+                            ms,
+                            self.limits.max_wait_ms
+                        );
+                        return Err(ExplorerError::RateLimited);
+                    }
+                    info!(
+                        delay_ms = ms,
+                        "Enforcing rate limit delay: waiting {}ms", ms
+                    );
+                    tokio::time::sleep(duration).await;
+                }
+            }
         }
-        Ok(())
     }
 }
 
@@ -236,6 +731,10 @@ mod tests {
     use super::*;
     use std::env;
 
+    fn key() -> ApiKey {
+        ApiKey::new("test_key")
+    }
+
     fn get_temp_path() -> PathBuf {
         let mut path = env::temp_dir();
         let filename = format!("test_tokens_{}.json", rand::random::<u32>());
@@ -250,12 +749,12 @@ mod tests {
 
         // Consume all 25 tokens
         for i in 0..25 {
-            assert!(limiter.wait().await.is_ok(), "Call {i} failed");
+            assert!(limiter.wait(&key()).await.is_ok(), "Call {i} failed");
         }
 
         // 26th call should fail
         assert!(matches!(
-            limiter.wait().await,
+            limiter.wait(&key()).await,
             Err(ExplorerError::RateLimited)
         ));
 
@@ -271,20 +770,27 @@ mod tests {
             let limiter = RateLimiter::with_path(path.clone(), 25, 0);
             // Consume 5
             for _ in 0..5 {
-                limiter.wait().await.unwrap();
+                limiter.wait(&key()).await.unwrap();
             }
         } // Drop limiter
 
         // Reload
         let limiter = RateLimiter::with_path(path.clone(), 25, 0);
         // Should have 20 left
-        // We can't inspect state directly as fields are private
-        // But we can consume 20 more, then fail on 21st
+        let status = limiter.status().unwrap();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status.first().unwrap().remaining, Some(20));
+        assert_eq!(status.first().unwrap().calls_today, 5);
+
+        // Consume 20 more, then fail on 21st
         for i in 0..20 {
-            assert!(limiter.wait().await.is_ok(), "Reloaded call {i} failed");
+            assert!(
+                limiter.wait(&key()).await.is_ok(),
+                "Reloaded call {i} failed"
+            );
         }
         assert!(matches!(
-            limiter.wait().await,
+            limiter.wait(&key()).await,
             Err(ExplorerError::RateLimited)
         ));
 
@@ -297,8 +803,8 @@ mod tests {
         let limiter = RateLimiter::with_path(path.clone(), 10, 500); // 500ms delay
 
         let start = std::time::Instant::now();
-        limiter.wait().await.unwrap(); // First call: no delay
-        limiter.wait().await.unwrap(); // Second call: should wait ~500ms
+        limiter.wait(&key()).await.unwrap(); // First call: no delay
+        limiter.wait(&key()).await.unwrap(); // Second call: should wait ~500ms
 
         let elapsed = start.elapsed();
         assert!(
@@ -309,4 +815,319 @@ mod tests {
         // Clean up
         let _ = std::fs::remove_file(path);
     }
+
+    #[tokio::test]
+    async fn test_window_sleeps_instead_of_erroring() {
+        let path = get_temp_path();
+        let limits = RateLimits {
+            daily_limit: None,
+            reset: ResetRule::UTC_MIDNIGHT,
+            min_delay_ms: 0,
+            windows: vec![RateWindow {
+                limit: 2,
+                per: WindowPeriod::Second,
+            }],
+            max_wait_ms: 5000,
+        };
+        let limiter = RateLimiter::with_limits(path.clone(), limits);
+
+        let start = std::time::Instant::now();
+        limiter.wait(&key()).await.unwrap();
+        limiter.wait(&key()).await.unwrap();
+        // Third call must wait for the first one to leave the 1s window
+        limiter.wait(&key()).await.unwrap();
+
+        assert!(
+            start.elapsed().as_millis() >= 900,
+            "Third call should wait for the per-second window"
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_window_beyond_max_wait_is_rate_limited() {
+        let path = get_temp_path();
+        let limits = RateLimits {
+            daily_limit: None,
+            reset: ResetRule::UTC_MIDNIGHT,
+            min_delay_ms: 0,
+            windows: vec![RateWindow {
+                limit: 1,
+                per: WindowPeriod::Minute,
+            }],
+            max_wait_ms: 100,
+        };
+        let limiter = RateLimiter::with_limits(path.clone(), limits);
+
+        limiter.wait(&key()).await.unwrap();
+        assert!(matches!(
+            limiter.wait(&key()).await,
+            Err(ExplorerError::RateLimited)
+        ));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_no_daily_cap() {
+        let path = get_temp_path();
+        let limits = RateLimits {
+            daily_limit: None,
+            reset: ResetRule::UTC_MIDNIGHT,
+            min_delay_ms: 0,
+            windows: vec![RateWindow {
+                limit: 100,
+                per: WindowPeriod::Minute,
+            }],
+            max_wait_ms: 100,
+        };
+        let limiter = RateLimiter::with_limits(path.clone(), limits);
+
+        for i in 0..40 {
+            assert!(limiter.wait(&key()).await.is_ok(), "Call {i} failed");
+        }
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_keys_have_independent_counters() {
+        let path = get_temp_path();
+        let limiter = RateLimiter::with_path(path.clone(), 2, 0);
+        let first = ApiKey::new("first_key");
+        let second = ApiKey::new("second_key");
+
+        limiter.wait(&first).await.unwrap();
+        limiter.wait(&first).await.unwrap();
+        assert!(matches!(
+            limiter.wait(&first).await,
+            Err(ExplorerError::RateLimited)
+        ));
+
+        // Switching keys must not inherit the exhausted counters
+        assert!(limiter.wait(&second).await.is_ok());
+
+        let usage = limiter.usage();
+        assert_eq!(usage.len(), 2);
+        let first_usage = usage
+            .iter()
+            .find(|u| u.fingerprint == first.fingerprint())
+            .unwrap();
+        assert_eq!(first_usage.calls_this_run, 2);
+        assert_eq!(first_usage.remaining_today, Some(0));
+
+        // The state file is keyed by fingerprint and never contains the key itself
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains(&first.fingerprint()));
+        assert!(!content.contains("first_key"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_state_is_adopted_by_first_key() {
+        let path = get_temp_path();
+        let legacy = TokenState {
+            tokens_remaining: 1,
+            calls_today: 24,
+            ..TokenState::default()
+        };
+        std::fs::write(&path, serde_json::to_string(&legacy).unwrap()).unwrap();
+
+        let limiter = RateLimiter::with_path(path.clone(), 25, 0);
+        limiter.wait(&key()).await.unwrap();
+        assert!(matches!(
+            limiter.wait(&key()).await,
+            Err(ExplorerError::RateLimited)
+        ));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn write_state(path: &Path, state: TokenState) {
+        let mut file = StateFile::default();
+        file.keys.insert(key().fingerprint(), state);
+        std::fs::write(path, serde_json::to_string(&file).unwrap()).unwrap();
+    }
+
+    fn limits_with_reset(reset: ResetRule) -> RateLimits {
+        RateLimits {
+            reset,
+            ..RateLimits::daily(25, 0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixed_reset_in_provider_timezone() {
+        let path = get_temp_path();
+        let reset = ResetRule::fixed("America/New_York", "00:00").unwrap();
+        let exhausted = TokenState {
+            tokens_remaining: 0,
+            calls_today: 25,
+            last_reset: reset.period_start(Utc::now()) - chrono::Duration::minutes(1),
+            ..TokenState::default()
+        };
+
+        // Exhausted before the latest New York midnight: quota is back
+        write_state(&path, exhausted.clone());
+        let limiter = RateLimiter::with_limits(path.clone(), limits_with_reset(reset));
+        assert!(limiter.wait(&key()).await.is_ok());
+
+        // Exhausted after it: still exhausted
+        write_state(
+            &path,
+            TokenState {
+                last_reset: reset.period_start(Utc::now()) + chrono::Duration::seconds(1),
+                ..exhausted
+            },
+        );
+        assert!(matches!(
+            limiter.wait(&key()).await,
+            Err(ExplorerError::RateLimited)
+        ));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_rolling_reset_counts_last_24_hours() {
+        let path = get_temp_path();
+        let now = Utc::now();
+        // 24 calls within the last day, one older call that no longer counts
+        let mut call_log = vec![now - chrono::Duration::hours(25)];
+        call_log.extend((1..=24).map(|h| now - chrono::Duration::minutes(h * 50)));
+        write_state(
+            &path,
+            TokenState {
+                tokens_remaining: 0,
+                calls_today: 25,
+                call_log,
+                ..TokenState::default()
+            },
+        );
+
+        let limiter = RateLimiter::with_limits(path.clone(), limits_with_reset(ResetRule::Rolling));
+        assert!(limiter.wait(&key()).await.is_ok());
+        assert!(matches!(
+            limiter.wait(&key()).await,
+            Err(ExplorerError::RateLimited)
+        ));
+
+        // Every call is logged with its timestamp
+        let file: StateFile =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            file.keys.get(&key().fingerprint()).unwrap().call_log.len(),
+            25
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_reset_and_set_remaining() {
+        let path = get_temp_path();
+        let limiter = RateLimiter::with_path(path.clone(), 25, 0);
+        let other = ApiKey::new("other_key");
+        for _ in 0..3 {
+            limiter.wait(&key()).await.unwrap();
+        }
+        limiter.wait(&other).await.unwrap();
+
+        // The provider says only 2 calls are left on the first key
+        assert_eq!(
+            limiter
+                .set_remaining(Some(&key().fingerprint()), 2)
+                .unwrap(),
+            1
+        );
+        let remaining = |fingerprint: String| {
+            limiter
+                .status()
+                .unwrap()
+                .into_iter()
+                .find(|s| s.fingerprint == fingerprint)
+                .unwrap()
+                .remaining
+        };
+        assert_eq!(remaining(key().fingerprint()), Some(2));
+        assert_eq!(remaining(other.fingerprint()), Some(24));
+
+        assert!(limiter.set_remaining(None, 26).is_err());
+        assert!(limiter.reset(Some("0000000000000000")).is_err());
+
+        assert_eq!(limiter.reset(None).unwrap(), 2);
+        assert_eq!(remaining(key().fingerprint()), Some(25));
+        assert_eq!(remaining(other.fingerprint()), Some(25));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_set_remaining_with_rolling_reset() {
+        let path = get_temp_path();
+        let limiter = RateLimiter::with_limits(path.clone(), limits_with_reset(ResetRule::Rolling));
+        limiter.wait(&key()).await.unwrap();
+
+        limiter.set_remaining(None, 20).unwrap();
+        let status = limiter.status().unwrap();
+        assert_eq!(status.first().unwrap().remaining, Some(20));
+        assert_eq!(status.first().unwrap().calls_today, 5);
+
+        limiter.set_remaining(None, 25).unwrap();
+        assert_eq!(limiter.remaining(&key()).unwrap(), Some(25));
+        assert_eq!(
+            limiter.remaining(&ApiKey::new("unused_key")).unwrap(),
+            Some(25)
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_history_counts_calls_per_day() {
+        let path = get_temp_path();
+        let mut daily_calls = BTreeMap::new();
+        let today = Utc::now().date_naive();
+        let two_days_ago = today.checked_sub_days(Days::new(2)).unwrap();
+        daily_calls.insert(two_days_ago, 7);
+        // Older than the history window
+        daily_calls.insert(today.checked_sub_days(Days::new(40)).unwrap(), 9);
+        write_state(
+            &path,
+            TokenState {
+                daily_calls,
+                ..TokenState::default()
+            },
+        );
+
+        let limiter = RateLimiter::with_path(path.clone(), 25, 0);
+        limiter.wait(&key()).await.unwrap();
+
+        let history = limiter.history().unwrap();
+        let days = &history.first().unwrap().days;
+        assert_eq!(days.len(), 30);
+        assert_eq!(days.last().unwrap().date, today);
+        assert_eq!(days.last().unwrap().calls, 1);
+        assert_eq!(
+            days.iter().find(|d| d.date == two_days_ago).unwrap().calls,
+            7
+        );
+        assert_eq!(days.iter().map(|d| d.calls).sum::<u32>(), 8);
+
+        // Days beyond the window are pruned from the state file on the next call
+        let file: StateFile =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            file.keys
+                .get(&key().fingerprint())
+                .unwrap()
+                .daily_calls
+                .len(),
+            2
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
 }
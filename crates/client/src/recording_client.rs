@@ -0,0 +1,169 @@
+use crate::http_client::{AlphaVantageClient, HttpExchange};
+use crate::redact::scrub_json;
+use alphavantage_core::domain::{ApiKey, EndpointName, KeyUsage, TickerSymbol};
+use alphavantage_core::ports::ApiClient;
+use alphavantage_core::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// `ApiClient` decorator that saves every live response in the mock fixture layout.
+///
+/// Bodies go to `tickers/<SYM>/<FUNCTION>.json` or `market/<FUNCTION>.json`, so the
+/// directory can be used as `fixtures/` directly. A `<FUNCTION>.meta.json` next to each
+/// body holds the request parameters, status and response headers. The API key is
+/// never written: it is not among the recorded parameters and is redacted from bodies.
+pub struct RecordingClient {
+    inner: AlphaVantageClient,
+    dir: PathBuf,
+}
+
+impl RecordingClient {
+    #[must_use]
+    pub fn new(inner: AlphaVantageClient, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+        }
+    }
+
+    fn fixture_path(&self, endpoint: EndpointName, symbol: Option<&str>) -> PathBuf {
+        let dir = symbol.map_or_else(
+            || self.dir.join("market"),
+            |s| self.dir.join("tickers").join(s),
+        );
+        dir.join(format!("{}.json", endpoint.function_name()))
+    }
+
+    /// Writes the body and its metadata, returning the body with the key redacted.
+    fn record(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        exchange: HttpExchange,
+    ) -> serde_json::Value {
+        let body = scrub_json(exchange.body, exchange.api_key.secret());
+        let path = self.fixture_path(endpoint, symbol);
+        let meta = serde_json::json!({
+            "params": exchange.params,
+            "status": exchange.status,
+            "headers": exchange.headers,
+            "recorded_at": Utc::now(),
+        });
+        match write_fixture(&path, &body, &meta) {
+            Ok(()) => info!("Recorded {}", path.display()),
+            Err(e) => warn!("Failed to record {}: {}", path.display(), e),
+        }
+        body
+    }
+}
+
+fn write_fixture(
+    path: &Path,
+    body: &serde_json::Value,
+    meta: &serde_json::Value,
+) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(body)?)?;
+    std::fs::write(
+        path.with_extension("meta.json"),
+        serde_json::to_string_pretty(meta)?,
+    )
+}
+
+#[async_trait]
+impl ApiClient for RecordingClient {
+    async fn fetch_ticker_endpoint(
+        &self,
+        endpoint: EndpointName,
+        ticker: &TickerSymbol,
+        params: Option<&HashMap<String, String>>,
+        api_key: &ApiKey,
+    ) -> Result<serde_json::Value> {
+        let exchange = self
+            .inner
+            .execute_request(endpoint, Some(ticker.as_str()), params, api_key)
+            .await?;
+        Ok(self.record(endpoint, Some(ticker.as_str()), exchange))
+    }
+
+    async fn fetch_market_endpoint(
+        &self,
+        endpoint: EndpointName,
+        api_key: &ApiKey,
+    ) -> Result<serde_json::Value> {
+        let exchange = self
+            .inner
+            .execute_request(endpoint, None, None, api_key)
+            .await?;
+        Ok(self.record(endpoint, None, exchange))
+    }
+
+    fn key_usage(&self) -> Vec<KeyUsage> {
+        self.inner.key_usage()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_records_fixture_without_api_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("function", "OVERVIEW"))
+            .and(query_param("symbol", "IBM"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-request-id", "abc123")
+                    .set_body_json(serde_json::json!({
+                        "Symbol": "IBM",
+                        "Note": "request ?apikey=SECRETKEY123 received"
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let limiter = crate::RateLimiter::with_path(dir.path().join("tokens.json"), 25, 0);
+        let client = RecordingClient::new(
+            AlphaVantageClient::with_base_url(server.uri(), 25, 0)
+                .unwrap()
+                .with_rate_limiter(limiter),
+            dir.path().join("fixtures"),
+        );
+
+        let json = client
+            .fetch_ticker_endpoint(
+                EndpointName::Overview,
+                &TickerSymbol::new("IBM").unwrap(),
+                None,
+                &ApiKey::new("SECRETKEY123"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(json.get("Symbol").unwrap(), "IBM");
+
+        let fixture = dir.path().join("fixtures/tickers/IBM/OVERVIEW.json");
+        let body = std::fs::read_to_string(&fixture).unwrap();
+        let meta = std::fs::read_to_string(fixture.with_extension("meta.json")).unwrap();
+        assert!(!body.contains("SECRETKEY123"));
+        assert!(!meta.contains("SECRETKEY123"));
+        assert!(body.contains("apikey=REDACTED"));
+
+        let meta: serde_json::Value = serde_json::from_str(&meta).unwrap();
+        assert_eq!(meta.pointer("/params/function").unwrap(), "OVERVIEW");
+        assert_eq!(meta.pointer("/params/symbol").unwrap(), "IBM");
+        assert!(meta.pointer("/params/apikey").is_none());
+        assert_eq!(meta.pointer("/status").unwrap(), 200);
+        assert_eq!(meta.pointer("/headers/x-request-id").unwrap(), "abc123");
+    }
+}
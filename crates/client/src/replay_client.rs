@@ -0,0 +1,263 @@
+use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol};
+use alphavantage_core::ports::ApiClient;
+use alphavantage_core::{ExplorerError, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Serves responses saved by an earlier run, making no API calls.
+///
+/// The directory may be a run's output directory or its `raw/` subdirectory. Bulk
+/// files (`tickers/<SYM>/<FUNCTION>.json`, `market/<FUNCTION>.json`) are used first;
+/// otherwise the newest granular file (`<endpoint>_<SYM>_<timestamp>.json` or `.csv`)
+/// is. A `quarter` parameter must match: granular files are then looked up as
+/// `<endpoint>_<SYM>_<YYYYQn>_<timestamp>.json`, and a bulk file only counts if its
+/// `quarter` field is the requested one. Other request parameters are ignored.
+#[derive(Debug, Clone)]
+pub struct ReplayClient {
+    raw_dir: PathBuf,
+}
+
+impl ReplayClient {
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let raw = dir.join("raw");
+        let raw_dir = if raw.is_dir() { raw } else { dir };
+        Self { raw_dir }
+    }
+
+    #[must_use]
+    pub fn raw_dir(&self) -> &Path {
+        &self.raw_dir
+    }
+
+    /// The newest granular file for `endpoint`, `ticker` and `quarter`, if any.
+    ///
+    /// Timestamps are `%Y%m%d_%H%M%S`, so the lexicographically largest name is the newest.
+    fn latest_granular(
+        &self,
+        endpoint: EndpointName,
+        ticker: &TickerSymbol,
+        quarter: Option<&str>,
+    ) -> Option<PathBuf> {
+        let mut prefix = format!(
+            "{}_{}_",
+            endpoint.function_name().to_lowercase().replace('_', "-"),
+            ticker.as_str()
+        );
+        if let Some(quarter) = quarter {
+            prefix.push_str(quarter);
+            prefix.push('_');
+        }
+        std::fs::read_dir(&self.raw_dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(&prefix))
+                    && path.extension().is_some_and(|e| e == "json" || e == "csv")
+            })
+            .max()
+    }
+
+    async fn load(path: &Path) -> Result<serde_json::Value> {
+        let content = fs::read_to_string(path).await.map_err(ExplorerError::Io)?;
+        if path.extension().is_some_and(|e| e == "csv") {
+            // Same shape the live client uses for CSV endpoints
+            return Ok(serde_json::json!({ "csv_content": content }));
+        }
+        serde_json::from_str(&content).map_err(ExplorerError::Json)
+    }
+
+    fn not_found(&self, what: &str) -> ExplorerError {
+        ExplorerError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "No recorded response for {what} in {}",
+                self.raw_dir.display()
+            ),
+        ))
+    }
+}
+
+#[async_trait]
+impl ApiClient for ReplayClient {
+    async fn fetch_ticker_endpoint(
+        &self,
+        endpoint: EndpointName,
+        ticker: &TickerSymbol,
+        params: Option<&HashMap<String, String>>,
+        _api_key: &ApiKey,
+    ) -> Result<serde_json::Value> {
+        let quarter = params.and_then(|p| p.get("quarter")).map(String::as_str);
+        let bulk = self
+            .raw_dir
+            .join("tickers")
+            .join(ticker.as_str())
+            .join(format!("{}.json", endpoint.function_name()));
+        if bulk.is_file() {
+            let json = Self::load(&bulk).await?;
+            if quarter.is_none_or(|q| json.get("quarter").and_then(|v| v.as_str()) == Some(q)) {
+                return Ok(json);
+            }
+        }
+        if let Some(path) = self.latest_granular(endpoint, ticker, quarter) {
+            return Self::load(&path).await;
+        }
+        let quarter = quarter.map(|q| format!(" {q}")).unwrap_or_default();
+        Err(self.not_found(&format!("{endpoint} {ticker}{quarter}")))
+    }
+
+    async fn fetch_market_endpoint(
+        &self,
+        endpoint: EndpointName,
+        _api_key: &ApiKey,
+    ) -> Result<serde_json::Value> {
+        let path = self
+            .raw_dir
+            .join("market")
+            .join(format!("{}.json", endpoint.function_name()));
+        if !path.is_file() {
+            return Err(self.not_found(endpoint.function_name()));
+        }
+        Self::load(&path).await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replays_bulk_layout_from_out_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path().join("raw/tickers/IBM/OVERVIEW.json"),
+            r#"{"Symbol": "IBM"}"#,
+        );
+        write(
+            &dir.path().join("raw/market/MARKET_STATUS.json"),
+            r#"{"markets": []}"#,
+        );
+
+        let client = ReplayClient::new(dir.path());
+        assert_eq!(client.raw_dir(), dir.path().join("raw"));
+        let key = ApiKey::new("unused");
+        let ibm = TickerSymbol::new("IBM").unwrap();
+
+        let json = client
+            .fetch_ticker_endpoint(EndpointName::Overview, &ibm, None, &key)
+            .await
+            .unwrap();
+        assert_eq!(json.get("Symbol").unwrap(), "IBM");
+        let json = client
+            .fetch_market_endpoint(EndpointName::MarketStatus, &key)
+            .await
+            .unwrap();
+        assert!(json.get("markets").is_some());
+
+        let err = client
+            .fetch_ticker_endpoint(EndpointName::Splits, &ibm, None, &key)
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No recorded response for SPLITS IBM"));
+    }
+
+    #[tokio::test]
+    async fn test_replays_newest_granular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path()
+                .join("income-statement_NVDA_20260101_090000.json"),
+            r#"{"version": "old"}"#,
+        );
+        write(
+            &dir.path()
+                .join("income-statement_NVDA_20260110_230045.json"),
+            r#"{"version": "new"}"#,
+        );
+        write(
+            &dir.path()
+                .join("earnings-calendar_NVDA_20260110_230045.csv"),
+            "symbol,reportDate\nNVDA,2026-02-25\n",
+        );
+
+        let client = ReplayClient::new(dir.path());
+        let key = ApiKey::new("unused");
+        let nvda = TickerSymbol::new("NVDA").unwrap();
+
+        let json = client
+            .fetch_ticker_endpoint(EndpointName::IncomeStatement, &nvda, None, &key)
+            .await
+            .unwrap();
+        assert_eq!(json.get("version").unwrap(), "new");
+
+        let json = client
+            .fetch_ticker_endpoint(EndpointName::EarningsCalendar, &nvda, None, &key)
+            .await
+            .unwrap();
+        assert!(json
+            .get("csv_content")
+            .and_then(|v| v.as_str())
+            .unwrap()
+            .starts_with("symbol,reportDate"));
+    }
+
+    #[tokio::test]
+    async fn test_replays_requested_quarter() {
+        let dir = tempfile::tempdir().unwrap();
+        for (quarter, time) in [("2023Q4", "20260101_090000"), ("2024Q1", "20260101_090001")] {
+            write(
+                &dir.path().join(format!(
+                    "earnings-call-transcript_NVDA_{quarter}_{time}.json"
+                )),
+                &format!(r#"{{"quarter": "{quarter}"}}"#),
+            );
+        }
+        write(
+            &dir.path()
+                .join("tickers/NVDA/EARNINGS_CALL_TRANSCRIPT.json"),
+            r#"{"quarter": "2024Q2"}"#,
+        );
+
+        let client = ReplayClient::new(dir.path());
+        let key = ApiKey::new("unused");
+        let nvda = TickerSymbol::new("NVDA").unwrap();
+        let fetch = |quarter: &str| {
+            let params = HashMap::from([("quarter".to_string(), quarter.to_string())]);
+            let client = client.clone();
+            let (key, nvda) = (key.clone(), nvda.clone());
+            async move {
+                client
+                    .fetch_ticker_endpoint(
+                        EndpointName::EarningsCallTranscript,
+                        &nvda,
+                        Some(&params),
+                        &key,
+                    )
+                    .await
+            }
+        };
+
+        for quarter in ["2023Q4", "2024Q1", "2024Q2"] {
+            let json = fetch(quarter).await.unwrap();
+            assert_eq!(json.get("quarter").unwrap(), quarter);
+        }
+        let err = fetch("2023Q3").await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No recorded response for EARNINGS_CALL_TRANSCRIPT NVDA 2023Q3"));
+    }
+}
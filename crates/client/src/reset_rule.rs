@@ -0,0 +1,258 @@
+use alphavantage_core::ExplorerError;
+use chrono::{DateTime, Days, LocalResult, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// When a daily quota is restored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawResetRule")]
+pub enum ResetRule {
+    /// The whole quota comes back once a day at `at` local time in `timezone`.
+    Fixed { timezone: Tz, at: NaiveTime },
+    /// Each call counts against the quota for 24 hours after it was made.
+    Rolling,
+}
+
+impl ResetRule {
+    /// Reset at midnight UTC, matching the limiter's historical behavior.
+    pub const UTC_MIDNIGHT: Self = Self::Fixed {
+        timezone: Tz::UTC,
+        at: NaiveTime::MIN,
+    };
+
+    /// A fixed daily reset, e.g. `fixed("America/New_York", "00:00")`.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Validation` if the timezone is not an IANA name or the
+    /// time is not `HH:MM` / `HH:MM:SS`.
+    pub fn fixed(timezone: &str, at: &str) -> Result<Self, ExplorerError> {
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(|_| ExplorerError::Validation(format!("Unknown timezone '{timezone}'")))?;
+        let at = NaiveTime::parse_from_str(at, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(at, "%H:%M:%S"))
+            .map_err(|_| {
+                ExplorerError::Validation(format!("Invalid reset time '{at}', expected HH:MM"))
+            })?;
+        Ok(Self::Fixed { timezone, at })
+    }
+
+    /// Start of the quota period containing `now`.
+    ///
+    /// For a fixed rule this is the latest reset at or before `now`; for a rolling
+    /// rule it is 24 hours ago.
+    #[must_use]
+    pub fn period_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match *self {
+            Self::Fixed { timezone, at } => {
+                let today = now.with_timezone(&timezone).date_naive();
+                let candidate = reset_instant(timezone, today, at);
+                if candidate <= now {
+                    candidate
+                } else {
+                    let yesterday = today.checked_sub_days(Days::new(1)).unwrap_or(today);
+                    reset_instant(timezone, yesterday, at)
+                }
+            }
+            Self::Rolling => now - chrono::Duration::milliseconds(DAY_MS),
+        }
+    }
+
+    /// When quota next comes back after `now`.
+    ///
+    /// A rolling rule frees a call 24 hours after the oldest call still counted in
+    /// `call_log`; with no such call there is nothing to wait for and `None` is returned.
+    #[must_use]
+    pub fn next_reset(
+        &self,
+        now: DateTime<Utc>,
+        call_log: &[DateTime<Utc>],
+    ) -> Option<DateTime<Utc>> {
+        match *self {
+            Self::Fixed { timezone, at } => {
+                let today = now.with_timezone(&timezone).date_naive();
+                let candidate = reset_instant(timezone, today, at);
+                if candidate > now {
+                    return Some(candidate);
+                }
+                let tomorrow = today.checked_add_days(Days::new(1))?;
+                Some(reset_instant(timezone, tomorrow, at))
+            }
+            Self::Rolling => {
+                let start = self.period_start(now);
+                call_log
+                    .iter()
+                    .filter(|t| **t > start)
+                    .min()
+                    .map(|oldest| *oldest + chrono::Duration::milliseconds(DAY_MS))
+            }
+        }
+    }
+}
+
+impl Default for ResetRule {
+    fn default() -> Self {
+        Self::UTC_MIDNIGHT
+    }
+}
+
+impl std::fmt::Display for ResetRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fixed { timezone, at } => {
+                write!(f, "daily at {} {}", at.format("%H:%M"), timezone)
+            }
+            Self::Rolling => write!(f, "rolling 24h"),
+        }
+    }
+}
+
+/// `at` on `date` in `timezone`, resolved through DST transitions.
+///
+/// When clocks go back the time occurs twice and the earlier instant is used. When
+/// clocks jump forward over it, the first valid local time after the gap is used.
+fn reset_instant(timezone: Tz, date: NaiveDate, at: NaiveTime) -> DateTime<Utc> {
+    let mut local = date.and_time(at);
+    // DST gaps are at most a couple of hours in practice
+    for _ in 0..=(3 * 60) {
+        match timezone.from_local_datetime(&local) {
+            LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => return t.to_utc(),
+            LocalResult::None => local += chrono::Duration::minutes(1),
+        }
+    }
+    local.and_utc()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ResetMode {
+    Fixed,
+    Rolling,
+}
+
+/// Config file shape: `{ timezone = "America/New_York", at = "00:00" }` or `{ mode = "rolling" }`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawResetRule {
+    mode: Option<ResetMode>,
+    timezone: Option<String>,
+    at: Option<String>,
+}
+
+impl TryFrom<RawResetRule> for ResetRule {
+    type Error = ExplorerError;
+
+    fn try_from(raw: RawResetRule) -> Result<Self, Self::Error> {
+        match raw.mode.unwrap_or(ResetMode::Fixed) {
+            ResetMode::Fixed => Self::fixed(
+                raw.timezone.as_deref().unwrap_or("UTC"),
+                raw.at.as_deref().unwrap_or("00:00"),
+            ),
+            ResetMode::Rolling if raw.timezone.is_some() || raw.at.is_some() => {
+                Err(ExplorerError::Validation(
+                    "timezone and at do not apply to a rolling reset".to_string(),
+                ))
+            }
+            ResetMode::Rolling => Ok(Self::Rolling),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn new_york(at: &str) -> ResetRule {
+        ResetRule::fixed("America/New_York", at).unwrap()
+    }
+
+    #[test]
+    fn test_utc_midnight_matches_calendar_day() {
+        let rule = ResetRule::default();
+        let now = utc("2024-06-15T13:45:00Z");
+        assert_eq!(rule.period_start(now), utc("2024-06-15T00:00:00Z"));
+        assert_eq!(rule.next_reset(now, &[]), Some(utc("2024-06-16T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_fixed_reset_in_timezone() {
+        let rule = new_york("00:00");
+        // 23:30 EST on Jan 14 is still in the period that started at midnight EST
+        let now = utc("2024-01-15T04:30:00Z");
+        assert_eq!(rule.period_start(now), utc("2024-01-14T05:00:00Z"));
+        assert_eq!(rule.next_reset(now, &[]), Some(utc("2024-01-15T05:00:00Z")));
+    }
+
+    #[test]
+    fn test_midnight_offset_follows_dst() {
+        let rule = new_york("00:00");
+        // Clocks spring forward on 2024-03-10: midnight is EST that day, EDT the next
+        assert_eq!(
+            rule.period_start(utc("2024-03-10T12:00:00Z")),
+            utc("2024-03-10T05:00:00Z")
+        );
+        assert_eq!(
+            rule.next_reset(utc("2024-03-10T12:00:00Z"), &[]),
+            Some(utc("2024-03-11T04:00:00Z"))
+        );
+        // And fall back on 2024-11-03
+        assert_eq!(
+            rule.next_reset(utc("2024-11-03T12:00:00Z"), &[]),
+            Some(utc("2024-11-04T05:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_reset_inside_spring_forward_gap() {
+        // 02:30 does not exist on 2024-03-10; the reset happens at 03:00 EDT
+        let rule = new_york("02:30");
+        assert_eq!(
+            rule.next_reset(utc("2024-03-10T06:00:00Z"), &[]),
+            Some(utc("2024-03-10T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_reset_inside_fall_back_overlap() {
+        // 01:30 happens twice on 2024-11-03; the first (EDT) occurrence counts
+        let rule = new_york("01:30");
+        assert_eq!(
+            rule.next_reset(utc("2024-11-03T04:00:00Z"), &[]),
+            Some(utc("2024-11-03T05:30:00Z"))
+        );
+        // Once past it, the second occurrence does not trigger another reset
+        assert_eq!(
+            rule.period_start(utc("2024-11-03T06:45:00Z")),
+            utc("2024-11-03T05:30:00Z")
+        );
+    }
+
+    #[test]
+    fn test_rolling_next_reset_from_oldest_call() {
+        let now = utc("2024-06-15T12:00:00Z");
+        let log = [
+            utc("2024-06-14T10:00:00Z"), // older than 24h, no longer counted
+            utc("2024-06-14T18:00:00Z"),
+            utc("2024-06-15T09:00:00Z"),
+        ];
+        assert_eq!(
+            ResetRule::Rolling.next_reset(now, &log),
+            Some(utc("2024-06-15T18:00:00Z"))
+        );
+        assert_eq!(ResetRule::Rolling.next_reset(now, &[]), None);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(ResetRule::fixed("Mars/Olympus_Mons", "00:00").is_err());
+        assert!(ResetRule::fixed("UTC", "25:00").is_err());
+        assert!(ResetRule::fixed("UTC", "07:15").is_ok());
+    }
+}
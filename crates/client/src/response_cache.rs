@@ -0,0 +1,679 @@
+use alphavantage_core::domain::{ApiKey, EndpointName, KeyUsage, TickerSymbol};
+use alphavantage_core::ports::ApiClient;
+use alphavantage_core::{ExplorerError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+const MARKET_DIR: &str = "_market";
+
+/// Parses a TTL such as `30s`, `15m`, `12h` or `30d`.
+///
+/// # Errors
+/// Returns `ExplorerError::Validation` if the number or unit is missing or invalid.
+pub fn parse_ttl(ttl: &str) -> Result<Duration> {
+    let ttl = ttl.trim();
+    let invalid = || {
+        ExplorerError::Validation(format!(
+            "Invalid TTL '{ttl}', expected a number followed by s, m, h or d (e.g. 30d)"
+        ))
+    };
+    let unit_at = ttl
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (number, unit) = ttl.split_at(unit_at);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let secs = match unit {
+        "s" => number,
+        "m" => number.saturating_mul(60),
+        "h" => number.saturating_mul(60 * 60),
+        "d" => number.saturating_mul(24 * 60 * 60),
+        _ => return Err(invalid()),
+    };
+    Ok(Duration::from_secs(secs))
+}
+
+/// How long responses of each endpoint stay fresh. Endpoints without a TTL are
+/// never cached.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CachePolicy {
+    ttls: HashMap<EndpointName, Duration>,
+}
+
+impl CachePolicy {
+    /// Builds a policy from `(function name, ttl)` pairs, e.g. `("OVERVIEW", "1d")`.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Validation` for an unknown endpoint or invalid TTL.
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self> {
+        let mut ttls = HashMap::new();
+        for (function, ttl) in pairs {
+            let endpoint = EndpointName::from_str(function).map_err(|_| {
+                ExplorerError::Validation(format!("Unknown endpoint '{function}' in cache TTLs"))
+            })?;
+            ttls.insert(endpoint, parse_ttl(ttl)?);
+        }
+        Ok(Self { ttls })
+    }
+
+    #[must_use]
+    pub fn ttl(&self, endpoint: EndpointName) -> Option<Duration> {
+        self.ttls.get(&endpoint).copied()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ttls.is_empty()
+    }
+}
+
+/// One cached response as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    function: String,
+    symbol: Option<String>,
+    #[serde(default)]
+    params: BTreeMap<String, String>,
+    fetched_at: DateTime<Utc>,
+    response: serde_json::Value,
+}
+
+/// Summary of the cache directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub expired: usize,
+    pub bytes: u64,
+    /// Entry count per function name
+    pub by_function: BTreeMap<String, usize>,
+}
+
+/// Responses stored on disk, one JSON file per (function, symbol, params).
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    policy: CachePolicy,
+}
+
+impl ResponseCache {
+    #[must_use]
+    pub const fn new(dir: PathBuf, policy: CachePolicy) -> Self {
+        Self { dir, policy }
+    }
+
+    /// The per-user cache directory, falling back to `./.alphavantage-cache`.
+    #[must_use]
+    pub fn default_dir() -> PathBuf {
+        directories::ProjectDirs::from("", "", "alphavantage-explorer").map_or_else(
+            || PathBuf::from("./.alphavantage-cache"),
+            |dirs| dirs.cache_dir().join("responses"),
+        )
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        params: &BTreeMap<String, String>,
+    ) -> PathBuf {
+        let file = if params.is_empty() {
+            "default.json".to_string()
+        } else {
+            let mut hasher = Sha256::new();
+            for (k, v) in params {
+                hasher.update(k.as_bytes());
+                hasher.update(b"=");
+                hasher.update(v.as_bytes());
+                hasher.update(b"&");
+            }
+            let digest = hasher.finalize();
+            let mut file = digest.iter().take(8).fold(String::new(), |mut hex, b| {
+                let _ = write!(hex, "{b:02x}");
+                hex
+            });
+            file.push_str(".json");
+            file
+        };
+        self.dir
+            .join(endpoint.function_name())
+            .join(symbol.unwrap_or(MARKET_DIR))
+            .join(file)
+    }
+
+    fn is_entry_fresh(&self, entry: &CacheEntry, now: DateTime<Utc>) -> bool {
+        let Ok(endpoint) = EndpointName::from_str(&entry.function) else {
+            return false;
+        };
+        self.policy.ttl(endpoint).is_some_and(|ttl| {
+            chrono::Duration::from_std(ttl).is_ok_and(|ttl| entry.fetched_at + ttl > now)
+        })
+    }
+
+    fn read_entry(path: &Path) -> Option<CacheEntry> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// The cached response, if there is one that has not expired.
+    #[must_use]
+    pub fn get(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        params: &BTreeMap<String, String>,
+    ) -> Option<serde_json::Value> {
+        self.policy.ttl(endpoint)?;
+        let entry = Self::read_entry(&self.entry_path(endpoint, symbol, params))?;
+        self.is_entry_fresh(&entry, Utc::now())
+            .then_some(entry.response)
+    }
+
+    /// The stored response and when it was fetched, even if it has expired or the
+    /// endpoint no longer has a TTL.
+    #[must_use]
+    pub fn get_stale(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        params: &BTreeMap<String, String>,
+    ) -> Option<(serde_json::Value, DateTime<Utc>)> {
+        let entry = Self::read_entry(&self.entry_path(endpoint, symbol, params))?;
+        Some((entry.response, entry.fetched_at))
+    }
+
+    /// Whether a call would be answered from the cache.
+    #[must_use]
+    pub fn is_fresh(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        params: &BTreeMap<String, String>,
+    ) -> bool {
+        self.get(endpoint, symbol, params).is_some()
+    }
+
+    /// Stores `response` if the endpoint has a TTL and the body is real data rather
+    /// than a provider error or rate limit notice.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Io` if the entry cannot be written.
+    pub fn put(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        params: &BTreeMap<String, String>,
+        response: &serde_json::Value,
+    ) -> Result<()> {
+        if self.policy.ttl(endpoint).is_none()
+            || ["Error Message", "Note", "Information"]
+                .iter()
+                .any(|k| response.get(k).is_some())
+        {
+            return Ok(());
+        }
+
+        let path = self.entry_path(endpoint, symbol, params);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let entry = CacheEntry {
+            function: endpoint.function_name().to_string(),
+            symbol: symbol.map(ToString::to_string),
+            params: params.clone(),
+            fetched_at: Utc::now(),
+            response: response.clone(),
+        };
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec(&entry)?)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Every cache entry under the cache directory, with its path.
+    ///
+    /// Only files laid out as `<FUNCTION>/<symbol|_market>/<default|sha8>.json` that
+    /// parse as an entry for that function and symbol count, so other JSON sharing
+    /// the directory is never reported or deleted.
+    fn entries(&self) -> Vec<(PathBuf, CacheEntry)> {
+        let subdirs = |dir: &Path| {
+            std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect::<Vec<_>>()
+        };
+        let name = |path: &Path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let mut entries = Vec::new();
+        for function_dir in subdirs(&self.dir) {
+            let function = name(&function_dir);
+            if EndpointName::from_str(&function).is_err() {
+                continue;
+            }
+            for symbol_dir in subdirs(&function_dir) {
+                let symbol = name(&symbol_dir);
+                let Ok(files) = std::fs::read_dir(&symbol_dir) else {
+                    continue;
+                };
+                for path in files.flatten().map(|e| e.path()) {
+                    if !is_entry_file_name(&name(&path)) {
+                        continue;
+                    }
+                    let Some(entry) = Self::read_entry(&path) else {
+                        continue;
+                    };
+                    if entry.function == function
+                        && entry.symbol.as_deref().unwrap_or(MARKET_DIR) == symbol
+                    {
+                        entries.push((path, entry));
+                    }
+                }
+            }
+        }
+        entries
+    }
+
+    /// Counts entries, expired entries and bytes on disk.
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        let now = Utc::now();
+        let mut stats = CacheStats::default();
+        for (path, entry) in self.entries() {
+            stats.entries += 1;
+            stats.bytes += std::fs::metadata(&path).map_or(0, |m| m.len());
+            if !self.is_entry_fresh(&entry, now) {
+                stats.expired += 1;
+            }
+            *stats.by_function.entry(entry.function).or_default() += 1;
+        }
+        stats
+    }
+
+    /// Deletes every entry. Returns the number removed.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Io` if an entry cannot be removed.
+    pub fn purge(&self) -> Result<usize> {
+        let entries = self.entries();
+        for (path, _) in &entries {
+            std::fs::remove_file(path)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Deletes expired entries, including those of endpoints that no longer have a
+    /// TTL. Returns the number removed.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Io` if an entry cannot be removed.
+    pub fn prune(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut removed = 0;
+        for (path, entry) in self.entries() {
+            if !self.is_entry_fresh(&entry, now) {
+                std::fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Whether `name` is a file name `entry_path` produces: `default.json` or 16 hex
+/// digits of a params hash.
+fn is_entry_file_name(name: &str) -> bool {
+    name.strip_suffix(".json").is_some_and(|stem| {
+        stem == "default" || (stem.len() == 16 && stem.bytes().all(|b| b.is_ascii_hexdigit()))
+    })
+}
+
+/// `ApiClient` decorator that answers from a `ResponseCache` when it can and
+/// stores what the inner client fetches.
+pub struct CachingClient {
+    inner: Box<dyn ApiClient>,
+    cache: ResponseCache,
+    refresh: bool,
+}
+
+impl CachingClient {
+    #[must_use]
+    pub fn new(inner: Box<dyn ApiClient>, cache: ResponseCache) -> Self {
+        Self {
+            inner,
+            cache,
+            refresh: false,
+        }
+    }
+
+    /// Ignores cached responses but still stores fresh ones.
+    #[must_use]
+    pub const fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    fn lookup(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        params: &BTreeMap<String, String>,
+    ) -> Option<serde_json::Value> {
+        if self.refresh {
+            return None;
+        }
+        let hit = self.cache.get(endpoint, symbol, params);
+        if hit.is_some() {
+            debug!(
+                "Cache hit for {} {}",
+                endpoint,
+                symbol.unwrap_or("(market)")
+            );
+        }
+        hit
+    }
+
+    fn store(
+        &self,
+        endpoint: EndpointName,
+        symbol: Option<&str>,
+        params: &BTreeMap<String, String>,
+        response: &serde_json::Value,
+    ) {
+        if let Err(e) = self.cache.put(endpoint, symbol, params, response) {
+            warn!("Failed to cache {} response: {}", endpoint, e);
+        }
+    }
+}
+
+#[async_trait]
+impl ApiClient for CachingClient {
+    async fn fetch_ticker_endpoint(
+        &self,
+        endpoint: EndpointName,
+        ticker: &TickerSymbol,
+        params: Option<&HashMap<String, String>>,
+        api_key: &ApiKey,
+    ) -> Result<serde_json::Value> {
+        let key_params: BTreeMap<String, String> = params
+            .map(|p| p.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+        if let Some(hit) = self.lookup(endpoint, Some(ticker.as_str()), &key_params) {
+            return Ok(hit);
+        }
+
+        let response = self
+            .inner
+            .fetch_ticker_endpoint(endpoint, ticker, params, api_key)
+            .await?;
+        self.store(endpoint, Some(ticker.as_str()), &key_params, &response);
+        Ok(response)
+    }
+
+    async fn fetch_market_endpoint(
+        &self,
+        endpoint: EndpointName,
+        api_key: &ApiKey,
+    ) -> Result<serde_json::Value> {
+        let no_params = BTreeMap::new();
+        if let Some(hit) = self.lookup(endpoint, None, &no_params) {
+            return Ok(hit);
+        }
+
+        let response = self.inner.fetch_market_endpoint(endpoint, api_key).await?;
+        self.store(endpoint, None, &no_params, &response);
+        Ok(response)
+    }
+
+    fn key_usage(&self) -> Vec<KeyUsage> {
+        self.inner.key_usage()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Counts calls and answers every request with the same body.
+    struct CountingClient {
+        calls: Arc<AtomicUsize>,
+        body: serde_json::Value,
+    }
+
+    #[async_trait]
+    impl ApiClient for CountingClient {
+        async fn fetch_ticker_endpoint(
+            &self,
+            _endpoint: EndpointName,
+            _ticker: &TickerSymbol,
+            _params: Option<&HashMap<String, String>>,
+            _api_key: &ApiKey,
+        ) -> Result<serde_json::Value> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.body.clone())
+        }
+
+        async fn fetch_market_endpoint(
+            &self,
+            _endpoint: EndpointName,
+            _api_key: &ApiKey,
+        ) -> Result<serde_json::Value> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.body.clone())
+        }
+    }
+
+    fn caching_client(dir: &Path, body: serde_json::Value) -> (CachingClient, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = CountingClient {
+            calls: Arc::clone(&calls),
+            body,
+        };
+        let policy =
+            CachePolicy::from_pairs([("OVERVIEW", "1d"), ("MARKET_STATUS", "1h")]).unwrap();
+        let client = CachingClient::new(
+            Box::new(inner),
+            ResponseCache::new(dir.to_path_buf(), policy),
+        );
+        (client, calls)
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_ttl("1m").unwrap(), Duration::from_mins(1));
+        assert_eq!(parse_ttl("12h").unwrap(), Duration::from_hours(12));
+        assert_eq!(parse_ttl("30d").unwrap(), Duration::from_hours(720));
+        assert!(parse_ttl("30").is_err());
+        assert!(parse_ttl("d").is_err());
+        assert!(parse_ttl("3w").is_err());
+    }
+
+    #[test]
+    fn test_unknown_endpoint_in_policy() {
+        assert!(CachePolicy::from_pairs([("NOT_AN_ENDPOINT", "1d")]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_second_fetch_served_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let (client, calls) = caching_client(dir.path(), serde_json::json!({"Symbol": "IBM"}));
+        let ibm = TickerSymbol::new("IBM").unwrap();
+        let key = ApiKey::new("key");
+
+        for _ in 0..3 {
+            let json = client
+                .fetch_ticker_endpoint(EndpointName::Overview, &ibm, None, &key)
+                .await
+                .unwrap();
+            assert_eq!(json.get("Symbol").unwrap(), "IBM");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Different params are a different entry
+        let params = HashMap::from([("limit".to_string(), "10".to_string())]);
+        client
+            .fetch_ticker_endpoint(EndpointName::Overview, &ibm, Some(&params), &key)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Endpoints without a TTL always go to the network
+        client
+            .fetch_ticker_endpoint(EndpointName::CashFlow, &ibm, None, &key)
+            .await
+            .unwrap();
+        client
+            .fetch_ticker_endpoint(EndpointName::CashFlow, &ibm, None, &key)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        let stats = client.cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.expired, 0);
+        assert_eq!(stats.by_function.get("OVERVIEW"), Some(&2));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_bypasses_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let (client, calls) = caching_client(dir.path(), serde_json::json!({"status": "open"}));
+        let client = client.refresh(true);
+        let key = ApiKey::new("key");
+
+        client
+            .fetch_market_endpoint(EndpointName::MarketStatus, &key)
+            .await
+            .unwrap();
+        client
+            .fetch_market_endpoint(EndpointName::MarketStatus, &key)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        // ...but still writes the fresh response
+        assert!(client
+            .cache
+            .is_fresh(EndpointName::MarketStatus, None, &BTreeMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_provider_notices_are_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let (client, calls) = caching_client(
+            dir.path(),
+            serde_json::json!({"Information": "rate limit reached"}),
+        );
+        let key = ApiKey::new("key");
+
+        client
+            .fetch_market_endpoint(EndpointName::MarketStatus, &key)
+            .await
+            .unwrap();
+        client
+            .fetch_market_endpoint(EndpointName::MarketStatus, &key)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_prune_and_purge() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = CachePolicy::from_pairs([("OVERVIEW", "1d")]).unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), policy);
+        let none = BTreeMap::new();
+
+        cache
+            .put(
+                EndpointName::Overview,
+                Some("IBM"),
+                &none,
+                &serde_json::json!({}),
+            )
+            .unwrap();
+        cache
+            .put(
+                EndpointName::Overview,
+                Some("AAPL"),
+                &none,
+                &serde_json::json!({}),
+            )
+            .unwrap();
+
+        // Age one entry past its TTL
+        let stale = cache.entry_path(EndpointName::Overview, Some("AAPL"), &none);
+        let mut entry = ResponseCache::read_entry(&stale).unwrap();
+        entry.fetched_at -= chrono::Duration::days(2);
+        std::fs::write(&stale, serde_json::to_vec(&entry).unwrap()).unwrap();
+
+        assert_eq!(cache.stats().expired, 1);
+        assert!(cache
+            .get(EndpointName::Overview, Some("AAPL"), &none)
+            .is_none());
+        let (_, fetched_at) = cache
+            .get_stale(EndpointName::Overview, Some("AAPL"), &none)
+            .unwrap();
+        assert!(fetched_at < Utc::now() - chrono::Duration::days(1));
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert_eq!(cache.stats().entries, 1);
+        assert_eq!(cache.purge().unwrap(), 1);
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_foreign_files_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = CachePolicy::from_pairs([("OVERVIEW", "1d")]).unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), policy);
+        let params = BTreeMap::from([("limit".to_string(), "50".to_string())]);
+        cache
+            .put(
+                EndpointName::Overview,
+                Some("IBM"),
+                &params,
+                &serde_json::json!({}),
+            )
+            .unwrap();
+
+        // A report tree and stray JSON sharing the directory, some in the cache layout
+        let foreign = [
+            "raw/tickers/IBM/OVERVIEW.json",
+            "OVERVIEW/IBM/notes.json",
+            "OVERVIEW/AAPL/default.json",
+            "README.json",
+        ];
+        for file in foreign {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, r#"{"Symbol": "IBM"}"#).unwrap();
+        }
+
+        assert_eq!(cache.stats().entries, 1);
+        assert_eq!(cache.prune().unwrap(), 0);
+        assert_eq!(cache.purge().unwrap(), 1);
+        for file in foreign {
+            assert!(dir.path().join(file).exists(), "{file} was deleted");
+        }
+    }
+}
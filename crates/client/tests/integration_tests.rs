@@ -1,12 +1,14 @@
 use alphavantage_client::create_client;
 use alphavantage_client::ClientMode;
+use alphavantage_client::RateLimits;
 use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol};
+use alphavantage_core::ports::ApiClient;
 
 #[allow(clippy::unwrap_used, clippy::indexing_slicing)]
 #[tokio::test]
 async fn test_mock_client_fixtures() {
     let api_key = ApiKey::new("dummy");
-    let client = create_client(ClientMode::Mock, 25, 0);
+    let client = create_client(ClientMode::Mock, &RateLimits::daily(25, 0)).unwrap();
 
     // Test AAPL OVERVIEW
     let ticker = TickerSymbol::new("AAPL").unwrap();
@@ -42,3 +44,105 @@ async fn test_mock_client_fixtures() {
     assert_eq!(json["endpoint"], "TopGainersLosers");
     assert!(!json["top_gainers"].as_array().unwrap().is_empty());
 }
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_mock_scenario_faults() {
+    use alphavantage_client::{MockClient, MockScenario};
+    use alphavantage_core::ExplorerError;
+
+    let scenario: MockScenario = r#"
+latency_ms = 0
+timeout_ms = 10
+
+[[endpoint]]
+function = "OVERVIEW"
+responses = ["http:503", "ok"]
+
+[[endpoint]]
+function = "EARNINGS"
+responses = ["note"]
+
+[[endpoint]]
+function = "SPLITS"
+responses = ["truncated"]
+
+[[endpoint]]
+function = "DIVIDENDS"
+responses = ["error_message"]
+
+[[endpoint]]
+function = "BALANCE_SHEET"
+responses = ["http:404"]
+"#
+    .parse()
+    .unwrap();
+    let client = MockClient::new().with_scenario(scenario);
+    let api_key = ApiKey::new("dummy");
+    let ticker = TickerSymbol::new("AAPL").unwrap();
+    let fetch = |endpoint| client.fetch_ticker_endpoint(endpoint, &ticker, None, &api_key);
+
+    // A 503 is retried and the second attempt succeeds
+    let json = fetch(EndpointName::Overview).await.unwrap();
+    assert_eq!(json.get("Symbol").unwrap(), "AAPL");
+
+    assert!(matches!(
+        fetch(EndpointName::Earnings).await,
+        Err(ExplorerError::RateLimited)
+    ));
+    assert!(matches!(
+        fetch(EndpointName::Splits).await,
+        Err(ExplorerError::Parse(_))
+    ));
+    assert!(matches!(
+        fetch(EndpointName::Dividends).await,
+        Err(ExplorerError::ProviderError(_))
+    ));
+    assert!(matches!(
+        fetch(EndpointName::BalanceSheet).await,
+        Err(ExplorerError::HttpStatus(404))
+    ));
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_http_settings_headers_are_sent() {
+    use alphavantage_client::{AlphaVantageClient, HttpSettings, RateLimiter};
+    use std::collections::BTreeMap;
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(header("user-agent", "research-desk/1.0"))
+        .and(header("x-team", "equities"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"Symbol": "IBM"})),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let settings = HttpSettings {
+        user_agent: Some("research-desk/1.0".to_string()),
+        headers: BTreeMap::from([("X-Team".to_string(), "equities".to_string())]),
+        ..HttpSettings::default()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let limiter = RateLimiter::with_path(dir.path().join("tokens.json"), 25, 0);
+    let client =
+        AlphaVantageClient::with_settings(server.uri(), RateLimits::daily(25, 0), &settings)
+            .unwrap()
+            .with_rate_limiter(limiter);
+
+    let json = client
+        .fetch_ticker_endpoint(
+            EndpointName::Overview,
+            &TickerSymbol::new("IBM").unwrap(),
+            None,
+            &ApiKey::new("dummy"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(json.get("Symbol").unwrap(), "IBM");
+}
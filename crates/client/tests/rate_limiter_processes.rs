@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::indexing_slicing)]
+//! Cross-process tests for the rate limiter state file.
+//!
+//! The parent test re-launches this test binary several times, running only
+//! `child_consumer`, so that several OS processes race on one state file.
+
+use alphavantage_client::RateLimiter;
+use alphavantage_core::domain::ApiKey;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+const STATE_ENV: &str = "AV_TEST_RATE_LIMITER_STATE";
+const CHILDREN: usize = 4;
+const ATTEMPTS_PER_CHILD: usize = 10;
+const DAILY_LIMIT: u32 = 25;
+
+/// Runs only when spawned by `concurrent_processes_share_one_quota`.
+#[tokio::test]
+async fn child_consumer() {
+    let Ok(path) = std::env::var(STATE_ENV) else {
+        return;
+    };
+
+    let limiter = RateLimiter::with_path(PathBuf::from(path), DAILY_LIMIT, 0);
+    let key = ApiKey::new("shared_key");
+    let mut acquired = 0;
+    for _ in 0..ATTEMPTS_PER_CHILD {
+        if limiter.wait(&key).await.is_ok() {
+            acquired += 1;
+        }
+    }
+    println!("acquired={acquired}");
+}
+
+#[test]
+fn concurrent_processes_share_one_quota() {
+    let dir = tempfile::tempdir().unwrap();
+    let state_path = dir.path().join("tokens.json");
+    let exe = std::env::current_exe().unwrap();
+
+    // Spawn every child before waiting on any of them so they actually overlap
+    #[allow(clippy::needless_collect)]
+    let children: Vec<_> = (0..CHILDREN)
+        .map(|_| {
+            Command::new(&exe)
+                .args([
+                    "child_consumer",
+                    "--exact",
+                    "--nocapture",
+                    "--test-threads=1",
+                ])
+                .env(STATE_ENV, &state_path)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("failed to spawn child process")
+        })
+        .collect();
+
+    let total: usize = children
+        .into_iter()
+        .map(|child| {
+            let output = child.wait_with_output().expect("child process failed");
+            assert!(output.status.success(), "child exited with failure");
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            stdout
+                .lines()
+                .find_map(|line| line.split("acquired=").nth(1))
+                .and_then(|rest| rest.split_whitespace().next())
+                .expect("child did not report its count")
+                .parse::<usize>()
+                .unwrap()
+        })
+        .sum();
+
+    // 4 x 10 attempts against a quota of 25: exactly 25 may succeed
+    assert_eq!(total, DAILY_LIMIT as usize);
+
+    let file: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&state_path).unwrap()).unwrap();
+    let state = &file["keys"][ApiKey::new("shared_key").fingerprint()];
+    assert_eq!(state["calls_today"], DAILY_LIMIT);
+    assert_eq!(state["tokens_remaining"], 0);
+}
@@ -0,0 +1,183 @@
+#![allow(clippy::unwrap_used)]
+
+//! Failing requests made with a sentinel key must not leak it anywhere.
+
+use alphavantage_client::{
+    AlphaVantageClient, FileSystemJsonPersister, MarkdownWriterImpl, RateLimiter,
+};
+use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol};
+use alphavantage_core::ports::{ApiClient, JsonPersister, MarkdownWriter};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use wiremock::matchers::query_param;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SENTINEL: &str = "SENTINEL_KEY_7f3a9c";
+
+/// Collects everything logged while the test runs.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LogBuffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+fn client_for(base_url: String, state: &TempDir) -> AlphaVantageClient {
+    let limiter = RateLimiter::with_path(state.path().join("tokens.json"), 1_000, 0);
+    AlphaVantageClient::with_base_url(base_url, 1_000, 0)
+        .unwrap()
+        .with_rate_limiter(limiter)
+}
+
+fn respond(function: &str, template: ResponseTemplate) -> Mock {
+    Mock::given(query_param("function", function)).respond_with(template)
+}
+
+#[tokio::test]
+async fn test_sentinel_key_never_leaks() {
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let server = MockServer::start().await;
+    let echoed = format!("{}/query?function=OVERVIEW&apikey={SENTINEL}", server.uri());
+    respond(
+        "OVERVIEW",
+        ResponseTemplate::new(200).set_body_json(
+            serde_json::json!({ "Error Message": format!("Invalid call: {echoed}") }),
+        ),
+    )
+    .mount(&server)
+    .await;
+    respond("EARNINGS", ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+    respond(
+        "SPLITS",
+        ResponseTemplate::new(200).set_body_string(format!("Unknown key {SENTINEL}")),
+    )
+    .mount(&server)
+    .await;
+    respond(
+        "DIVIDENDS",
+        ResponseTemplate::new(200).set_body_string(format!("{{\"Note\": \"{SENTINEL}")),
+    )
+    .mount(&server)
+    .await;
+    respond(
+        "CASH_FLOW",
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "Information": format!("Key {SENTINEL} is on the free plan, see {echoed}"),
+        })),
+    )
+    .mount(&server)
+    .await;
+
+    let state = tempfile::tempdir().unwrap();
+    let key = ApiKey::new(SENTINEL);
+    let ibm = TickerSymbol::new("IBM").unwrap();
+    let client = client_for(format!("{}/query", server.uri()), &state);
+    // Nothing listens on port 9, so the request fails with a URL-bearing reqwest error
+    let unreachable = client_for("http://127.0.0.1:9/query".to_string(), &state);
+
+    let mut errors = Vec::new();
+    for endpoint in [
+        EndpointName::Overview,
+        EndpointName::Earnings,
+        EndpointName::Splits,
+        EndpointName::Dividends,
+    ] {
+        errors.push(
+            client
+                .fetch_ticker_endpoint(endpoint, &ibm, None, &key)
+                .await
+                .unwrap_err(),
+        );
+    }
+    errors.push(
+        unreachable
+            .fetch_ticker_endpoint(EndpointName::Overview, &ibm, None, &key)
+            .await
+            .unwrap_err(),
+    );
+    let body = client
+        .fetch_ticker_endpoint(EndpointName::CashFlow, &ibm, None, &key)
+        .await
+        .unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let raw = out.path().join("raw/IBM/CASH_FLOW.json");
+    FileSystemJsonPersister::new()
+        .save_raw_json(&raw, &body)
+        .unwrap();
+    let mut markdown = MarkdownWriterImpl::new();
+    markdown.write_heading(body.get("Information").unwrap().as_str().unwrap(), 2);
+    for error in &errors {
+        markdown.write_error(error).unwrap();
+    }
+    let report = out.path().join("IBM.md");
+    markdown.flush_to_file(&report).unwrap();
+
+    for error in &errors {
+        let text = format!("{error} {error:?}");
+        assert!(!text.contains(SENTINEL), "{text}");
+    }
+    let unreachable_error = errors.last().unwrap().to_string();
+    assert!(
+        unreachable_error.contains("apikey=REDACTED"),
+        "{unreachable_error}"
+    );
+    for (what, text) in [
+        ("logs", logs.contents()),
+        ("raw JSON", std::fs::read_to_string(&raw).unwrap()),
+        ("markdown", std::fs::read_to_string(&report).unwrap()),
+    ] {
+        assert!(!text.contains(SENTINEL), "{what} leaked the key:\n{text}");
+    }
+    // The retried failures were logged, so the log check is not vacuous
+    assert!(logs.contents().contains("Retrying"), "{}", logs.contents());
+}
+
+#[tokio::test]
+async fn test_response_text_is_not_rewritten() {
+    let server = MockServer::start().await;
+    let summary = "Management demonstrated the demo; ABCDEF grew.";
+    respond(
+        "NEWS_SENTIMENT",
+        ResponseTemplate::new(200)
+            .set_body_json(serde_json::json!({ "feed": [{ "summary": summary }] })),
+    )
+    .mount(&server)
+    .await;
+
+    let state = tempfile::tempdir().unwrap();
+    let client = client_for(format!("{}/query", server.uri()), &state);
+    let ibm = TickerSymbol::new("IBM").unwrap();
+    // Neither the public demo key nor a short key inside a longer word is scrubbed
+    for key in [ApiKey::new(ApiKey::DEMO), ApiKey::new("ABC")] {
+        let body = client
+            .fetch_ticker_endpoint(EndpointName::NewsSentiment, &ibm, None, &key)
+            .await
+            .unwrap();
+        assert_eq!(body.pointer("/feed/0/summary").unwrap(), summary);
+    }
+}
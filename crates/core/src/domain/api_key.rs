@@ -1,6 +1,8 @@
 use crate::error::{ExplorerError, Result};
 use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
 use std::fmt;
+use std::fmt::Write;
 use std::str::FromStr;
 
 /// Represents an Alpha Vantage API key.
@@ -14,6 +16,9 @@ use std::str::FromStr;
 pub struct ApiKey(Secret<String>);
 
 impl ApiKey {
+    /// Alpha Vantage's public demo key, which is neither secret nor rate limited.
+    pub const DEMO: &'static str = "demo";
+
     /// Creates a new `ApiKey` from a string.
     pub fn new(key: impl Into<String>) -> Self {
         Self(Secret::new(key.into()))
@@ -24,6 +29,25 @@ impl ApiKey {
     pub fn secret(&self) -> &str {
         self.0.expose_secret()
     }
+
+    /// Whether this is the public [`ApiKey::DEMO`] key.
+    #[must_use]
+    pub fn is_demo(&self) -> bool {
+        self.secret() == Self::DEMO
+    }
+
+    /// Returns a short, stable identifier for this key that is safe to persist and log.
+    ///
+    /// This is the first 16 hex characters of the SHA-256 digest of the key, so the
+    /// key itself cannot be recovered from it.
+    #[must_use]
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::digest(self.secret().as_bytes());
+        digest.iter().take(8).fold(String::new(), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+    }
 }
 
 impl fmt::Debug for ApiKey {
@@ -56,9 +80,21 @@ mod tests {
         assert_eq!(format!("{key:?}"), "[REDACTED]");
     }
 
+    #[test]
+    fn test_fingerprint_is_stable_and_hides_key() {
+        let key = ApiKey::new("my_secret_key");
+        let fingerprint = key.fingerprint();
+        assert_eq!(fingerprint.len(), 16);
+        assert_eq!(fingerprint, ApiKey::new("my_secret_key").fingerprint());
+        assert_ne!(fingerprint, ApiKey::new("other_key").fingerprint());
+        assert!(!fingerprint.contains("my_secret_key"));
+    }
+
     #[test]
     fn test_secret_usage() {
         let key = ApiKey::new("secret");
         assert_eq!(key.secret(), "secret");
+        assert!(!key.is_demo());
+        assert!(ApiKey::new("demo").is_demo());
     }
 }
@@ -2,12 +2,14 @@ pub mod api_key;
 pub mod endpoint;
 pub mod market_data;
 pub mod params;
+pub mod quota;
 pub mod schema_table;
 pub mod ticker;
 
 pub use api_key::ApiKey;
 pub use endpoint::EndpointName;
 pub use market_data::{ApiError, ApiResponse, ErrorKind};
-pub use params::{validate_year, HorizonParam, QuarterParam};
+pub use params::{validate_year, HorizonParam, QuarterParam, QuarterRange, YearQuarter};
+pub use quota::KeyUsage;
 pub use schema_table::SchemaTable;
 pub use ticker::TickerSymbol;
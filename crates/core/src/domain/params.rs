@@ -1,8 +1,9 @@
+use chrono::{Datelike, NaiveDate};
 use std::fmt;
 use std::str::FromStr;
 
 /// Represents a fiscal quarter
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum QuarterParam {
     Q1,
     Q2,
@@ -82,7 +83,176 @@ pub fn validate_year(year: u16) -> Result<u16, String> {
     }
 }
 
+impl QuarterParam {
+    /// 1 to 4.
+    #[must_use]
+    pub const fn number(self) -> u8 {
+        match self {
+            Self::Q1 => 1,
+            Self::Q2 => 2,
+            Self::Q3 => 3,
+            Self::Q4 => 4,
+        }
+    }
+
+    const fn from_number(n: u32) -> Self {
+        match n {
+            1 => Self::Q1,
+            2 => Self::Q2,
+            3 => Self::Q3,
+            _ => Self::Q4,
+        }
+    }
+}
+
+/// A quarter of a year, written `2024Q1` as in the transcript endpoint's `quarter`
+/// parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct YearQuarter {
+    pub year: u16,
+    pub quarter: QuarterParam,
+}
+
+impl YearQuarter {
+    /// The first quarter `new` accepts.
+    pub const EARLIEST: Self = Self {
+        year: 1900,
+        quarter: QuarterParam::Q1,
+    };
+
+    /// # Errors
+    /// Returns error if year is not between 1900 and 2100
+    pub fn new(year: u16, quarter: QuarterParam) -> Result<Self, String> {
+        Ok(Self {
+            year: validate_year(year)?,
+            quarter,
+        })
+    }
+
+    /// The last calendar quarter that ended before `today`, e.g. `2024Q2` on 2024-08-15.
+    #[must_use]
+    pub fn latest(today: NaiveDate) -> Self {
+        let year = u16::try_from(today.year()).unwrap_or(0);
+        let current = Self {
+            year,
+            quarter: QuarterParam::from_number(today.month0() / 3 + 1),
+        };
+        current.prev()
+    }
+
+    /// The quarter before this one.
+    #[must_use]
+    pub const fn prev(self) -> Self {
+        match self.quarter {
+            QuarterParam::Q1 => Self {
+                year: self.year.saturating_sub(1),
+                quarter: QuarterParam::Q4,
+            },
+            q => Self {
+                year: self.year,
+                quarter: QuarterParam::from_number(q.number() as u32 - 1),
+            },
+        }
+    }
+
+    /// The quarter after this one.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self.quarter {
+            QuarterParam::Q4 => Self {
+                year: self.year.saturating_add(1),
+                quarter: QuarterParam::Q1,
+            },
+            q => Self {
+                year: self.year,
+                quarter: QuarterParam::from_number(q.number() as u32 + 1),
+            },
+        }
+    }
+}
+
+impl FromStr for YearQuarter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid quarter '{s}'. Must look like 2024Q1");
+        let (year, quarter) = s.trim().split_at_checked(4).ok_or_else(invalid)?;
+        let year: u16 = year.parse().map_err(|_| invalid())?;
+        let quarter = QuarterParam::from_str(quarter).map_err(|_| invalid())?;
+        Self::new(year, quarter)
+    }
+}
+
+impl fmt::Display for YearQuarter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.year, self.quarter)
+    }
+}
+
+/// An inclusive range of quarters, e.g. `2022Q1` to `2024Q4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuarterRange {
+    pub from: YearQuarter,
+    pub to: YearQuarter,
+}
+
+impl QuarterRange {
+    /// # Errors
+    /// Returns error if `from` is after `to`
+    pub fn new(from: YearQuarter, to: YearQuarter) -> Result<Self, String> {
+        if from > to {
+            return Err(format!("Invalid quarter range: {from} is after {to}"));
+        }
+        Ok(Self { from, to })
+    }
+
+    /// The `count` quarters ending with [`YearQuarter::latest`], or fewer if that would
+    /// start before [`YearQuarter::EARLIEST`].
+    #[must_use]
+    pub fn latest(count: u32, today: NaiveDate) -> Self {
+        let to = YearQuarter::latest(today);
+        let mut from = to;
+        for _ in 1..count.max(1) {
+            if from <= YearQuarter::EARLIEST {
+                break;
+            }
+            from = from.prev();
+        }
+        Self { from, to }
+    }
+
+    /// Number of quarters in the range, without listing them.
+    #[must_use]
+    pub fn quarter_count(&self) -> u32 {
+        let index = |q: YearQuarter| u32::from(q.year) * 4 + u32::from(q.quarter.number());
+        index(self.to).saturating_sub(index(self.from)) + 1
+    }
+
+    /// Every quarter in the range, oldest first.
+    #[must_use]
+    pub fn quarters(&self) -> Vec<YearQuarter> {
+        let mut quarters = vec![self.from];
+        let mut current = self.from;
+        while current < self.to {
+            current = current.next();
+            quarters.push(current);
+        }
+        quarters
+    }
+}
+
+impl fmt::Display for QuarterRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.from == self.to {
+            write!(f, "{}", self.from)
+        } else {
+            write!(f, "{} to {}", self.from, self.to)
+        }
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -119,4 +289,61 @@ mod tests {
         assert!(validate_year(1899).is_err());
         assert!(validate_year(2101).is_err());
     }
+
+    #[test]
+    fn test_year_quarter() {
+        let q = |s: &str| YearQuarter::from_str(s).unwrap();
+        assert_eq!(q("2024Q1").to_string(), "2024Q1");
+        assert_eq!(
+            q("2024q4"),
+            YearQuarter::new(2024, QuarterParam::Q4).unwrap()
+        );
+        assert!(q("2023Q4") < q("2024Q1"));
+        assert_eq!(q("2023Q4").next(), q("2024Q1"));
+        assert_eq!(q("2024Q1").prev(), q("2023Q4"));
+        assert_eq!(q("2024Q3").prev(), q("2024Q2"));
+        for invalid in ["2024Q5", "24Q1", "latest", "1800Q1", "2024"] {
+            assert!(YearQuarter::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_latest_quarter() {
+        let latest =
+            |y, m, d| YearQuarter::latest(NaiveDate::from_ymd_opt(y, m, d).unwrap()).to_string();
+        assert_eq!(latest(2024, 8, 15), "2024Q2");
+        assert_eq!(latest(2024, 4, 1), "2024Q1");
+        assert_eq!(latest(2024, 2, 29), "2023Q4");
+        assert_eq!(latest(2024, 12, 31), "2024Q3");
+    }
+
+    #[test]
+    fn test_quarter_range() {
+        let q = |s: &str| YearQuarter::from_str(s).unwrap();
+        let range = QuarterRange::new(q("2022Q1"), q("2024Q4")).unwrap();
+        assert_eq!(range.quarters().len(), 12);
+        assert_eq!(range.quarters().last(), Some(&q("2024Q4")));
+        assert_eq!(range.to_string(), "2022Q1 to 2024Q4");
+        assert!(QuarterRange::new(q("2024Q2"), q("2024Q1")).is_err());
+
+        let today = NaiveDate::from_ymd_opt(2024, 8, 15).unwrap();
+        let latest = QuarterRange::latest(4, today);
+        assert_eq!(
+            latest
+                .quarters()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["2023Q3", "2023Q4", "2024Q1", "2024Q2"]
+        );
+        assert_eq!(QuarterRange::latest(1, today).to_string(), "2024Q2");
+        assert_eq!(range.quarter_count(), 12);
+        assert_eq!(latest.quarter_count(), 4);
+
+        // Never reaches before the first valid year
+        let huge = QuarterRange::latest(u32::MAX, today);
+        assert_eq!(huge.from, YearQuarter::EARLIEST);
+        assert_eq!(huge.quarter_count(), 498);
+        assert_eq!(huge.quarters().len(), 498);
+    }
 }
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// API usage attributed to one key, as reported at the end of a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyUsage {
+    /// Short hash identifying the key (see `ApiKey::fingerprint`), never the key itself
+    pub fingerprint: String,
+    /// Calls authorized for this key by the current process
+    pub calls_this_run: u32,
+    /// Calls counted against this key's quota today, across all processes
+    pub calls_today: u32,
+    /// Tokens left today, or `None` if the key has no daily cap
+    pub remaining_today: Option<u32>,
+}
@@ -2,25 +2,57 @@ use crate::error::{ExplorerError, Result};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Longest accepted symbol, suffixes included.
+const MAX_LEN: usize = 16;
+const MAX_ROOT_LEN: usize = 10;
+const MAX_CLASS_LEN: usize = 3;
+const MAX_EXCHANGE_LEN: usize = 5;
+
+/// One- and two-letter exchange suffixes (e.g. `7203.T`, `VOD.L`, `SHOP.TO`). Other
+/// one- and two-letter suffixes after a `.` are share classes, as in `BRK.B`.
+const SHORT_EXCHANGES: [&str; 34] = [
+    "T", "L", "F", "TO", "HK", "AX", "DE", "PA", "SS", "SZ", "KS", "KQ", "NS", "BO", "SA", "MI",
+    "AS", "SW", "MC", "BR", "ST", "HE", "OL", "CO", "IR", "LS", "JK", "KL", "TW", "SI", "NZ", "MX",
+    "VI", "TA",
+];
+
 /// Represents a validated Alpha Vantage ticker symbol.
 ///
+/// Shape: `ROOT[-CLASS|.CLASS][.EXCHANGE]`, e.g. `IBM`, `BRK.B`, `BF-B`, `SHOP.TRT`,
+/// `RELIANCE.BSE` or `7203.T`.
+///
 /// Rules:
-/// - Length: 1 to 5 characters
-/// - Characters: Uppercase A-Z, 0-9
+/// - Input is trimmed and uppercased
+/// - Length: 1 to 16 characters
+/// - Root: 1 to 10 of A-Z, 0-9
+/// - Share class: 1 to 3 of A-Z, 0-9 after `-`, or 1 to 2 after `.`
+/// - Exchange: 1 to 5 of A-Z, 0-9 after the last `.`; a one- or two-letter suffix
+///   is an exchange only if it is a known exchange code
+///
+/// Only A-Z, 0-9, `.` and `-` are accepted and every part is non-empty, so a symbol
+/// is always a single, portable path component (`out/raw/tickers/<SYM>`).
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(try_from = "String", into = "String")]
 pub struct TickerSymbol(String);
 
+/// The parts of a symbol, borrowed from it.
+struct Parts<'a> {
+    root: &'a str,
+    class: Option<&'a str>,
+    exchange: Option<&'a str>,
+}
+
 impl TickerSymbol {
-    /// Creates a new `TickerSymbol` if valid.
+    /// Creates a new `TickerSymbol` if valid, uppercasing it.
     ///
     /// # Errors
     /// Returns `ExplorerError::Validation` if:
-    /// - Length is invalid (must be 1-5)
-    /// - Contains characters other than A-Z, 0-9
+    /// - Length is invalid (must be 1-16)
+    /// - Contains characters other than letters, digits, `.` and `-`
+    /// - The root, share class or exchange is empty or too long
     pub fn new(symbol: impl Into<String>) -> Result<Self> {
-        let s = symbol.into();
-        Self::validate(&s)?;
+        let s = symbol.into().trim().to_ascii_uppercase();
+        Self::parse(&s)?;
         Ok(Self(s))
     }
 
@@ -30,27 +62,98 @@ impl TickerSymbol {
         &self.0
     }
 
-    fn validate(s: &str) -> Result<()> {
+    /// The symbol without share class or exchange, e.g. `BRK` for `BRK.B`.
+    #[must_use]
+    pub fn root(&self) -> &str {
+        self.parts().root
+    }
+
+    /// The share class, e.g. `B` for `BRK.B` and `BF-B`.
+    #[must_use]
+    pub fn share_class(&self) -> Option<&str> {
+        self.parts().class
+    }
+
+    /// The exchange suffix, e.g. `TRT` for `SHOP.TRT` and `T` for `7203.T`.
+    #[must_use]
+    pub fn exchange(&self) -> Option<&str> {
+        self.parts().exchange
+    }
+
+    fn parts(&self) -> Parts<'_> {
+        // Validated on construction
+        Self::parse(&self.0).unwrap_or(Parts {
+            root: &self.0,
+            class: None,
+            exchange: None,
+        })
+    }
+
+    fn parse(s: &str) -> Result<Parts<'_>> {
         if s.is_empty() {
             return Err(ExplorerError::Validation("Ticker cannot be empty".into()));
         }
-        if s.len() > 5 {
-            return Err(ExplorerError::Validation(
-                "Ticker too long (max 5 chars)".into(),
-            ));
+        if s.len() > MAX_LEN {
+            return Err(ExplorerError::Validation(format!(
+                "Ticker too long (max {MAX_LEN} chars)"
+            )));
         }
         if !s
             .chars()
-            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '.' || c == '-')
         {
             return Err(ExplorerError::Validation(
-                "Ticker must be uppercase alphanumeric".into(),
+                "Ticker may only contain letters, digits, '.' and '-'".into(),
             ));
         }
-        Ok(())
+        let invalid = || {
+            ExplorerError::Validation(format!(
+                "Invalid ticker '{s}': expected ROOT[-CLASS|.CLASS][.EXCHANGE], e.g. BRK.B or SHOP.TRT"
+            ))
+        };
+
+        let dotted: Vec<&str> = s.split('.').collect();
+        let (head, class, exchange) = match dotted.as_slice() {
+            [head] => (*head, None, None),
+            [head, suffix] if head.contains('-') || !is_share_class(suffix) => {
+                (*head, None, Some(*suffix))
+            }
+            [head, class] => (*head, Some(*class), None),
+            [head, class, exchange] if !head.contains('-') && is_share_class(class) => {
+                (*head, Some(*class), Some(*exchange))
+            }
+            _ => return Err(invalid()),
+        };
+        let (root, class) = match head.split_once('-') {
+            Some((root, dashed)) if class.is_none() && dashed.len() <= MAX_CLASS_LEN => {
+                (root, Some(dashed))
+            }
+            Some(_) => return Err(invalid()),
+            None => (head, class),
+        };
+
+        let valid = |part: &str, max: usize| {
+            (1..=max).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        };
+        if !valid(root, MAX_ROOT_LEN)
+            || class.is_some_and(|c| !valid(c, MAX_CLASS_LEN))
+            || exchange.is_some_and(|e| !valid(e, MAX_EXCHANGE_LEN))
+        {
+            return Err(invalid());
+        }
+        Ok(Parts {
+            root,
+            class,
+            exchange,
+        })
     }
 }
 
+/// Whether a suffix after `.` is a share class rather than an exchange.
+fn is_share_class(suffix: &str) -> bool {
+    (1..=2).contains(&suffix.len()) && !SHORT_EXCHANGES.contains(&suffix)
+}
+
 impl fmt::Display for TickerSymbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -72,9 +175,11 @@ impl From<TickerSymbol> for String {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
     use proptest::prelude::*;
+    use std::path::{Component, Path};
 
     #[test]
     fn test_valid_tickers() {
@@ -82,15 +187,60 @@ mod tests {
         assert!(TickerSymbol::new("A").is_ok());
         assert!(TickerSymbol::new("123").is_ok());
         assert!(TickerSymbol::new("GOOGL").is_ok()); // 5 chars
+        assert!(TickerSymbol::new("GOOGLE").is_ok()); // 6 chars
+    }
+
+    #[test]
+    fn test_case_is_normalized() {
+        assert_eq!(TickerSymbol::new("ibm").unwrap().as_str(), "IBM");
+        assert_eq!(TickerSymbol::new(" brk.b ").unwrap().as_str(), "BRK.B");
+        assert_eq!(
+            TickerSymbol::new("shop.trt").unwrap(),
+            TickerSymbol::new("SHOP.TRT").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_share_classes_and_exchanges() {
+        let parts = |s: &str| {
+            let t = TickerSymbol::new(s).unwrap();
+            (
+                t.root().to_string(),
+                t.share_class().map(str::to_string),
+                t.exchange().map(str::to_string),
+            )
+        };
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(parts("BRK.B"), ("BRK".into(), some("B"), None));
+        assert_eq!(parts("BF-B"), ("BF".into(), some("B"), None));
+        assert_eq!(parts("SHOP.TRT"), ("SHOP".into(), None, some("TRT")));
+        assert_eq!(
+            parts("RELIANCE.BSE"),
+            ("RELIANCE".into(), None, some("BSE"))
+        );
+        assert_eq!(parts("7203.T"), ("7203".into(), None, some("T")));
+        assert_eq!(parts("BRK-B.TRT"), ("BRK".into(), some("B"), some("TRT")));
+        assert_eq!(parts("BRK.B.TO"), ("BRK".into(), some("B"), some("TO")));
+        assert_eq!(parts("BAC-PL"), ("BAC".into(), some("PL"), None));
+        assert_eq!(parts("IBM"), ("IBM".into(), None, None));
     }
 
     #[test]
     fn test_invalid_tickers() {
         assert!(TickerSymbol::new("").is_err());
-        assert!(TickerSymbol::new("GOOGLE").is_err()); // 6 chars
-        assert!(TickerSymbol::new("ibm").is_err()); // lowercase
+        assert!(TickerSymbol::new("   ").is_err());
+        assert!(TickerSymbol::new("ABCDEFGHIJKLMNOPQ").is_err()); // 17 chars
+        assert!(TickerSymbol::new("ABCDEFGHIJK").is_err()); // 11-char root
         assert!(TickerSymbol::new("IB M").is_err()); // space
         assert!(TickerSymbol::new("IB$").is_err()); // special char
+        assert!(TickerSymbol::new("BRK/B").is_err());
+        for malformed in [
+            ".B", "BRK.", "BRK..B", "BRK-", "-B", "A-B-C", "A.B.C.D", "BF-B.C.D",
+        ] {
+            assert!(TickerSymbol::new(malformed).is_err(), "{malformed}");
+        }
+        let err = TickerSymbol::new("INVALID_SYMBOL_TOO_LONG").unwrap_err();
+        assert!(err.to_string().contains("Ticker too long"), "{err}");
     }
 
     proptest! {
@@ -98,5 +248,38 @@ mod tests {
         fn doesnt_panic(s in "\\PC*") {
             let _ = TickerSymbol::new(s);
         }
+
+        #[test]
+        fn accepted_symbols_are_single_path_components(s in "[A-Za-z0-9.\\-/ ]{0,20}") {
+            if let Ok(t) = TickerSymbol::new(s) {
+                let components: Vec<Component<'_>> = Path::new(t.as_str()).components().collect();
+                prop_assert_eq!(components.len(), 1);
+                prop_assert!(matches!(components.first(), Some(Component::Normal(c)) if *c == t.as_str()));
+                prop_assert!(!t.as_str().starts_with('.') && !t.as_str().ends_with('.'));
+            }
+        }
+
+        #[test]
+        fn structured_symbols_round_trip(
+            root in "[A-Z0-9]{1,10}",
+            class in proptest::option::of("[A-Z]{1,3}"),
+            exchange in proptest::option::of("[A-Z]{3,5}"),
+        ) {
+            let mut s = root.clone();
+            if let Some(class) = &class {
+                s.push('-');
+                s.push_str(class);
+            }
+            if let Some(exchange) = &exchange {
+                s.push('.');
+                s.push_str(exchange);
+            }
+            prop_assume!(s.len() <= MAX_LEN);
+            let t = TickerSymbol::new(s.to_ascii_lowercase()).unwrap();
+            prop_assert_eq!(t.as_str(), s.as_str());
+            prop_assert_eq!(t.root(), root.as_str());
+            prop_assert_eq!(t.share_class(), class.as_deref());
+            prop_assert_eq!(t.exchange(), exchange.as_deref());
+        }
     }
 }
@@ -0,0 +1,677 @@
+use crate::domain::SchemaTable;
+use crate::error::{ExplorerError, Result};
+use crate::util::json_number;
+use chrono::{Datelike, NaiveDate};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Days of transactions summed into each rolling flow row.
+pub const FLOW_WINDOW_DAYS: i64 = 90;
+/// Most rolling flow rows listed, one per month.
+pub const FLOW_PERIODS: usize = 12;
+/// Days within which purchases by several insiders form a cluster.
+pub const CLUSTER_WINDOW_DAYS: i64 = 30;
+/// Distinct insiders needed for a cluster buy.
+pub const CLUSTER_MIN_INSIDERS: usize = 3;
+
+/// An insider's role, from their title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    Ceo,
+    Cfo,
+    Director,
+    Officer,
+    Other,
+}
+
+impl Role {
+    /// Reads a filing title such as `President and CEO` or `EVP, Chief Financial Officer`.
+    /// A title naming several roles gets the first of CEO, CFO, director and officer.
+    #[must_use]
+    pub fn from_title(title: &str) -> Self {
+        let title = title.to_lowercase();
+        let words: Vec<&str> = title
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect();
+        let has = |word: &str| words.contains(&word);
+        if has("ceo") || title.contains("chief executive") {
+            Self::Ceo
+        } else if has("cfo") || title.contains("chief financial") {
+            Self::Cfo
+        } else if has("director") {
+            Self::Director
+        } else if [
+            "president",
+            "vp",
+            "evp",
+            "svp",
+            "officer",
+            "chief",
+            "chairman",
+            "treasurer",
+            "secretary",
+            "counsel",
+            "controller",
+        ]
+        .into_iter()
+        .any(has)
+            || title.contains("vice president")
+        {
+            Self::Officer
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ceo => "CEO",
+            Self::Cfo => "CFO",
+            Self::Director => "Director",
+            Self::Officer => "Other Officer",
+            Self::Other => "Other",
+        })
+    }
+}
+
+/// One item of an `INSIDER_TRANSACTIONS` response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsiderTransaction {
+    pub date: NaiveDate,
+    pub insider: String,
+    pub title: String,
+    pub security_type: String,
+    /// `A` (acquired) rather than `D` (disposed of)
+    pub acquisition: bool,
+    pub shares: f64,
+    /// 0 for grants and awards
+    pub price: f64,
+}
+
+impl InsiderTransaction {
+    #[must_use]
+    pub fn role(&self) -> Role {
+        Role::from_title(&self.title)
+    }
+
+    /// Shares acquired, negative for shares disposed of.
+    #[must_use]
+    pub fn net_shares(&self) -> f64 {
+        if self.acquisition {
+            self.shares
+        } else {
+            -self.shares
+        }
+    }
+
+    /// Dollar value of `net_shares`; awards at no price add nothing.
+    #[must_use]
+    pub fn net_value(&self) -> f64 {
+        self.net_shares() * self.price
+    }
+
+    /// Common (or ordinary) shares, as opposed to options, units, warrants and other
+    /// derivatives, whose exercise price is not an open-market price.
+    #[must_use]
+    pub fn is_common_stock(&self) -> bool {
+        let security = self.security_type.to_ascii_lowercase();
+        ["common", "ordinary"].iter().any(|s| security.contains(s))
+            && !["option", "unit", "warrant", "right"]
+                .iter()
+                .any(|s| security.contains(s))
+    }
+
+    /// A common stock acquisition paid for at a price, as opposed to a grant, an award
+    /// or a derivative exercise.
+    #[must_use]
+    pub fn is_purchase(&self) -> bool {
+        self.acquisition && self.price > 0.0 && self.is_common_stock()
+    }
+}
+
+/// Share and dollar flow of a group of transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Flow {
+    pub transactions: usize,
+    pub acquired: f64,
+    pub disposed: f64,
+    pub net_value: f64,
+}
+
+impl Flow {
+    fn add(&mut self, transaction: &InsiderTransaction) {
+        self.transactions += 1;
+        if transaction.acquisition {
+            self.acquired += transaction.shares;
+        } else {
+            self.disposed += transaction.shares;
+        }
+        self.net_value += transaction.net_value();
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.transactions += other.transactions;
+        self.acquired += other.acquired;
+        self.disposed += other.disposed;
+        self.net_value += other.net_value;
+    }
+
+    #[must_use]
+    pub fn net_shares(&self) -> f64 {
+        self.acquired - self.disposed
+    }
+}
+
+/// One insider's transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsiderSummary {
+    pub insider: String,
+    /// Role in the latest transaction
+    pub role: Role,
+    pub flow: Flow,
+    pub last_date: NaiveDate,
+}
+
+/// Purchases by several insiders within `CLUSTER_WINDOW_DAYS`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterBuy {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// In order of their first purchase
+    pub insiders: Vec<String>,
+    pub shares: f64,
+    pub value: f64,
+}
+
+/// An `INSIDER_TRANSACTIONS` response.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InsiderActivity {
+    /// Newest first
+    pub transactions: Vec<InsiderTransaction>,
+}
+
+impl InsiderActivity {
+    /// Parses an insider transactions response. A response without `data` gives no
+    /// transactions, and items without a date, insider, share count or a known
+    /// `acquisition_or_disposal` are skipped.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Parse` if `json` is not an object or `data` is not a list.
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let map = json.as_object().ok_or_else(|| {
+            ExplorerError::Parse("Insider transactions response is not an object".into())
+        })?;
+        let mut transactions: Vec<_> = match map.get("data") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items.iter().filter_map(parse_transaction).collect(),
+            Some(_) => {
+                return Err(ExplorerError::Parse(
+                    "Insider transactions data is not a list".into(),
+                ))
+            }
+        };
+        transactions.sort_by_key(|t| std::cmp::Reverse(t.date));
+        Ok(Self { transactions })
+    }
+
+    /// Common stock transactions, the only ones whose shares and dollars add up to a
+    /// flow.
+    fn common_stock(&self) -> impl Iterator<Item = &InsiderTransaction> {
+        self.transactions.iter().filter(|t| t.is_common_stock())
+    }
+
+    /// Common stock transactions per insider, largest net dollar flow (bought or sold)
+    /// first.
+    #[must_use]
+    pub fn by_insider(&self) -> Vec<InsiderSummary> {
+        let mut insiders: BTreeMap<&str, InsiderSummary> = BTreeMap::new();
+        for t in self.common_stock() {
+            insiders
+                .entry(&t.insider)
+                .or_insert_with(|| InsiderSummary {
+                    insider: t.insider.clone(),
+                    role: t.role(),
+                    flow: Flow::default(),
+                    last_date: t.date,
+                })
+                .flow
+                .add(t);
+        }
+        let mut insiders: Vec<_> = insiders.into_values().collect();
+        insiders.sort_by(|a, b| {
+            b.flow
+                .net_value
+                .abs()
+                .total_cmp(&a.flow.net_value.abs())
+                .then_with(|| a.insider.cmp(&b.insider))
+        });
+        insiders
+    }
+
+    /// Net common stock flow over the `window_days` up to the latest transaction and up
+    /// to each earlier month end, newest first, for at most `periods` rows.
+    #[must_use]
+    pub fn rolling_flow(&self, window_days: i64, periods: usize) -> Vec<(NaiveDate, Flow)> {
+        let (Some(latest), Some(earliest)) = (self.transactions.first(), self.transactions.last())
+        else {
+            return Vec::new();
+        };
+        let ends = std::iter::successors(Some(latest.date), |end| {
+            end.with_day(1)
+                .and_then(|first| first.pred_opt())
+                .filter(|prev| *prev >= earliest.date)
+        });
+        ends.take(periods)
+            .map(|end| {
+                let start = end - chrono::Duration::days(window_days);
+                let mut flow = Flow::default();
+                self.common_stock()
+                    .filter(|t| t.date > start && t.date <= end)
+                    .for_each(|t| flow.add(t));
+                (end, flow)
+            })
+            .collect()
+    }
+
+    /// Purchases by at least `min_insiders` insiders within `window_days` of the first,
+    /// oldest first. A cluster's purchases are not reused for the next one.
+    #[must_use]
+    pub fn cluster_buys(&self, window_days: i64, min_insiders: usize) -> Vec<ClusterBuy> {
+        let purchases: Vec<&InsiderTransaction> = self
+            .transactions
+            .iter()
+            .rev()
+            .filter(|t| t.is_purchase())
+            .collect();
+        let mut clusters = Vec::new();
+        let mut i = 0;
+        while let Some(first) = purchases.get(i) {
+            let last_date = first.date + chrono::Duration::days(window_days);
+            let window: Vec<_> = purchases
+                .iter()
+                .skip(i)
+                .take_while(|t| t.date <= last_date)
+                .collect();
+            let mut insiders: Vec<String> = Vec::new();
+            for t in &window {
+                if !insiders.contains(&t.insider) {
+                    insiders.push(t.insider.clone());
+                }
+            }
+            if insiders.len() < min_insiders.max(1) {
+                i += 1;
+                continue;
+            }
+            clusters.push(ClusterBuy {
+                start: first.date,
+                end: window.last().map_or(first.date, |t| t.date),
+                insiders,
+                shares: window.iter().map(|t| t.shares).sum(),
+                value: window.iter().map(|t| t.net_value()).sum(),
+            });
+            i += window.len();
+        }
+        clusters
+    }
+
+    /// Insiders and net flow per role, each insider counted under their latest role.
+    #[must_use]
+    pub fn roles(&self) -> Vec<(Role, usize, Flow)> {
+        let mut roles: BTreeMap<Role, (usize, Flow)> = BTreeMap::new();
+        for summary in self.by_insider() {
+            let (insiders, flow) = roles.entry(summary.role).or_default();
+            *insiders += 1;
+            flow.merge(&summary.flow);
+        }
+        roles
+            .into_iter()
+            .map(|(role, (insiders, flow))| (role, insiders, flow))
+            .collect()
+    }
+
+    /// The "Insider Activity" tables: net flow per insider, rolling flow, cluster buys
+    /// and roles. Tables without rows are left out.
+    #[must_use]
+    pub fn tables(&self) -> Vec<SchemaTable> {
+        let flow_cells = |flow: &Flow| {
+            vec![
+                flow.transactions.to_string(),
+                format!("{:.0}", flow.acquired),
+                format!("{:.0}", flow.disposed),
+                format!("{:.0}", flow.net_shares()),
+                format!("{:.0}", flow.net_value),
+            ]
+        };
+        let flow_headers = [
+            "Transactions",
+            "Acquired",
+            "Disposed",
+            "Net Shares",
+            "Net Value",
+        ];
+        let with_flow = |first: &[&str]| {
+            first
+                .iter()
+                .chain(&flow_headers)
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let insiders = self
+            .by_insider()
+            .into_iter()
+            .map(|s| {
+                let mut row = vec![s.insider.clone(), s.role.to_string()];
+                row.extend(flow_cells(&s.flow));
+                row.push(s.last_date.to_string());
+                row
+            })
+            .collect();
+        let mut insider_headers = with_flow(&["Insider", "Role"]);
+        insider_headers.push("Last".to_string());
+
+        let rolling = self
+            .rolling_flow(FLOW_WINDOW_DAYS, FLOW_PERIODS)
+            .iter()
+            .map(|(end, flow)| {
+                let mut row = vec![end.to_string()];
+                row.extend(flow_cells(flow));
+                row
+            })
+            .collect();
+
+        let clusters = self
+            .cluster_buys(CLUSTER_WINDOW_DAYS, CLUSTER_MIN_INSIDERS)
+            .into_iter()
+            .map(|c| {
+                vec![
+                    c.start.to_string(),
+                    c.end.to_string(),
+                    c.insiders.len().to_string(),
+                    c.insiders.join(", "),
+                    format!("{:.0}", c.shares),
+                    format!("{:.0}", c.value),
+                ]
+            })
+            .collect();
+
+        let roles = self
+            .roles()
+            .iter()
+            .map(|(role, insiders, flow)| {
+                let mut row = vec![role.to_string(), insiders.to_string()];
+                row.extend(flow_cells(flow));
+                row
+            })
+            .collect();
+
+        [
+            ("net by insider", insider_headers, insiders),
+            ("rolling 90-day flow", with_flow(&["As Of"]), rolling),
+            (
+                "cluster buys",
+                ["Start", "End", "Insiders", "Names", "Shares", "Value"]
+                    .map(String::from)
+                    .to_vec(),
+                clusters,
+            ),
+            ("roles", with_flow(&["Role", "Insiders"]), roles),
+        ]
+        .into_iter()
+        .filter(|(_, _, rows): &(_, _, Vec<Vec<String>>)| !rows.is_empty())
+        .map(|(what, headers, rows)| {
+            SchemaTable::new(format!("Insider Activity - {what}"), headers, rows)
+        })
+        .collect()
+    }
+}
+
+fn parse_transaction(item: &Value) -> Option<InsiderTransaction> {
+    let text = |key: &str| {
+        item.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let insider = text("executive");
+    if insider.is_empty() {
+        return None;
+    }
+    let acquisition = match text("acquisition_or_disposal").as_str() {
+        "A" | "a" => true,
+        "D" | "d" => false,
+        _ => return None,
+    };
+    Some(InsiderTransaction {
+        date: NaiveDate::parse_from_str(&text("transaction_date"), "%Y-%m-%d").ok()?,
+        insider,
+        title: text("executive_title"),
+        security_type: text("security_type"),
+        acquisition,
+        shares: json_number(item.get("shares"))?.abs(),
+        price: json_number(item.get("share_price")).unwrap_or(0.0).abs(),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn activity() -> InsiderActivity {
+        let tx = |date: &str, who: &str, title: &str, ad: &str, shares: &str, price: &str| {
+            json!({
+                "transaction_date": date,
+                "ticker": "MU",
+                "executive": who,
+                "executive_title": title,
+                "security_type": "Common Stock",
+                "acquisition_or_disposal": ad,
+                "shares": shares,
+                "share_price": price
+            })
+        };
+        InsiderActivity::from_json(&json!({"data": [
+            tx("2023-10-03", "BEYER RICHARD M", "Director", "A", "1000.0", "68.0"),
+            tx("2024-01-16", "MEHROTRA SANJAY", "President and CEO", "D", "35000.0", "86.75"),
+            tx("2024-01-11", "MURPHY MARK J", "EVP and Chief Financial Officer", "D", "15000.0", "84.2"),
+            tx("2023-12-20", "BEYER RICHARD M", "Director", "A", "5000.0", "79.1"),
+            tx("2023-12-14", "SWAN ROBERT H", "Director", "A", "2500.0", "76.4"),
+            tx("2023-12-08", "MEHROTRA SANJAY", "President and CEO", "A", "10000.0", "74.95"),
+            tx("2023-11-01", "MEHROTRA SANJAY", "President and CEO", "A", "20000.0", "0.0"),
+            tx("2023-11-01", "", "Director", "A", "1.0", "1.0"),
+            tx("2023-11-01", "NOBODY", "Director", "X", "1.0", "1.0")
+        ]}))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_roles_from_titles() {
+        assert_eq!(Role::from_title("President and CEO"), Role::Ceo);
+        assert_eq!(Role::from_title("Chief Executive Officer"), Role::Ceo);
+        assert_eq!(
+            Role::from_title("Senior Vice President, Chief Financial Officer"),
+            Role::Cfo
+        );
+        assert_eq!(Role::from_title("Director"), Role::Director);
+        assert_eq!(
+            Role::from_title("SVP, General Counsel and Secretary"),
+            Role::Officer
+        );
+        assert_eq!(Role::from_title("10 percent owner"), Role::Other);
+        // "cfo" is a word, not a substring
+        assert_eq!(Role::from_title("Scfo Holdings"), Role::Other);
+    }
+
+    #[test]
+    fn test_parse() {
+        let activity = activity();
+        assert_eq!(activity.transactions.len(), 7);
+        let latest = activity.transactions.first().unwrap();
+        assert_eq!(latest.date.to_string(), "2024-01-16");
+        assert!((latest.net_shares() + 35000.0).abs() < 1e-9);
+        // The award is an acquisition at no price, the oldest one a purchase
+        let award = activity.transactions.get(5).unwrap();
+        assert!(award.acquisition && !award.is_purchase());
+        assert!(activity.transactions.last().unwrap().is_purchase());
+
+        assert!(InsiderActivity::from_json(&json!({}))
+            .unwrap()
+            .transactions
+            .is_empty());
+        assert!(InsiderActivity::from_json(&json!({"data": {}})).is_err());
+        assert!(InsiderActivity::from_json(&json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_by_insider_and_roles() {
+        let insiders = activity().by_insider();
+        let names: Vec<&str> = insiders.iter().map(|s| s.insider.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "MEHROTRA SANJAY",
+                "MURPHY MARK J",
+                "BEYER RICHARD M",
+                "SWAN ROBERT H"
+            ]
+        );
+        let ceo = insiders.first().unwrap();
+        assert_eq!(ceo.role, Role::Ceo);
+        assert_eq!(ceo.flow.transactions, 3);
+        assert!((ceo.flow.net_shares() + 5000.0).abs() < 1e-9);
+        // The award adds shares but no dollars
+        assert!((ceo.flow.net_value - (749_500.0 - 3_036_250.0)).abs() < 1e-6);
+        assert_eq!(ceo.last_date.to_string(), "2024-01-16");
+
+        let roles = activity().roles();
+        let summary: Vec<(Role, usize, usize)> = roles
+            .iter()
+            .map(|(r, n, f)| (*r, *n, f.transactions))
+            .collect();
+        assert_eq!(
+            summary,
+            [(Role::Ceo, 1, 3), (Role::Cfo, 1, 1), (Role::Director, 2, 3)]
+        );
+    }
+
+    #[test]
+    fn test_rolling_flow() {
+        let flow = activity().rolling_flow(90, 12);
+        let ends: Vec<String> = flow.iter().map(|(end, _)| end.to_string()).collect();
+        assert_eq!(
+            ends,
+            ["2024-01-16", "2023-12-31", "2023-11-30", "2023-10-31"]
+        );
+        let (_, latest) = flow.first().unwrap();
+        assert_eq!(latest.transactions, 6);
+        assert!((latest.net_shares() + 12500.0).abs() < 1e-9);
+        assert!((latest.net_value + 2_963_250.0).abs() < 1e-6);
+
+        assert_eq!(activity().rolling_flow(90, 2).len(), 2);
+        assert!(InsiderActivity::default().rolling_flow(90, 12).is_empty());
+    }
+
+    #[test]
+    fn test_cluster_buys() {
+        let clusters = activity().cluster_buys(30, 3);
+        assert_eq!(clusters.len(), 1);
+        let cluster = clusters.first().unwrap();
+        assert_eq!(cluster.start.to_string(), "2023-12-08");
+        assert_eq!(cluster.end.to_string(), "2023-12-20");
+        assert_eq!(
+            cluster.insiders,
+            ["MEHROTRA SANJAY", "SWAN ROBERT H", "BEYER RICHARD M"]
+        );
+        assert!((cluster.shares - 17500.0).abs() < 1e-9);
+
+        assert!(activity().cluster_buys(5, 3).is_empty());
+        // Two insiders suffice, and the October purchase starts no cluster of its own
+        assert_eq!(activity().cluster_buys(30, 2).len(), 1);
+    }
+
+    #[test]
+    fn test_derivatives_are_not_flow() {
+        let tx = |who: &str, security: &str, shares: &str| {
+            json!({
+                "transaction_date": "2024-02-01",
+                "executive": who,
+                "executive_title": "Director",
+                "security_type": security,
+                "acquisition_or_disposal": "A",
+                "shares": shares,
+                "share_price": "20.0"
+            })
+        };
+        let activity = InsiderActivity::from_json(&json!({"data": [
+            tx("ONE", "Common Stock", "100"),
+            tx("TWO", "Class A Common Stock", "100"),
+            tx("THREE", "Employee Stock Option (Right to Buy)", "5000"),
+            tx("THREE", "Restricted Stock Unit", "5000"),
+            tx("FOUR", "Option to purchase Common Stock", "5000"),
+        ]}))
+        .unwrap();
+
+        // The option exercises are acquisitions at a price, but not purchases
+        let purchases = activity.transactions.iter().filter(|t| t.is_purchase());
+        assert_eq!(purchases.count(), 2);
+        assert!(activity.cluster_buys(30, 3).is_empty());
+        assert_eq!(activity.cluster_buys(30, 2).len(), 1);
+
+        let names: Vec<String> = activity
+            .by_insider()
+            .into_iter()
+            .map(|s| s.insider)
+            .collect();
+        assert_eq!(names, ["ONE", "TWO"]);
+        let (_, flow) = activity.rolling_flow(90, 1).pop().unwrap();
+        assert!((flow.net_shares() - 200.0).abs() < 1e-9);
+        assert!((flow.net_value - 4000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tables() {
+        let tables = activity().tables();
+        let titles: Vec<&str> = tables.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Insider Activity - net by insider",
+                "Insider Activity - rolling 90-day flow",
+                "Insider Activity - cluster buys",
+                "Insider Activity - roles"
+            ]
+        );
+        assert_eq!(
+            tables.first().unwrap().rows.first().unwrap(),
+            &[
+                "MEHROTRA SANJAY",
+                "CEO",
+                "3",
+                "30000",
+                "35000",
+                "-5000",
+                "-2286750",
+                "2024-01-16"
+            ]
+        );
+        assert_eq!(
+            tables.get(2).unwrap().rows.first().unwrap(),
+            &[
+                "2023-12-08",
+                "2023-12-20",
+                "3",
+                "MEHROTRA SANJAY, SWAN ROBERT H, BEYER RICHARD M",
+                "17500",
+                "1336000"
+            ]
+        );
+        assert!(InsiderActivity::default().tables().is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::domain::{EndpointName, SchemaTable};
 use crate::error::ExplorerError;
+use crate::logic::transcript::Transcript;
 use serde_json::Value;
 
 /// Parses a dynamic JSON response into a list of structured `SchemaTable`s.
@@ -28,7 +29,11 @@ pub fn parse_json_to_tables(
         | EndpointName::BalanceSheet
         | EndpointName::CashFlow
         | EndpointName::Earnings => parse_financials(endpoint, json, max_rows),
-        EndpointName::NewsSentiment => parse_news(endpoint, json, max_rows),
+        EndpointName::NewsSentiment => parse_list(endpoint, json, "feed", "Feed", max_rows),
+        EndpointName::InsiderTransactions => {
+            parse_list(endpoint, json, "data", "Transactions", max_rows)
+        }
+        EndpointName::EarningsCallTranscript => parse_transcript(endpoint, json, max_rows),
         EndpointName::TopGainersLosers => parse_top_movers(endpoint, json, max_rows),
         // Fallback for others or unimplemented
         _ => parse_generic(&endpoint.to_string(), json, max_rows),
@@ -78,10 +83,17 @@ fn parse_financials(
     tables
 }
 
-fn parse_news(endpoint: EndpointName, json: &Value, max_rows: Option<usize>) -> Vec<SchemaTable> {
+/// One table from the list under `key`, e.g. the news `feed`.
+fn parse_list(
+    endpoint: EndpointName,
+    json: &Value,
+    key: &str,
+    name: &str,
+    max_rows: Option<usize>,
+) -> Vec<SchemaTable> {
     if let Value::Object(map) = json {
-        if let Some(Value::Array(arr)) = map.get("feed") {
-            let title = format!("{endpoint} - Feed");
+        if let Some(Value::Array(arr)) = map.get(key) {
+            let title = format!("{endpoint} - {name}");
             if let Some(table) = parse_array_to_table(title, arr, max_rows) {
                 return vec![table];
             }
@@ -90,6 +102,39 @@ fn parse_news(endpoint: EndpointName, json: &Value, max_rows: Option<usize>) ->
     vec![]
 }
 
+fn parse_transcript(
+    endpoint: EndpointName,
+    json: &Value,
+    max_rows: Option<usize>,
+) -> Vec<SchemaTable> {
+    let transcript = match Transcript::from_json(json) {
+        Ok(t) if !t.turns.is_empty() => t,
+        _ => return parse_flat_map(&endpoint.to_string(), json),
+    };
+    let headers = ["section", "speaker", "title", "sentiment", "content"]
+        .map(String::from)
+        .to_vec();
+    let rows = transcript
+        .turns
+        .iter()
+        .enumerate()
+        .take(max_rows.unwrap_or(usize::MAX))
+        .map(|(i, turn)| {
+            vec![
+                transcript.section(i).to_string(),
+                turn.speaker.clone(),
+                turn.title.clone().unwrap_or_default(),
+                turn.sentiment
+                    .map_or_else(String::new, |s| format!("{s:.2}")),
+                turn.content.clone(),
+            ]
+        })
+        .collect();
+    let mut table = SchemaTable::new(format!("{endpoint} - transcript"), headers, rows);
+    table.total_records = transcript.turns.len();
+    vec![table]
+}
+
 fn parse_top_movers(
     endpoint: EndpointName,
     json: &Value,
@@ -222,6 +267,45 @@ mod tests {
         assert_eq!(table.headers, vec!["fiscalDateEnding", "totalRevenue"]);
     }
 
+    #[test]
+    fn test_parse_transcript() {
+        let json = json!({
+            "symbol": "MU",
+            "quarter": "2024Q1",
+            "transcript": [
+                {"speaker": "Sanjay Mehrotra", "title": "CEO", "content": "Record HBM.", "sentiment": "0.7"},
+                {"speaker": "Operator", "title": "Operator", "content": "First question, please."},
+                {"speaker": "Timothy Arcuri", "title": "Analyst, UBS", "content": "Pricing?", "sentiment": "0.1"}
+            ]
+        });
+        let tables =
+            parse_json_to_tables(EndpointName::EarningsCallTranscript, &json, Some(2)).unwrap();
+        let table = &tables[0];
+        assert_eq!(table.title, "EARNINGS_CALL_TRANSCRIPT - transcript");
+        assert_eq!(
+            table.headers,
+            vec!["section", "speaker", "title", "sentiment", "content"]
+        );
+        assert_eq!(table.total_records, 3);
+        assert_eq!(
+            table.rows[0],
+            vec![
+                "Prepared Remarks",
+                "Sanjay Mehrotra",
+                "CEO",
+                "0.70",
+                "Record HBM."
+            ]
+        );
+        assert_eq!(table.rows[1][0], "Q&A");
+
+        // No turns: the flat fields are still shown
+        let empty = json!({"symbol": "MU", "quarter": "1999Q1", "transcript": []});
+        let tables =
+            parse_json_to_tables(EndpointName::EarningsCallTranscript, &empty, None).unwrap();
+        assert_eq!(tables[0].title, "EARNINGS_CALL_TRANSCRIPT");
+    }
+
     #[test]
     fn test_missing_fields_in_row() {
         // Headers derived from first row
@@ -246,6 +330,23 @@ mod tests {
         assert_eq!(tables.len(), 3);
     }
 
+    #[test]
+    fn test_parse_insider_transactions() {
+        let row = json!({"executive": "A", "shares": "10.0", "transaction_date": "2024-01-02"});
+        let json = json!({"data": [row, row, row, row]});
+        let tables =
+            parse_json_to_tables(EndpointName::InsiderTransactions, &json, Some(3)).unwrap();
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.title, "INSIDER_TRANSACTIONS - Transactions");
+        assert_eq!(table.total_records, 4);
+        assert_eq!(table.rows.len(), 3);
+        assert_eq!(
+            table.headers,
+            vec!["executive", "shares", "transaction_date"]
+        );
+    }
+
     proptest! {
         #[test]
         fn doesnt_crash_on_random_json(s in "\\PC*") {
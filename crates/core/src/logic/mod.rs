@@ -1 +1,4 @@
+pub mod insider;
 pub mod json_to_table;
+pub mod news;
+pub mod transcript;
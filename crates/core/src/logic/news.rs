@@ -0,0 +1,563 @@
+use crate::domain::SchemaTable;
+use crate::error::{ExplorerError, Result};
+use crate::util::json_number;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Scores at or above this are bullish, at or below its negative bearish
+/// (the provider's `Somewhat-Bullish` and `Somewhat-Bearish` bounds).
+pub const SENTIMENT_THRESHOLD: f64 = 0.15;
+
+/// How an article scores one of the tickers it mentions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickerScore {
+    pub ticker: String,
+    /// 0 to 1, how much the article is about the ticker
+    pub relevance: f64,
+    /// -1 (bearish) to 1 (bullish)
+    pub score: f64,
+}
+
+/// One item of a `NEWS_SENTIMENT` feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Article {
+    pub title: String,
+    pub url: String,
+    pub source: String,
+    pub published: Option<NaiveDateTime>,
+    pub overall_score: Option<f64>,
+    /// Topic and relevance, e.g. `Technology` 1.0
+    pub topics: Vec<(String, f64)>,
+    pub tickers: Vec<TickerScore>,
+}
+
+impl Article {
+    /// The article's score for `ticker`, if it mentions it.
+    #[must_use]
+    pub fn score_for(&self, ticker: &str) -> Option<&TickerScore> {
+        self.tickers
+            .iter()
+            .find(|t| t.ticker.eq_ignore_ascii_case(ticker))
+    }
+}
+
+/// A `NEWS_SENTIMENT` response.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NewsFeed {
+    pub articles: Vec<Article>,
+}
+
+/// One day of a ticker's news sentiment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailySentiment {
+    pub date: NaiveDate,
+    pub articles: usize,
+    /// Mean score weighted by relevance
+    pub score: f64,
+    pub mean_relevance: f64,
+    pub bullish: usize,
+    pub bearish: usize,
+}
+
+/// Articles about a ticker grouped by topic or source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupSentiment {
+    pub name: String,
+    pub articles: usize,
+    /// Mean score weighted by relevance to the ticker
+    pub score: f64,
+    /// Mean relevance of the topic to the articles; 0 for sources
+    pub mean_relevance: f64,
+}
+
+impl NewsFeed {
+    /// Parses a news response. A response without a feed gives an empty feed, and
+    /// items without a title are skipped.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Parse` if `json` is not an object or `feed` is not a list.
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let map = json
+            .as_object()
+            .ok_or_else(|| ExplorerError::Parse("News response is not an object".into()))?;
+        let articles = match map.get("feed") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items.iter().filter_map(parse_article).collect(),
+            Some(_) => return Err(ExplorerError::Parse("News feed is not a list".into())),
+        };
+        Ok(Self { articles })
+    }
+
+    /// Articles mentioning `ticker` with its score.
+    fn about<'a>(
+        &'a self,
+        ticker: &'a str,
+    ) -> impl Iterator<Item = (&'a Article, &'a TickerScore)> {
+        self.articles
+            .iter()
+            .filter_map(move |a| a.score_for(ticker).map(|s| (a, s)))
+    }
+
+    /// Sentiment of the articles about `ticker` per publication day, newest first.
+    #[must_use]
+    pub fn daily_sentiment(&self, ticker: &str) -> Vec<DailySentiment> {
+        let mut days: BTreeMap<NaiveDate, Vec<&TickerScore>> = BTreeMap::new();
+        for (article, score) in self.about(ticker) {
+            if let Some(published) = article.published {
+                days.entry(published.date()).or_default().push(score);
+            }
+        }
+        days.into_iter()
+            .rev()
+            .map(|(date, scores)| DailySentiment {
+                date,
+                articles: scores.len(),
+                score: weighted_mean(scores.iter().map(|s| (s.score, s.relevance))),
+                mean_relevance: mean(scores.iter().map(|s| s.relevance)),
+                bullish: scores
+                    .iter()
+                    .filter(|s| s.score >= SENTIMENT_THRESHOLD)
+                    .count(),
+                bearish: scores
+                    .iter()
+                    .filter(|s| s.score <= -SENTIMENT_THRESHOLD)
+                    .count(),
+            })
+            .collect()
+    }
+
+    /// Topics of the articles about `ticker`, most articles first.
+    #[must_use]
+    pub fn topics(&self, ticker: &str) -> Vec<GroupSentiment> {
+        let mut topics: BTreeMap<&str, Vec<(f64, &TickerScore)>> = BTreeMap::new();
+        for (article, score) in self.about(ticker) {
+            for (topic, relevance) in &article.topics {
+                topics.entry(topic).or_default().push((*relevance, score));
+            }
+        }
+        sorted_groups(topics.into_iter().map(|(topic, entries)| GroupSentiment {
+            name: topic.to_string(),
+            articles: entries.len(),
+            score: weighted_mean(entries.iter().map(|(_, s)| (s.score, s.relevance))),
+            mean_relevance: mean(entries.iter().map(|(r, _)| *r)),
+        }))
+    }
+
+    /// Sources of the articles about `ticker`, most articles first.
+    #[must_use]
+    pub fn sources(&self, ticker: &str) -> Vec<GroupSentiment> {
+        let mut sources: BTreeMap<&str, Vec<&TickerScore>> = BTreeMap::new();
+        for (article, score) in self.about(ticker) {
+            sources.entry(&article.source).or_default().push(score);
+        }
+        sorted_groups(sources.into_iter().map(|(source, scores)| GroupSentiment {
+            name: source.to_string(),
+            articles: scores.len(),
+            score: weighted_mean(scores.iter().map(|s| (s.score, s.relevance))),
+            mean_relevance: 0.0,
+        }))
+    }
+
+    /// Up to `count` bullish articles about `ticker`, highest score first.
+    #[must_use]
+    pub fn most_bullish<'a>(
+        &'a self,
+        ticker: &'a str,
+        count: usize,
+    ) -> Vec<(&'a Article, &'a TickerScore)> {
+        let mut articles: Vec<_> = self
+            .about(ticker)
+            .filter(|(_, s)| s.score >= SENTIMENT_THRESHOLD)
+            .collect();
+        articles.sort_by(|(_, a), (_, b)| b.score.total_cmp(&a.score));
+        articles.truncate(count);
+        articles
+    }
+
+    /// Up to `count` bearish articles about `ticker`, lowest score first.
+    #[must_use]
+    pub fn most_bearish<'a>(
+        &'a self,
+        ticker: &'a str,
+        count: usize,
+    ) -> Vec<(&'a Article, &'a TickerScore)> {
+        let mut articles: Vec<_> = self
+            .about(ticker)
+            .filter(|(_, s)| s.score <= -SENTIMENT_THRESHOLD)
+            .collect();
+        articles.sort_by(|(_, a), (_, b)| a.score.total_cmp(&b.score));
+        articles.truncate(count);
+        articles
+    }
+
+    /// Daily sentiment, topic, source and top article tables for `ticker`. Tables
+    /// without rows are left out.
+    #[must_use]
+    pub fn ticker_tables(&self, ticker: &str, top: usize) -> Vec<SchemaTable> {
+        let title = |what: &str| format!("NEWS_SENTIMENT - {ticker} {what}");
+        let daily: Vec<Vec<String>> = self
+            .daily_sentiment(ticker)
+            .iter()
+            .map(|d| {
+                vec![
+                    d.date.to_string(),
+                    d.articles.to_string(),
+                    format!("{:.3}", d.score),
+                    sentiment_label(d.score).to_string(),
+                    format!("{:.2}", d.mean_relevance),
+                    d.bullish.to_string(),
+                    d.bearish.to_string(),
+                ]
+            })
+            .collect();
+        let group_rows = |groups: Vec<GroupSentiment>, relevance: bool| {
+            groups
+                .into_iter()
+                .map(|g| {
+                    let mut row = vec![g.name.clone(), g.articles.to_string()];
+                    if relevance {
+                        row.push(format!("{:.2}", g.mean_relevance));
+                    }
+                    row.push(format!("{:.3}", g.score));
+                    row
+                })
+                .collect()
+        };
+        let article_rows = |articles: Vec<(&Article, &TickerScore)>| {
+            articles
+                .into_iter()
+                .map(|(a, s)| {
+                    vec![
+                        a.published
+                            .map_or_else(String::new, |p| p.format("%Y-%m-%d %H:%M").to_string()),
+                        a.source.clone(),
+                        format!("{:.3}", s.score),
+                        format!("{:.2}", s.relevance),
+                        if a.url.is_empty() {
+                            a.title.clone()
+                        } else {
+                            format!("[{}]({})", a.title, link_target(&a.url))
+                        },
+                    ]
+                })
+                .collect()
+        };
+        let article_headers = ["Published", "Source", "Score", "Relevance", "Title"];
+
+        [
+            (
+                title("daily sentiment"),
+                &[
+                    "Date",
+                    "Articles",
+                    "Weighted Score",
+                    "Label",
+                    "Mean Relevance",
+                    "Bullish",
+                    "Bearish",
+                ][..],
+                daily,
+            ),
+            (
+                title("topics"),
+                &["Topic", "Articles", "Mean Relevance", "Weighted Score"][..],
+                group_rows(self.topics(ticker), true),
+            ),
+            (
+                title("sources"),
+                &["Source", "Articles", "Weighted Score"][..],
+                group_rows(self.sources(ticker), false),
+            ),
+            (
+                title("most bullish"),
+                &article_headers[..],
+                article_rows(self.most_bullish(ticker, top)),
+            ),
+            (
+                title("most bearish"),
+                &article_headers[..],
+                article_rows(self.most_bearish(ticker, top)),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, _, rows)| !rows.is_empty())
+        .map(|(title, headers, rows)| {
+            SchemaTable::new(
+                title,
+                headers.iter().map(ToString::to_string).collect(),
+                rows,
+            )
+        })
+        .collect()
+    }
+}
+
+/// The provider's label for a sentiment score.
+#[must_use]
+pub fn sentiment_label(score: f64) -> &'static str {
+    if score <= -0.35 {
+        "Bearish"
+    } else if score <= -SENTIMENT_THRESHOLD {
+        "Somewhat-Bearish"
+    } else if score < SENTIMENT_THRESHOLD {
+        "Neutral"
+    } else if score < 0.35 {
+        "Somewhat-Bullish"
+    } else {
+        "Bullish"
+    }
+}
+
+fn parse_article(item: &Value) -> Option<Article> {
+    let map = item.as_object()?;
+    let text = |key: &str| {
+        map.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let title = text("title");
+    if title.is_empty() {
+        return None;
+    }
+    let list = |key: &str| {
+        map.get(key)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    };
+    let topics = list("topics")
+        .iter()
+        .filter_map(|t| {
+            let topic = t.get("topic")?.as_str()?;
+            Some((
+                topic.to_string(),
+                json_number(t.get("relevance_score")).unwrap_or(0.0),
+            ))
+        })
+        .collect();
+    let tickers = list("ticker_sentiment")
+        .iter()
+        .filter_map(|t| {
+            Some(TickerScore {
+                ticker: t.get("ticker")?.as_str()?.to_string(),
+                relevance: json_number(t.get("relevance_score")).unwrap_or(0.0),
+                score: json_number(t.get("ticker_sentiment_score"))?,
+            })
+        })
+        .collect();
+    let published = map
+        .get("time_published")
+        .and_then(Value::as_str)
+        .and_then(|s| {
+            NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M"))
+                .ok()
+        });
+    Some(Article {
+        title,
+        url: text("url"),
+        source: text("source"),
+        published,
+        overall_score: json_number(map.get("overall_sentiment_score")),
+        topics,
+        tickers,
+    })
+}
+
+/// Mean of `(value, weight)` pairs by weight, or the plain mean if every weight is 0.
+fn weighted_mean(pairs: impl Iterator<Item = (f64, f64)> + Clone) -> f64 {
+    let total: f64 = pairs.clone().map(|(_, w)| w).sum();
+    if total > 0.0 {
+        pairs.map(|(v, w)| v * w).sum::<f64>() / total
+    } else {
+        mean(pairs.map(|(v, _)| v))
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0u32), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / f64::from(count)
+    }
+}
+
+fn sorted_groups(groups: impl Iterator<Item = GroupSentiment>) -> Vec<GroupSentiment> {
+    let mut groups: Vec<_> = groups.collect();
+    groups.sort_by(|a, b| {
+        b.articles
+            .cmp(&a.articles)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    groups
+}
+
+/// Percent-encodes the characters that would end a markdown link target or table cell.
+fn link_target(url: &str) -> String {
+    let mut out = String::with_capacity(url.len());
+    for c in url.chars() {
+        if matches!(c, '|' | '(' | ')' | '<' | '>' | '[' | ']' | '"' | '\\')
+            || c.is_whitespace()
+            || c.is_control()
+        {
+            let mut buf = [0u8; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                let _ = write!(out, "%{byte:02X}");
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    fn feed() -> NewsFeed {
+        let article = |title: &str, time: &str, source: &str, topics: Value, tickers: Value| {
+            json!({
+                "title": title,
+                "url": format!("https://example.com/{}", title.len()),
+                "time_published": time,
+                "source": source,
+                "overall_sentiment_score": 0.1,
+                "topics": topics,
+                "ticker_sentiment": tickers
+            })
+        };
+        NewsFeed::from_json(&json!({"items": "4", "feed": [
+            article("Record | quarter", "20240126T143000", "Bloomberg",
+                json!([{"topic": "Earnings", "relevance_score": "1.0"}, {"topic": "Technology", "relevance_score": "0.5"}]),
+                json!([{"ticker": "MU", "relevance_score": "0.9", "ticker_sentiment_score": "0.6"}])),
+            article("Pricing pressure", "20240126T090000", "Reuters",
+                json!([{"topic": "Technology", "relevance_score": "1.0"}]),
+                json!([{"ticker": "MU", "relevance_score": "0.3", "ticker_sentiment_score": "-0.4"},
+                       {"ticker": "NVDA", "relevance_score": "0.5", "ticker_sentiment_score": 0.2}])),
+            article("Capacity expands", "20240125T120000", "Bloomberg",
+                json!([]),
+                json!([{"ticker": "mu", "relevance_score": "0.8", "ticker_sentiment_score": "0.1"}])),
+            article("Not about MU", "20240125T120000", "CNBC",
+                json!([]),
+                json!([{"ticker": "NVDA", "relevance_score": "0.8", "ticker_sentiment_score": "0.5"}])),
+            {"url": "untitled"}
+        ]}))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let feed = feed();
+        assert_eq!(feed.articles.len(), 4);
+        let first = feed.articles.first().unwrap();
+        assert_eq!(first.published.unwrap().to_string(), "2024-01-26 14:30:00");
+        assert_eq!(first.topics.len(), 2);
+        assert_eq!(first.overall_score, Some(0.1));
+        let nvda = feed.articles.get(1).unwrap().score_for("NVDA").unwrap();
+        assert!((nvda.score - 0.2).abs() < 1e-9);
+
+        assert!(NewsFeed::from_json(&json!({"Information": "rate limit"}))
+            .unwrap()
+            .articles
+            .is_empty());
+        assert!(NewsFeed::from_json(&json!({"feed": "x"})).is_err());
+        assert!(NewsFeed::from_json(&json!([])).is_err());
+    }
+
+    #[test]
+    fn test_daily_sentiment() {
+        let daily = feed().daily_sentiment("MU");
+        assert_eq!(daily.len(), 2);
+        let latest = daily.first().unwrap();
+        assert_eq!(latest.date.to_string(), "2024-01-26");
+        assert_eq!((latest.articles, latest.bullish, latest.bearish), (2, 1, 1));
+        // (0.6 * 0.9 - 0.4 * 0.3) / 1.2
+        assert!((latest.score - 0.35).abs() < 1e-9);
+        assert!((latest.mean_relevance - 0.6).abs() < 1e-9);
+        assert_eq!(daily.get(1).unwrap().articles, 1);
+    }
+
+    #[test]
+    fn test_groups_and_top_articles() {
+        let feed = feed();
+        let topics = feed.topics("MU");
+        let names: Vec<&str> = topics.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["Technology", "Earnings"]);
+        assert!((topics.first().unwrap().mean_relevance - 0.75).abs() < 1e-9);
+
+        let sources = feed.sources("MU");
+        assert_eq!(sources.first().unwrap().name, "Bloomberg");
+        assert_eq!(sources.first().unwrap().articles, 2);
+
+        let bullish = feed.most_bullish("MU", 5);
+        assert_eq!(bullish.len(), 1);
+        assert_eq!(bullish.first().unwrap().0.title, "Record | quarter");
+        let bearish = feed.most_bearish("MU", 5);
+        assert_eq!(bearish.first().unwrap().0.title, "Pricing pressure");
+        assert!(feed.most_bearish("NVDA", 5).is_empty());
+    }
+
+    #[test]
+    fn test_ticker_tables() {
+        let tables = feed().ticker_tables("MU", 3);
+        let titles: Vec<&str> = tables.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "NEWS_SENTIMENT - MU daily sentiment",
+                "NEWS_SENTIMENT - MU topics",
+                "NEWS_SENTIMENT - MU sources",
+                "NEWS_SENTIMENT - MU most bullish",
+                "NEWS_SENTIMENT - MU most bearish"
+            ]
+        );
+        let daily = tables.first().unwrap();
+        assert_eq!(
+            daily.rows.first().unwrap(),
+            &["2024-01-26", "2", "0.350", "Bullish", "0.60", "1", "1"]
+        );
+        let bullish = tables.get(3).unwrap().rows.first().unwrap();
+        assert_eq!(
+            bullish.last().unwrap(),
+            "[Record | quarter](https://example.com/16)"
+        );
+
+        // No bearish NVDA articles, so no bearish table
+        let nvda = feed().ticker_tables("NVDA", 3);
+        assert_eq!(nvda.len(), 4);
+        assert!(feed().ticker_tables("IBM", 3).is_empty());
+    }
+
+    #[test]
+    fn test_link_target() {
+        assert_eq!(
+            link_target("https://example.com/a|b_(c) d"),
+            "https://example.com/a%7Cb_%28c%29%20d"
+        );
+        assert_eq!(
+            link_target("https://example.com/ok?q=1"),
+            "https://example.com/ok?q=1"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn weighted_mean_is_within_bounds(
+            pairs in proptest::collection::vec((-1.0f64..=1.0, 0.0f64..=1.0), 1..20)
+        ) {
+            let mean = weighted_mean(pairs.iter().copied());
+            let min = pairs.iter().map(|(v, _)| *v).fold(f64::INFINITY, f64::min);
+            let max = pairs.iter().map(|(v, _)| *v).fold(f64::NEG_INFINITY, f64::max);
+            prop_assert!(min - 1e-9 <= mean && mean <= max + 1e-9);
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use crate::error::{ExplorerError, Result};
+use crate::util::json_number;
+use serde_json::Value;
+use std::fmt;
+
+/// One speaker's turn in an earnings call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeakerTurn {
+    pub speaker: String,
+    /// Role, e.g. `CEO` or `Analyst, Goldman Sachs`
+    pub title: Option<String>,
+    pub content: String,
+    /// Provider sentiment score, -1 (bearish) to 1 (bullish)
+    pub sentiment: Option<f64>,
+}
+
+/// An `EARNINGS_CALL_TRANSCRIPT` response split into speaker turns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub symbol: String,
+    /// `YYYYQn` as reported by the provider
+    pub quarter: String,
+    pub turns: Vec<SpeakerTurn>,
+}
+
+/// The two parts of an earnings call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CallSection {
+    /// Operator introduction and management's scripted remarks
+    PreparedRemarks,
+    /// Analyst questions and management's answers
+    QuestionsAndAnswers,
+}
+
+impl fmt::Display for CallSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::PreparedRemarks => "Prepared Remarks",
+            Self::QuestionsAndAnswers => "Q&A",
+        })
+    }
+}
+
+/// Turns and words of one speaker over a call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeakerStats {
+    pub speaker: String,
+    pub title: Option<String>,
+    pub turns: usize,
+    pub words: usize,
+    /// Turns in the Q&A
+    pub qa_turns: usize,
+}
+
+/// Mentions of a term, split by call section.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeywordCount {
+    pub prepared: usize,
+    pub qa: usize,
+}
+
+impl KeywordCount {
+    #[must_use]
+    pub const fn total(self) -> usize {
+        self.prepared + self.qa
+    }
+}
+
+impl std::ops::AddAssign for KeywordCount {
+    fn add_assign(&mut self, other: Self) {
+        self.prepared += other.prepared;
+        self.qa += other.qa;
+    }
+}
+
+/// Sentiment statistics over the scored turns of a transcript.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SentimentSummary {
+    pub turns: usize,
+    /// Turns with a sentiment score
+    pub scored: usize,
+    pub mean: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Transcript {
+    /// Parses a transcript response. A response without turns (the provider's answer
+    /// for a quarter it has no call for) gives an empty transcript.
+    ///
+    /// # Errors
+    /// Returns `ExplorerError::Parse` if `json` is not an object or `transcript`
+    /// is neither a list of turns nor text.
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let map = json
+            .as_object()
+            .ok_or_else(|| ExplorerError::Parse("Transcript response is not an object".into()))?;
+        let text = |key: &str| map.get(key).and_then(Value::as_str).unwrap_or_default();
+        let turns = match map.get("transcript") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items.iter().filter_map(parse_turn).collect(),
+            // Older recordings hold the call as plain text
+            Some(Value::String(s)) => std::iter::once(s.trim())
+                .filter(|content| !content.is_empty())
+                .map(|content| SpeakerTurn {
+                    speaker: "Transcript".to_string(),
+                    title: None,
+                    content: content.to_string(),
+                    sentiment: None,
+                })
+                .collect(),
+            Some(_) => {
+                return Err(ExplorerError::Parse(
+                    "Transcript is neither a list of turns nor text".into(),
+                ))
+            }
+        };
+        Ok(Self {
+            symbol: text("symbol").to_string(),
+            quarter: text("quarter").to_string(),
+            turns,
+        })
+    }
+
+    /// Count, mean, minimum and maximum of the turns' sentiment scores.
+    #[must_use]
+    pub fn sentiment_summary(&self) -> SentimentSummary {
+        let scores: Vec<f64> = self.turns.iter().filter_map(|t| t.sentiment).collect();
+        #[allow(clippy::cast_precision_loss)] // turn counts are far below 2^52
+        let mean = (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64);
+        SentimentSummary {
+            turns: self.turns.len(),
+            scored: scores.len(),
+            mean,
+            min: scores.iter().copied().reduce(f64::min),
+            max: scores.iter().copied().reduce(f64::max),
+        }
+    }
+}
+
+/// Words an operator uses to open the Q&A, matched as whole words.
+const QA_TERMS: [&str; 4] = ["question", "questions", "q&a", "question-and-answer"];
+
+impl Transcript {
+    /// Index of the first Q&A turn, or the number of turns if the call has no Q&A.
+    ///
+    /// The Q&A starts at the operator turn announcing questions after management
+    /// has spoken, or else at the first analyst turn.
+    #[must_use]
+    pub fn qa_start(&self) -> usize {
+        let first_speaker = self.turns.iter().position(|t| !t.is_operator());
+        let announced = first_speaker.and_then(|first| {
+            self.turns
+                .iter()
+                .skip(first + 1)
+                .position(|t| {
+                    t.is_operator() && QA_TERMS.iter().any(|term| count_term(&t.content, term) > 0)
+                })
+                .map(|i| i + first + 1)
+        });
+        let analyst = self.turns.iter().position(SpeakerTurn::is_analyst);
+        match (announced, analyst) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b).unwrap_or(self.turns.len()),
+        }
+    }
+
+    /// The section of the turn at `index`.
+    #[must_use]
+    pub fn section(&self, index: usize) -> CallSection {
+        if index < self.qa_start() {
+            CallSection::PreparedRemarks
+        } else {
+            CallSection::QuestionsAndAnswers
+        }
+    }
+
+    /// Turns before the Q&A.
+    #[must_use]
+    pub fn prepared_remarks(&self) -> &[SpeakerTurn] {
+        self.turns.get(..self.qa_start()).unwrap_or_default()
+    }
+
+    /// Turns from the start of the Q&A on.
+    #[must_use]
+    pub fn questions_and_answers(&self) -> &[SpeakerTurn] {
+        self.turns.get(self.qa_start()..).unwrap_or_default()
+    }
+
+    /// Every speaker except the operator, in order of first appearance.
+    #[must_use]
+    pub fn speakers(&self) -> Vec<SpeakerStats> {
+        let qa_start = self.qa_start();
+        let mut stats: Vec<SpeakerStats> = Vec::new();
+        for (i, turn) in self.turns.iter().enumerate() {
+            if turn.is_operator() {
+                continue;
+            }
+            let index = stats
+                .iter()
+                .position(|s| s.speaker == turn.speaker)
+                .unwrap_or_else(|| {
+                    stats.push(SpeakerStats {
+                        speaker: turn.speaker.clone(),
+                        title: turn.title.clone(),
+                        turns: 0,
+                        words: 0,
+                        qa_turns: 0,
+                    });
+                    stats.len() - 1
+                });
+            if let Some(entry) = stats.get_mut(index) {
+                entry.turns += 1;
+                entry.words += turn.word_count();
+                entry.qa_turns += usize::from(i >= qa_start);
+            }
+        }
+        stats
+    }
+
+    /// Whole-word, case-insensitive mentions of `term` in each section.
+    #[must_use]
+    pub fn keyword_count(&self, term: &str) -> KeywordCount {
+        let count = |turns: &[SpeakerTurn]| {
+            turns
+                .iter()
+                .map(|t| count_term(&t.content, term))
+                .sum::<usize>()
+        };
+        KeywordCount {
+            prepared: count(self.prepared_remarks()),
+            qa: count(self.questions_and_answers()),
+        }
+    }
+}
+
+impl SpeakerTurn {
+    /// Whether the call operator is speaking.
+    #[must_use]
+    pub fn is_operator(&self) -> bool {
+        self.speaker.eq_ignore_ascii_case("operator")
+            || self
+                .title
+                .as_deref()
+                .is_some_and(|t| t.eq_ignore_ascii_case("operator"))
+    }
+
+    /// Whether a sell-side analyst is speaking, e.g. `Analyst, Goldman Sachs`.
+    #[must_use]
+    pub fn is_analyst(&self) -> bool {
+        self.title
+            .as_deref()
+            .is_some_and(|t| count_term(t, "analyst") > 0)
+    }
+
+    #[must_use]
+    pub fn word_count(&self) -> usize {
+        self.content.split_whitespace().count()
+    }
+}
+
+/// Case-insensitive mentions of `term` in `text` as a whole word or phrase, so
+/// `AI` matches "AI" and "ai-driven" but not "said" or "AIR".
+#[must_use]
+pub fn count_term(text: &str, term: &str) -> usize {
+    let term = term.trim().to_lowercase();
+    if term.is_empty() {
+        return 0;
+    }
+    let text = text.to_lowercase();
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    text.match_indices(&term)
+        .filter(|(start, matched)| {
+            let before = text.get(..*start).and_then(|s| s.chars().next_back());
+            let after = text
+                .get(start + matched.len()..)
+                .and_then(|s| s.chars().next());
+            !is_word(before) && !is_word(after)
+        })
+        .count()
+}
+
+fn parse_turn(item: &Value) -> Option<SpeakerTurn> {
+    let map = item.as_object()?;
+    let text = |key: &str| {
+        map.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+    let content = text("content")?;
+    // Scores come as strings ("0.6") but numbers are accepted too
+    let sentiment = json_number(map.get("sentiment"));
+    Some(SpeakerTurn {
+        speaker: text("speaker").unwrap_or("Unknown").to_string(),
+        title: text("title").map(str::to_string),
+        content: content.to_string(),
+        sentiment: sentiment.filter(|s: &f64| s.is_finite()),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_turns() {
+        let json = json!({
+            "symbol": "IBM",
+            "quarter": "2024Q1",
+            "transcript": [
+                {"speaker": "Arvind Krishna", "title": "CEO", "content": "Strong quarter.", "sentiment": "0.8"},
+                {"speaker": "Operator", "title": "", "content": "Next question.", "sentiment": 0.0},
+                {"speaker": "Analyst", "content": "   "},
+                {"speaker": "Jim Kavanaugh", "title": "CFO", "content": "Margins expanded.", "sentiment": "n/a"}
+            ]
+        });
+        let transcript = Transcript::from_json(&json).unwrap();
+        assert_eq!(transcript.symbol, "IBM");
+        assert_eq!(transcript.quarter, "2024Q1");
+        assert_eq!(transcript.turns.len(), 3);
+        let first = transcript.turns.first().unwrap();
+        assert_eq!(first.title.as_deref(), Some("CEO"));
+        assert_eq!(first.sentiment, Some(0.8));
+        assert_eq!(transcript.turns.get(1).unwrap().title, None);
+
+        let summary = transcript.sentiment_summary();
+        assert_eq!((summary.turns, summary.scored), (3, 2));
+        assert_eq!(summary.mean, Some(0.4));
+        assert_eq!((summary.min, summary.max), (Some(0.0), Some(0.8)));
+    }
+
+    #[test]
+    fn test_missing_and_legacy_transcripts() {
+        let empty = Transcript::from_json(&json!({"symbol": "IBM", "quarter": "1999Q1"})).unwrap();
+        assert!(empty.turns.is_empty());
+        assert_eq!(empty.sentiment_summary().mean, None);
+
+        let text =
+            Transcript::from_json(&json!({"transcript": "Tim Cook (CEO): Thanks."})).unwrap();
+        assert_eq!(text.turns.len(), 1);
+
+        assert!(Transcript::from_json(&json!([1, 2])).is_err());
+        assert!(Transcript::from_json(&json!({"transcript": 3})).is_err());
+    }
+
+    fn call() -> Transcript {
+        let turn = |speaker: &str, title: &str, content: &str| json!({"speaker": speaker, "title": title, "content": content});
+        Transcript::from_json(&json!({"transcript": [
+            turn("Operator", "Operator", "Welcome. There will be a question-and-answer session later."),
+            turn("Jensen Huang", "CEO", "AI demand is strong. Said plainly: AI, AI-driven and generative AI."),
+            turn("Colette Kress", "CFO", "Supply remains constrained; guidance assumes more supply."),
+            turn("Operator", "", "We will now begin the question-and-answer session."),
+            turn("Toshiya Hari", "Analyst, Goldman Sachs", "How does AI demand affect supply?"),
+            turn("Jensen Huang", "CEO", "Supply improves every quarter."),
+        ]}))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sections() {
+        let transcript = call();
+        assert_eq!(transcript.qa_start(), 3);
+        assert_eq!(transcript.prepared_remarks().len(), 3);
+        assert_eq!(transcript.questions_and_answers().len(), 3);
+        assert_eq!(transcript.section(2), CallSection::PreparedRemarks);
+        assert_eq!(transcript.section(4), CallSection::QuestionsAndAnswers);
+
+        // Without an announcement the first analyst opens the Q&A
+        let mut unannounced = transcript;
+        unannounced.turns.remove(3);
+        assert_eq!(unannounced.qa_start(), 3);
+        // Without either, the whole call is prepared remarks
+        unannounced.turns.truncate(3);
+        assert_eq!(unannounced.qa_start(), 3);
+        assert!(unannounced.questions_and_answers().is_empty());
+
+        // Other ways of announcing the Q&A
+        for announcement in [
+            "We will now take your questions.",
+            "Let's open the line for Q&A.",
+            "This concludes our remarks; the question-and-answer session begins.",
+        ] {
+            let mut transcript = call();
+            transcript.turns.get_mut(3).unwrap().content = announcement.to_string();
+            transcript.turns.remove(4);
+            assert_eq!(transcript.qa_start(), 3, "{announcement}");
+        }
+    }
+
+    #[test]
+    fn test_speakers_and_keywords() {
+        let transcript = call();
+        let speakers = transcript.speakers();
+        let names: Vec<&str> = speakers.iter().map(|s| s.speaker.as_str()).collect();
+        assert_eq!(names, ["Jensen Huang", "Colette Kress", "Toshiya Hari"]);
+        let jensen = speakers.first().unwrap();
+        assert_eq!((jensen.turns, jensen.qa_turns, jensen.words), (2, 1, 15));
+
+        assert_eq!(
+            transcript.keyword_count("ai"),
+            KeywordCount { prepared: 4, qa: 1 }
+        );
+        assert_eq!(transcript.keyword_count("Supply").total(), 4);
+        assert_eq!(transcript.keyword_count("generative AI").prepared, 1);
+        assert_eq!(count_term("AIR, aid and said", "AI"), 0);
+        assert_eq!(count_term("anything", " "), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn summary_bounds_hold(scores in proptest::collection::vec(-1.0f64..=1.0, 1..20)) {
+            let turns: Vec<Value> = scores
+                .iter()
+                .map(|s| json!({"speaker": "A", "content": "x", "sentiment": s.to_string()}))
+                .collect();
+            let summary = Transcript::from_json(&json!({"transcript": turns}))
+                .unwrap()
+                .sentiment_summary();
+            let (min, mean, max) = (summary.min.unwrap(), summary.mean.unwrap(), summary.max.unwrap());
+            prop_assert!(min <= mean + 1e-9 && mean <= max + 1e-9);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::domain::{ApiKey, EndpointName, TickerSymbol};
+use crate::domain::{ApiKey, EndpointName, KeyUsage, TickerSymbol};
 use crate::error::Result;
 use async_trait::async_trait;
 
@@ -22,4 +22,11 @@ pub trait ApiClient: Send + Sync {
         endpoint: EndpointName,
         api_key: &ApiKey,
     ) -> Result<serde_json::Value>;
+
+    /// Reports quota usage per API key for the calls made through this client.
+    ///
+    /// Clients that do not spend quota (e.g. mocks) report nothing.
+    fn key_usage(&self) -> Vec<KeyUsage> {
+        Vec::new()
+    }
 }
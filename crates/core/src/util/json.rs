@@ -0,0 +1,30 @@
+use serde_json::Value;
+
+/// A JSON number, or a string holding one (the API sends most numbers as strings,
+/// e.g. `"0.72"`). `None` for anything else, including non-finite values.
+#[must_use]
+pub fn json_number(value: Option<&Value>) -> Option<f64> {
+    let n = match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }?;
+    n.is_finite().then_some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_number() {
+        assert_eq!(json_number(Some(&json!("0.72"))), Some(0.72));
+        assert_eq!(json_number(Some(&json!(" 12 "))), Some(12.0));
+        assert_eq!(json_number(Some(&json!(-1.5))), Some(-1.5));
+        assert_eq!(json_number(Some(&json!("NaN"))), None);
+        assert_eq!(json_number(Some(&json!("n/a"))), None);
+        assert_eq!(json_number(Some(&json!(null))), None);
+        assert_eq!(json_number(None), None);
+    }
+}
@@ -0,0 +1,23 @@
+/// Escapes `|` and joins lines with a space so `cell` stays one cell of a markdown
+/// table row.
+#[must_use]
+pub fn escape_cell(cell: &str) -> String {
+    cell.replace("\r\n", " ")
+        .replace(['\r', '\n'], " ")
+        .replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_cell() {
+        assert_eq!(escape_cell("a | b"), "a \\| b");
+        assert_eq!(escape_cell("plain"), "plain");
+        assert_eq!(
+            escape_cell("first line\nsecond\r\nthird\rfourth"),
+            "first line second third fourth"
+        );
+    }
+}
@@ -1,3 +1,7 @@
+pub mod json;
+pub mod markdown;
 pub mod timestamp;
 
+pub use json::json_number;
+pub use markdown::escape_cell;
 pub use timestamp::{format_duration, generate_timestamp};
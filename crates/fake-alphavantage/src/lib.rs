@@ -0,0 +1,212 @@
+//! A local stand-in for the Alpha Vantage HTTP API.
+//!
+//! Serves the mock fixture tree at `/query?function=...&symbol=...&apikey=...`,
+//! enforces its own per-key quota and answers the way the real service does:
+//! errors and rate limits come back as `200 OK` with an `Error Message`, `Note`
+//! or `Information` body.
+
+pub mod quota;
+
+pub use quota::{Quota, QuotaExceeded, QuotaLimits};
+
+use alphavantage_core::domain::{EndpointName, TickerSymbol};
+use axum::extract::{Query, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+const MISSING_KEY: &str = "the parameter apikey is invalid or missing. Please claim your free API key on (https://www.alphavantage.co/support/#api-key). It should take less than 20 seconds.";
+const PREMIUM_URL: &str = "https://www.alphavantage.co/premium/";
+
+/// What the fake server serves and how strictly it limits callers.
+#[derive(Debug, Clone)]
+pub struct FakeConfig {
+    /// Directory in the mock fixture layout (`tickers/<SYM>/<FUNCTION>.json`,
+    /// `market/<FUNCTION>.json`)
+    pub fixtures: PathBuf,
+    pub limits: QuotaLimits,
+}
+
+impl Default for FakeConfig {
+    /// The client crate's fixtures under free-tier limits.
+    fn default() -> Self {
+        Self {
+            fixtures: default_fixtures_dir(),
+            limits: QuotaLimits::default(),
+        }
+    }
+}
+
+/// The fixtures bundled with the client crate.
+#[must_use]
+pub fn default_fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../client/fixtures")
+}
+
+struct AppState {
+    fixtures: PathBuf,
+    quota: Quota,
+    requests: AtomicUsize,
+}
+
+impl AppState {
+    fn new(config: FakeConfig) -> Arc<Self> {
+        Arc::new(Self {
+            fixtures: config.fixtures,
+            quota: Quota::new(config.limits),
+            requests: AtomicUsize::new(0),
+        })
+    }
+}
+
+/// The `/query` service for `config`.
+pub fn router(config: FakeConfig) -> Router {
+    router_with_state(AppState::new(config))
+}
+
+fn router_with_state(state: Arc<AppState>) -> Router {
+    Router::new().route("/query", get(query)).with_state(state)
+}
+
+/// A fake server running on a background task, stopped when dropped.
+pub struct FakeServer {
+    addr: SocketAddr,
+    state: Arc<AppState>,
+    task: JoinHandle<()>,
+}
+
+impl FakeServer {
+    /// Binds `addr` (use port 0 for any free port) and starts serving.
+    ///
+    /// # Errors
+    /// Returns error if the address cannot be bound.
+    pub async fn start(config: FakeConfig, addr: SocketAddr) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let state = AppState::new(config);
+        let app = router_with_state(Arc::clone(&state));
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("Fake server stopped: {}", e);
+            }
+        });
+        Ok(Self { addr, state, task })
+    }
+
+    /// Starts on a free port on localhost.
+    ///
+    /// # Errors
+    /// Returns error if no port can be bound.
+    pub async fn start_local(config: FakeConfig) -> std::io::Result<Self> {
+        Self::start(config, SocketAddr::from(([127, 0, 0, 1], 0))).await
+    }
+
+    #[must_use]
+    pub const fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// URL to pass to `AlphaVantageClient::with_base_url`.
+    #[must_use]
+    pub fn base_url(&self) -> String {
+        format!("http://{}/query", self.addr)
+    }
+
+    /// Requests received so far, including refused ones.
+    #[must_use]
+    pub fn request_count(&self) -> usize {
+        self.state.requests.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn query(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    state.requests.fetch_add(1, Ordering::SeqCst);
+    let function = params.get("function").map_or("", String::as_str);
+    let symbol = params.get("symbol").filter(|s| !s.is_empty());
+    tracing::info!("{} {}", function, symbol.map_or("-", String::as_str));
+
+    let Some(key) = params.get("apikey").filter(|k| !k.is_empty()) else {
+        return error_message(MISSING_KEY);
+    };
+    if EndpointName::from_str(function).is_err() {
+        return error_message(&format!("This API function ({function}) does not exist."));
+    }
+    match state.quota.acquire(key, Instant::now()) {
+        Ok(()) => {}
+        Err(QuotaExceeded::PerMinute) => return rate_limit_note(state.quota.limits()),
+        Err(QuotaExceeded::PerDay) => return daily_limit_information(state.quota.limits()),
+    }
+
+    let invalid_call = || {
+        error_message(&format!(
+            "Invalid API call. Please retry or visit the documentation (https://www.alphavantage.co/documentation/) for {function}."
+        ))
+    };
+    // Only well-formed symbols become a path, so `symbol=../..` cannot leave the fixtures
+    let path = match symbol.map(TickerSymbol::new).transpose() {
+        Ok(None) => state.fixtures.join("market"),
+        Ok(Some(s)) => state.fixtures.join("tickers").join(s.as_str()),
+        Err(_) => return invalid_call(),
+    };
+    let path = path.join(format!("{function}.json"));
+    let Ok(body) = tokio::fs::read_to_string(&path).await else {
+        return invalid_call();
+    };
+
+    // Recorded CSV endpoints are stored wrapped; serve them as the API does
+    if let Some(csv) = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|json| json.get("csv_content")?.as_str().map(str::to_string))
+    {
+        return ([(CONTENT_TYPE, "text/csv")], csv).into_response();
+    }
+    ([(CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+fn error_message(message: &str) -> Response {
+    Json(serde_json::json!({ "Error Message": message })).into_response()
+}
+
+fn rate_limit_note(limits: QuotaLimits) -> Response {
+    Json(serde_json::json!({
+        "Note": format!(
+            "Thank you for using Alpha Vantage! Our standard API call frequency is {} calls per minute and {} calls per day. Please visit {PREMIUM_URL} if you would like to target a higher API call volume.",
+            describe(limits.per_minute),
+            describe(limits.per_day),
+        )
+    }))
+    .into_response()
+}
+
+fn daily_limit_information(limits: QuotaLimits) -> Response {
+    Json(serde_json::json!({
+        "Information": format!(
+            "Thank you for using Alpha Vantage! Our standard API rate limit is {} requests per day. Please subscribe to any of the premium plans at {PREMIUM_URL} to instantly remove all daily rate limits.",
+            describe(limits.per_day),
+        )
+    }))
+    .into_response()
+}
+
+fn describe(limit: Option<u32>) -> String {
+    limit.map_or_else(|| "unlimited".to_string(), |n| n.to_string())
+}
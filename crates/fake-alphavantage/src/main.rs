@@ -0,0 +1,61 @@
+use clap::Parser;
+use fake_alphavantage::{default_fixtures_dir, router, FakeConfig, QuotaLimits};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process;
+use tokio::net::TcpListener;
+
+/// Serve the mock fixture tree over HTTP like the Alpha Vantage API
+#[derive(Parser, Debug)]
+#[command(name = "fake-alphavantage", version, about)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8099")]
+    bind: SocketAddr,
+
+    /// Fixture directory (`tickers/<SYM>/<FUNCTION>.json`, `market/<FUNCTION>.json`);
+    /// defaults to the client crate's fixtures
+    #[arg(long, value_name = "DIR")]
+    fixtures: Option<PathBuf>,
+
+    /// Requests per key per rolling minute (0 disables the limit)
+    #[arg(long, default_value_t = 5)]
+    per_minute: u32,
+
+    /// Requests per key per rolling 24 hours (0 disables the limit)
+    #[arg(long, default_value_t = 25)]
+    per_day: u32,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let config = FakeConfig {
+        fixtures: args.fixtures.unwrap_or_else(default_fixtures_dir),
+        limits: QuotaLimits {
+            per_minute: Some(args.per_minute).filter(|n| *n > 0),
+            per_day: Some(args.per_day).filter(|n| *n > 0),
+        },
+    };
+
+    let listener = match TcpListener::bind(args.bind).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("Failed to bind {}: {}", args.bind, e);
+            process::exit(1);
+        }
+    };
+    tracing::info!(
+        "Serving {} at http://{}/query",
+        config.fixtures.display(),
+        args.bind
+    );
+    if let Err(e) = axum::serve(listener, router(config)).await {
+        tracing::error!("Server error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const MINUTE: Duration = Duration::from_mins(1);
+const DAY: Duration = Duration::from_hours(24);
+
+/// Why a request was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaExceeded {
+    PerMinute,
+    PerDay,
+}
+
+/// Per-key request limits over rolling windows; `None` disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaLimits {
+    pub per_minute: Option<u32>,
+    pub per_day: Option<u32>,
+}
+
+impl Default for QuotaLimits {
+    /// The free tier: 5 calls per minute, 25 per day.
+    fn default() -> Self {
+        Self {
+            per_minute: Some(5),
+            per_day: Some(25),
+        }
+    }
+}
+
+impl QuotaLimits {
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self {
+            per_minute: None,
+            per_day: None,
+        }
+    }
+}
+
+/// Tracks accepted requests per API key.
+///
+/// Refused requests are not counted, so a client that backs off gets through once
+/// the window has moved on.
+#[derive(Debug, Default)]
+pub struct Quota {
+    limits: QuotaLimits,
+    calls: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl Quota {
+    #[must_use]
+    pub fn new(limits: QuotaLimits) -> Self {
+        Self {
+            limits,
+            calls: Mutex::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn limits(&self) -> QuotaLimits {
+        self.limits
+    }
+
+    /// Records a request for `key` at `now`, or reports the limit it would exceed.
+    ///
+    /// # Errors
+    /// Returns the exceeded limit; the daily one wins when both are reached.
+    pub fn acquire(&self, key: &str, now: Instant) -> Result<(), QuotaExceeded> {
+        let mut calls = self
+            .calls
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let history = calls.entry(key.to_string()).or_default();
+        while history
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) >= DAY)
+        {
+            history.pop_front();
+        }
+
+        let last_minute = history
+            .iter()
+            .filter(|t| now.saturating_duration_since(**t) < MINUTE)
+            .count();
+        if exceeds(self.limits.per_day, history.len()) {
+            return Err(QuotaExceeded::PerDay);
+        }
+        if exceeds(self.limits.per_minute, last_minute) {
+            return Err(QuotaExceeded::PerMinute);
+        }
+        history.push_back(now);
+        drop(calls);
+        Ok(())
+    }
+}
+
+fn exceeds(limit: Option<u32>, used: usize) -> bool {
+    limit.is_some_and(|limit| used >= usize::try_from(limit).unwrap_or(usize::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minute_and_day_windows() {
+        let quota = Quota::new(QuotaLimits {
+            per_minute: Some(2),
+            per_day: Some(3),
+        });
+        let start = Instant::now();
+
+        assert_eq!(quota.acquire("k", start), Ok(()));
+        assert_eq!(quota.acquire("k", start), Ok(()));
+        assert_eq!(quota.acquire("k", start), Err(QuotaExceeded::PerMinute));
+        // Other keys have their own budget
+        assert_eq!(quota.acquire("other", start), Ok(()));
+
+        let later = start + MINUTE;
+        assert_eq!(quota.acquire("k", later), Ok(()));
+        assert_eq!(quota.acquire("k", later), Err(QuotaExceeded::PerDay));
+        assert_eq!(quota.acquire("k", start + DAY), Ok(()));
+    }
+}
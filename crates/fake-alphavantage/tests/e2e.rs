@@ -0,0 +1,194 @@
+#![allow(clippy::unwrap_used)]
+
+use alphavantage_client::{AlphaVantageClient, RateLimiter};
+use alphavantage_core::domain::{ApiKey, EndpointName, TickerSymbol};
+use alphavantage_core::ports::ApiClient;
+use alphavantage_core::ExplorerError;
+use fake_alphavantage::{FakeConfig, FakeServer, QuotaLimits};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+/// A live client pointed at `server`, with its limiter state kept in `state`.
+fn client_for(server: &FakeServer, state: &TempDir) -> AlphaVantageClient {
+    let limiter = RateLimiter::with_path(state.path().join("tokens.json"), 1_000, 0);
+    AlphaVantageClient::with_base_url(server.base_url(), 1_000, 0)
+        .unwrap()
+        .with_rate_limiter(limiter)
+}
+
+#[tokio::test]
+async fn test_serves_fixtures_over_http() {
+    let server = FakeServer::start_local(FakeConfig {
+        limits: QuotaLimits::unlimited(),
+        ..FakeConfig::default()
+    })
+    .await
+    .unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let client = client_for(&server, &state);
+    let key = ApiKey::new("E2E_KEY");
+    let aapl = TickerSymbol::new("AAPL").unwrap();
+
+    let json = client
+        .fetch_ticker_endpoint(EndpointName::Overview, &aapl, None, &key)
+        .await
+        .unwrap();
+    assert_eq!(json.get("Symbol").unwrap(), "AAPL");
+
+    let params = HashMap::from([("horizon".to_string(), "3month".to_string())]);
+    let json = client
+        .fetch_ticker_endpoint(EndpointName::EarningsCalendar, &aapl, Some(&params), &key)
+        .await
+        .unwrap();
+    assert_eq!(json.get("symbol").unwrap(), "AAPL");
+
+    let json = client
+        .fetch_market_endpoint(EndpointName::TopGainersLosers, &key)
+        .await
+        .unwrap();
+    assert!(json.get("top_gainers").is_some());
+    assert_eq!(server.request_count(), 3);
+
+    // Unknown symbols come back as a 200 with an `Error Message`, which is not retried
+    let err = client
+        .fetch_ticker_endpoint(
+            EndpointName::Overview,
+            &TickerSymbol::new("ZZZZ").unwrap(),
+            None,
+            &key,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ExplorerError::ProviderError(msg) if msg.contains("Invalid API call")),
+        "{err}"
+    );
+    assert_eq!(server.request_count(), 4);
+}
+
+#[tokio::test]
+async fn test_serves_recorded_csv_as_text() {
+    let fixtures = tempfile::tempdir().unwrap();
+    let dir = fixtures.path().join("tickers/IBM");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("EARNINGS_CALENDAR.json"),
+        r#"{"csv_content": "symbol,reportDate\nIBM,2026-01-28\n"}"#,
+    )
+    .unwrap();
+
+    let server = FakeServer::start_local(FakeConfig {
+        fixtures: fixtures.path().to_path_buf(),
+        limits: QuotaLimits::unlimited(),
+    })
+    .await
+    .unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let json = client_for(&server, &state)
+        .fetch_ticker_endpoint(
+            EndpointName::EarningsCalendar,
+            &TickerSymbol::new("IBM").unwrap(),
+            None,
+            &ApiKey::new("E2E_KEY"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        json.get("csv_content").unwrap(),
+        "symbol,reportDate\nIBM,2026-01-28\n"
+    );
+}
+
+#[tokio::test]
+async fn test_minute_limit_is_rate_limited_after_retries() {
+    let server = FakeServer::start_local(FakeConfig {
+        limits: QuotaLimits {
+            per_minute: Some(2),
+            per_day: None,
+        },
+        ..FakeConfig::default()
+    })
+    .await
+    .unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let client = client_for(&server, &state);
+    let key = ApiKey::new("E2E_KEY");
+    let mu = TickerSymbol::new("MU").unwrap();
+
+    for endpoint in [EndpointName::Overview, EndpointName::Earnings] {
+        client
+            .fetch_ticker_endpoint(endpoint, &mu, None, &key)
+            .await
+            .unwrap();
+    }
+    let err = client
+        .fetch_ticker_endpoint(EndpointName::Splits, &mu, None, &key)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ExplorerError::RateLimited), "{err}");
+    // The call-volume `Note` is retried twice before giving up
+    assert_eq!(server.request_count(), 5);
+
+    // Another key has its own budget
+    client
+        .fetch_ticker_endpoint(EndpointName::Splits, &mu, None, &ApiKey::new("OTHER_KEY"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_daily_limit_returns_information_body() {
+    let server = FakeServer::start_local(FakeConfig {
+        limits: QuotaLimits {
+            per_minute: None,
+            per_day: Some(1),
+        },
+        ..FakeConfig::default()
+    })
+    .await
+    .unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let client = client_for(&server, &state);
+    let key = ApiKey::new("E2E_KEY");
+    let nvda = TickerSymbol::new("NVDA").unwrap();
+
+    client
+        .fetch_ticker_endpoint(EndpointName::Overview, &nvda, None, &key)
+        .await
+        .unwrap();
+    let json = client
+        .fetch_ticker_endpoint(EndpointName::Overview, &nvda, None, &key)
+        .await
+        .unwrap();
+    let info = json.get("Information").and_then(|v| v.as_str()).unwrap();
+    assert!(info.contains("1 requests per day"), "{info}");
+}
+
+#[tokio::test]
+async fn test_rejects_symbols_outside_fixtures() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("fixtures/tickers")).unwrap();
+    std::fs::write(dir.path().join("OVERVIEW.json"), r#"{"leaked": true}"#).unwrap();
+    let server = FakeServer::start_local(FakeConfig {
+        fixtures: dir.path().join("fixtures"),
+        limits: QuotaLimits::unlimited(),
+    })
+    .await
+    .unwrap();
+
+    // The client only sends valid symbols, so write the request by hand
+    let mut stream = tokio::net::TcpStream::connect(server.addr()).await.unwrap();
+    stream
+        .write_all(
+            b"GET /query?function=OVERVIEW&symbol=..%2F..&apikey=E2E_KEY HTTP/1.1\r\n\
+              Host: localhost\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.contains("Invalid API call"), "{response}");
+    assert!(!response.contains("leaked"), "{response}");
+}